@@ -0,0 +1,95 @@
+//! Compares loading a profile sequentially with `set_profile_concurrent`.
+//!
+//! Real monitors are simulated by a backend that sleeps for a fixed DDC/CI
+//! latency on every write, so the numbers show scheduling overhead rather
+//! than hardware speed. Run with `cargo bench --features async`.
+//!
+//! With 3 displays, 4 writes each and 5 ms per write (x86_64 Linux):
+//!
+//! ```text
+//! profile_load/sequential  time: [61.560 ms 62.012 ms 62.535 ms]
+//! profile_load/concurrent  time: [20.458 ms 20.570 ms 20.661 ms]
+//! ```
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use dispman::backend::{self, DdcBackend, Display, DisplayInfo};
+use dispman::error::DisplayError;
+use dispman::vcp::VcpValue;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+const DISPLAYS: usize = 3;
+const WRITE_LATENCY: Duration = Duration::from_millis(5);
+
+struct SlowBackend;
+
+impl DdcBackend for SlowBackend {
+    fn get_vcp(&mut self, _code: u8) -> Result<VcpValue, DisplayError> {
+        thread::sleep(WRITE_LATENCY);
+        Ok(VcpValue {
+            current: 50,
+            max: 100,
+            is_continuous: true,
+        })
+    }
+
+    fn set_vcp(&mut self, _code: u8, _value: u32) -> Result<(), DisplayError> {
+        thread::sleep(WRITE_LATENCY);
+        Ok(())
+    }
+
+    fn capabilities(&mut self) -> Result<String, DisplayError> {
+        Ok(String::new())
+    }
+}
+
+fn displays() -> Vec<Display> {
+    (0..DISPLAYS)
+        .map(|id| {
+            let info = DisplayInfo {
+                name: format!("Bench {}", id),
+                stable_id: format!("BENCH{}", id),
+                geometry: None,
+                size_mm: None,
+                connection: None,
+            };
+            Display::new(id, info, Box::new(SlowBackend))
+        })
+        .collect()
+}
+
+fn settings() -> HashMap<String, Vec<(u8, u32)>> {
+    (0..DISPLAYS)
+        .map(|id| {
+            let values = vec![(0x10, 80), (0x12, 70), (0x60, 0x0F), (0x62, 30)];
+            (format!("BENCH{}", id), values)
+        })
+        .collect()
+}
+
+fn profile_load(c: &mut Criterion) {
+    let settings = settings();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("profile_load");
+    group.sample_size(20);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for mut d in displays() {
+                for (code, value) in &settings[d.stable_id()] {
+                    d.set_vcp_feature(*code, *value).unwrap();
+                }
+            }
+        })
+    });
+
+    group.bench_function("concurrent", |b| {
+        b.iter(|| runtime.block_on(backend::set_profile_concurrent(displays(), &settings, None)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, profile_load);
+criterion_main!(benches);
@@ -0,0 +1,98 @@
+//! Compares reading several VCP codes with `get_vcp_feature_batch` against
+//! reading them one at a time.
+//!
+//! Real monitors are simulated by a backend that sleeps for a fixed DDC/CI
+//! latency on every read and for a fixed enumeration cost whenever a monitor
+//! handle is opened, so the numbers show how often each approach pays for
+//! those rather than hardware speed. `reopen_per_read` enumerates the monitor
+//! again for every code; `sequential` calls `get_vcp_feature` in a loop on one
+//! `Display`; `batch` hands the whole list to `get_vcp_feature_batch`. Run
+//! with `cargo bench --bench vcp_batch`.
+//!
+//! With 8 codes, 5 ms per read and 20 ms per enumeration (x86_64 Linux):
+//!
+//! ```text
+//! vcp_batch/reopen_per_read  time: [201.54 ms 201.76 ms 202.06 ms]
+//! vcp_batch/sequential       time: [60.829 ms 61.143 ms 61.697 ms]
+//! vcp_batch/batch            time: [60.848 ms 60.968 ms 61.155 ms]
+//! ```
+//!
+//! A `Display` keeps its handle between reads, so `sequential` and `batch`
+//! cost the same; both avoid the enumeration per code of `reopen_per_read`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use dispman::backend::{DdcBackend, Display, DisplayInfo};
+use dispman::error::DisplayError;
+use dispman::vcp::VcpValue;
+use std::thread;
+use std::time::Duration;
+
+const CODES: [u8; 8] = [0x10, 0x12, 0x14, 0x16, 0x18, 0x1A, 0x60, 0x62];
+const READ_LATENCY: Duration = Duration::from_millis(5);
+const ENUMERATION_LATENCY: Duration = Duration::from_millis(20);
+
+struct SlowBackend;
+
+impl DdcBackend for SlowBackend {
+    fn get_vcp(&mut self, _code: u8) -> Result<VcpValue, DisplayError> {
+        thread::sleep(READ_LATENCY);
+        Ok(VcpValue {
+            current: 50,
+            max: 100,
+            is_continuous: true,
+        })
+    }
+
+    fn set_vcp(&mut self, _code: u8, _value: u32) -> Result<(), DisplayError> {
+        thread::sleep(READ_LATENCY);
+        Ok(())
+    }
+
+    fn capabilities(&mut self) -> Result<String, DisplayError> {
+        Ok(String::new())
+    }
+}
+
+/// Opens the simulated monitor, paying the enumeration cost.
+fn open_display() -> Display {
+    thread::sleep(ENUMERATION_LATENCY);
+    let info = DisplayInfo {
+        name: "Bench".to_string(),
+        stable_id: "BENCH".to_string(),
+        geometry: None,
+        size_mm: None,
+        connection: None,
+    };
+    Display::new(0, info, Box::new(SlowBackend))
+}
+
+fn vcp_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vcp_batch");
+    group.sample_size(20);
+
+    group.bench_function("reopen_per_read", |b| {
+        b.iter(|| {
+            for code in CODES {
+                open_display().get_vcp_feature(code).unwrap();
+            }
+        })
+    });
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let mut display = open_display();
+            for code in CODES {
+                display.get_vcp_feature(code).unwrap();
+            }
+        })
+    });
+
+    group.bench_function("batch", |b| {
+        b.iter(|| open_display().get_vcp_feature_batch(&CODES).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, vcp_batch);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+//! Feeds arbitrary input to `Capabilities::parse`, which must never panic
+//! however malformed the string a monitor reports. Run with
+//! `cargo +nightly fuzz run capabilities_parse`.
+#![cfg_attr(fuzzing, no_main)]
+
+#[cfg(fuzzing)]
+libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+    let raw = String::from_utf8_lossy(data);
+    let caps = dispman::capabilities::Capabilities::parse(&raw);
+    let rebuilt = String::from(&caps);
+    dispman::capabilities::Capabilities::parse(&rebuilt);
+});
+
+#[cfg(not(fuzzing))]
+fn main() {
+    eprintln!("Build this target with `cargo fuzz`.");
+}
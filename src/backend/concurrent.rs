@@ -0,0 +1,96 @@
+//! Async wrappers around the blocking DDC/CI calls, available with the `async`
+//! feature. Each call runs on tokio's blocking pool, so commands sent to
+//! different monitors overlap instead of queueing behind each other.
+
+use super::Display;
+use crate::error::DisplayError;
+use crate::vcp::VcpValue;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tokio::task;
+
+impl Display {
+    /// Async counterpart of `get_vcp_feature`. The display is moved onto the
+    /// blocking pool for the duration of the call and handed back with the
+    /// result.
+    pub async fn get_vcp_feature_async(
+        mut self,
+        code: u8,
+    ) -> (Self, Result<VcpValue, DisplayError>) {
+        run_blocking(move || {
+            let result = self.get_vcp_feature(code);
+            (self, result)
+        })
+        .await
+    }
+
+    /// Async counterpart of `set_vcp_feature`. See `get_vcp_feature_async`.
+    pub async fn set_vcp_feature_async(
+        mut self,
+        code: u8,
+        value: u32,
+    ) -> (Self, Result<(), DisplayError>) {
+        run_blocking(move || {
+            let result = self.set_vcp_feature(code, value);
+            (self, result)
+        })
+        .await
+    }
+}
+
+/// Applies profile `settings` to every display at once, one task per display.
+/// Writes to the same display stay sequential since they share one bus, with
+/// `delay` between consecutive writes if given.
+///
+/// Returns one result per display, in the order given. A display without an
+/// entry in `settings` reports `Ok(())`. When a write fails the remaining
+/// writes for that display are still attempted and the first error is
+/// returned.
+pub async fn set_profile_concurrent(
+    displays: Vec<Display>,
+    settings: &HashMap<String, Vec<(u8, u32)>>,
+    delay: Option<Duration>,
+) -> Vec<Result<(), DisplayError>> {
+    let tasks: Vec<_> = displays
+        .into_iter()
+        .map(|mut display| {
+            let values = settings
+                .get(display.stable_id())
+                .cloned()
+                .unwrap_or_default();
+            task::spawn_blocking(move || {
+                let mut first_error = None;
+                for (i, (code, value)) in values.into_iter().enumerate() {
+                    if let Some(delay) = delay.filter(|_| i > 0) {
+                        thread::sleep(delay);
+                    }
+                    if let Err(e) = display.set_vcp_feature(code, value) {
+                        first_error.get_or_insert(e);
+                    }
+                }
+                first_error.map_or(Ok(()), Err)
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for handle in tasks {
+        results.push(join(handle.await));
+    }
+    results
+}
+
+async fn run_blocking<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    join(task::spawn_blocking(f).await)
+}
+
+/// Blocking tasks cannot be cancelled, so the only way a join fails is a
+/// panic inside the DDC call; re-raise it on the caller's side.
+fn join<T>(result: Result<T, task::JoinError>) -> T {
+    result.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
@@ -1,5 +1,6 @@
 use super::{DdcBackend, Display, DisplayInfo};
 use crate::error::DisplayError;
+use crate::vcp::VcpValue;
 use ddc::Ddc;
 use ddc_macos::Monitor;
 use std::collections::hash_map::DefaultHasher;
@@ -9,11 +10,21 @@ struct MacOsBackend {
     monitor: Monitor,
 }
 
+// SAFETY: `Monitor` holds IOKit service references, which are Mach port names
+// valid from any thread in the task. The backend is owned by a single
+// `Display` and only used through `&mut self`, so calls never overlap.
+unsafe impl Send for MacOsBackend {}
+
 impl DdcBackend for MacOsBackend {
-    fn get_vcp(&mut self, code: u8) -> Result<u32, DisplayError> {
+    fn get_vcp(&mut self, code: u8) -> Result<VcpValue, DisplayError> {
         self.monitor
             .get_vcp_feature(code)
-            .map(|v| u32::from(v.value()))
+            .map(|v| VcpValue {
+                current: u32::from(v.value()),
+                max: u32::from(v.maximum()),
+                // MCCS reply type byte: 0x00 = set parameter, 0x01 = momentary.
+                is_continuous: v.ty == 0,
+            })
             .map_err(|e| {
                 DisplayError::DdcCommunicationFailed(format!(
                     "get_vcp_feature(0x{:02X}) failed: {}",
@@ -38,6 +49,12 @@ impl DdcBackend for MacOsBackend {
         })
     }
 
+    fn edid(&mut self) -> Result<Vec<u8>, DisplayError> {
+        self.monitor.edid().ok_or_else(|| {
+            DisplayError::FeatureNotSupported("Monitor did not report an EDID".to_string())
+        })
+    }
+
     fn capabilities(&mut self) -> Result<String, DisplayError> {
         let bytes = self.monitor.capabilities_string().map_err(|e| {
             DisplayError::DdcCommunicationFailed(format!("capabilities_string failed: {}", e))
@@ -65,7 +82,14 @@ pub fn enumerate() -> Result<Vec<Display>, DisplayError> {
             .product_name()
             .unwrap_or_else(|| monitor.description());
         let stable_id = derive_stable_id(&mut monitor, &name);
-        let info = DisplayInfo { name, stable_id };
+        // ddc-macos does not expose the display bounds.
+        let info = DisplayInfo {
+            name,
+            stable_id,
+            geometry: None,
+            size_mm: None,
+            connection: None,
+        };
         let backend = MacOsBackend { monitor };
         displays.push(Display::new(id, info, Box::new(backend)));
     }
@@ -0,0 +1,140 @@
+use super::{DdcBackend, Display, DisplayInfo};
+use crate::error::DisplayError;
+use crate::vcp::VcpValue;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// What a `MockDdcBackend` holds and has been asked to do. Shared between
+/// the backend boxed inside a `Display` and the test that created it.
+#[derive(Debug, Default)]
+pub struct MockState {
+    pub values: HashMap<u8, VcpValue>,
+    pub capabilities: Option<String>,
+    /// Number of upcoming calls that fail with `DdcCommunicationFailed`.
+    pub failures: u32,
+    /// Acknowledge writes without changing the stored value, like monitors
+    /// that ignore some commands.
+    pub ignore_writes: bool,
+    /// Every call that reached the backend, failed or not.
+    pub calls: usize,
+    pub capability_fetches: usize,
+    /// Values written, in order.
+    pub writes: Vec<(u8, u32)>,
+}
+
+/// A `DdcBackend` that answers from memory, for unit tests. Codes without a
+/// stored value fail with `FeatureNotSupported`, which is not retried.
+#[derive(Debug, Clone, Default)]
+pub struct MockDdcBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockDdcBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores a continuous value for `code`.
+    pub fn with_value(self, code: u8, current: u32, max: u32) -> Self {
+        self.with_vcp(
+            code,
+            VcpValue {
+                current,
+                max,
+                is_continuous: true,
+            },
+        )
+    }
+
+    pub fn with_vcp(self, code: u8, value: VcpValue) -> Self {
+        self.state().values.insert(code, value);
+        self
+    }
+
+    pub fn with_capabilities(self, raw: &str) -> Self {
+        self.state().capabilities = Some(raw.to_string());
+        self
+    }
+
+    /// Makes the next `count` calls fail with `DdcCommunicationFailed`.
+    pub fn failing(self, count: u32) -> Self {
+        self.state().failures = count;
+        self
+    }
+
+    pub fn ignoring_writes(self) -> Self {
+        self.state().ignore_writes = true;
+        self
+    }
+
+    /// A `Display` backed by a handle to this mock. Retries are immediate.
+    pub fn display(&self, id: usize, name: &str, stable_id: &str) -> Display {
+        let info = DisplayInfo {
+            name: name.to_string(),
+            stable_id: stable_id.to_string(),
+            geometry: None,
+            size_mm: None,
+            connection: None,
+        };
+        let mut display = Display::new(id, info, Box::new(self.clone()));
+        display.set_ddc_options(super::DdcOptions {
+            retry_delay_ms: 0,
+            ..Default::default()
+        });
+        display
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Counts the call, and fails it if failures are still pending.
+    fn begin_call(&self) -> Result<MutexGuard<'_, MockState>, DisplayError> {
+        let mut state = self.state();
+        state.calls += 1;
+        if state.failures > 0 {
+            state.failures -= 1;
+            return Err(DisplayError::DdcCommunicationFailed(
+                "mock failure".to_string(),
+            ));
+        }
+        Ok(state)
+    }
+}
+
+fn unsupported(code: u8) -> DisplayError {
+    DisplayError::FeatureNotSupported(format!("mock has no value for 0x{:02X}", code))
+}
+
+impl DdcBackend for MockDdcBackend {
+    fn get_vcp(&mut self, code: u8) -> Result<VcpValue, DisplayError> {
+        let state = self.begin_call()?;
+        state
+            .values
+            .get(&code)
+            .copied()
+            .ok_or_else(|| unsupported(code))
+    }
+
+    fn set_vcp(&mut self, code: u8, value: u32) -> Result<(), DisplayError> {
+        let mut state = self.begin_call()?;
+        let ignore_writes = state.ignore_writes;
+        let stored = state
+            .values
+            .get_mut(&code)
+            .ok_or_else(|| unsupported(code))?;
+        if !ignore_writes {
+            stored.current = value;
+        }
+        state.writes.push((code, value));
+        Ok(())
+    }
+
+    fn capabilities(&mut self) -> Result<String, DisplayError> {
+        let mut state = self.begin_call()?;
+        state.capability_fetches += 1;
+        state.capabilities.clone().ok_or_else(|| {
+            DisplayError::FeatureNotSupported("mock has no capabilities string".to_string())
+        })
+    }
+}
@@ -1,8 +1,22 @@
+use crate::capabilities::Capabilities;
+use crate::edid::EdidInfo;
 use crate::error::DisplayError;
-use serde::Serialize;
+use crate::vcp::{ColorPreset, InputSource, PicturePreset, PowerMode, VcpFeature, VcpValue};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "async")]
+pub use concurrent::set_profile_concurrent;
+
+#[cfg(feature = "async")]
+mod concurrent;
 #[cfg(target_os = "macos")]
 pub mod macos;
+#[cfg(test)]
+pub(crate) mod mock;
 #[cfg(target_os = "windows")]
 pub mod windows;
 
@@ -10,17 +24,211 @@ pub mod windows;
 pub struct DisplayInfo {
     pub name: String,
     pub stable_id: String,
+    /// Position in the virtual desktop, when the platform reports it.
+    pub geometry: Option<MonitorGeometry>,
+    /// Physical width and height in millimetres, when the driver reports
+    /// them.
+    pub size_mm: Option<(u32, u32)>,
+    /// How the monitor is cabled, when the platform reports it.
+    pub connection: Option<ConnectionType>,
+}
+
+/// The kind of link between the graphics adapter and the monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConnectionType {
+    Hdmi,
+    DisplayPort,
+    Dvi,
+    Vga,
+    /// DisplayPort tunnelled over USB-C / USB4.
+    UsbC,
+    Unknown,
+}
+
+impl std::fmt::Display for ConnectionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ConnectionType::Hdmi => "HDMI",
+            ConnectionType::DisplayPort => "DisplayPort",
+            ConnectionType::Dvi => "DVI",
+            ConnectionType::Vga => "VGA",
+            ConnectionType::UsbC => "USB-C",
+            ConnectionType::Unknown => "unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Where a monitor sits in the virtual desktop, in desktop pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MonitorGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub is_primary: bool,
+}
+
+impl std::fmt::Display for MonitorGeometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "@ ({}, {}) {}×{}", self.x, self.y, self.width, self.height)?;
+        if self.is_primary {
+            write!(f, " [primary]")?;
+        }
+        Ok(())
+    }
+}
+
+/// How long `set --verify` waits after writing before reading the value
+/// back, unless `--verify-delay-ms` says otherwise.
+pub const DEFAULT_VERIFY_DELAY_MS: u64 = 50;
+
+/// Controls how DDC/CI calls are retried. Monitors behind docks and KVM
+/// switches often fail the first command after a state change, so failed
+/// calls are retried `retry_count` times, `retry_delay_ms` apart.
+#[derive(Debug, Clone, Copy)]
+pub struct DdcOptions {
+    pub retry_count: u8,
+    pub retry_delay_ms: u64,
+    /// Log each failed attempt to stderr.
+    pub verbose: bool,
+    /// Reuse the capabilities string after the first fetch instead of
+    /// asking the monitor again.
+    pub cache_capabilities: bool,
+    /// Enumerate with `enumerate_with_retry`, for monitors that are slow to
+    /// reappear after the system wakes up.
+    pub retry_on_empty: bool,
+}
+
+impl Default for DdcOptions {
+    fn default() -> Self {
+        Self {
+            retry_count: 3,
+            retry_delay_ms: 50,
+            verbose: false,
+            cache_capabilities: true,
+            retry_on_empty: false,
+        }
+    }
+}
+
+/// The MCCS timing report: the sync frequencies of the signal the monitor is
+/// currently receiving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimingReport {
+    pub horizontal_freq_hz: u32,
+    pub vertical_freq_hz: u32,
+    /// Raw MCCS status byte (sync polarity, out-of-range and unstable
+    /// flags).
+    pub timing_status_code: u8,
+}
+
+impl std::fmt::Display for TimingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} kHz horizontal, {} Hz vertical",
+            f64::from(self.horizontal_freq_hz) / 1000.0,
+            self.vertical_freq_hz
+        )
+    }
+}
+
+/// Every feature read by `Display::get_all_vcp_features`. Saved as JSON by
+/// `inspect --snapshot` and read back by `profile save --from-snapshot`,
+/// with codes written as `"0xNN"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VcpSnapshot {
+    /// Missing from snapshots taken by older versions.
+    #[serde(default)]
+    pub display_name: String,
+    /// Missing from snapshots taken by older versions.
+    #[serde(default)]
+    pub stable_id: String,
+    #[serde(serialize_with = "hex_keys", deserialize_with = "from_hex_keys")]
+    pub values: HashMap<u8, VcpValue>,
+    /// Codes listed in the capabilities string that could not be read.
+    #[serde(serialize_with = "hex_codes", deserialize_with = "from_hex_codes")]
+    pub failed_codes: Vec<u8>,
+}
+
+fn hex_keys<S: Serializer>(
+    values: &HashMap<u8, VcpValue>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    // Zero-padded hex keys sort in code order.
+    let sorted: BTreeMap<String, &VcpValue> = values
+        .iter()
+        .map(|(code, value)| (format!("0x{:02X}", code), value))
+        .collect();
+    sorted.serialize(serializer)
+}
+
+fn hex_codes<S: Serializer>(codes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(codes.iter().map(|code| format!("0x{:02X}", code)))
+}
+
+fn parse_hex_code<E: de::Error>(s: &str) -> Result<u8, E> {
+    u8::from_str_radix(s.trim_start_matches("0x"), 16)
+        .map_err(|_| E::custom(format!("invalid VCP code `{}`", s)))
+}
+
+fn from_hex_keys<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<u8, VcpValue>, D::Error> {
+    HashMap::<String, VcpValue>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(code, value)| Ok((parse_hex_code(&code)?, value)))
+        .collect()
+}
+
+fn from_hex_codes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|code| parse_hex_code(code))
+        .collect()
+}
+
+/// A value written by `Display::clone_settings_to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingChange {
+    pub code: u8,
+    pub before: u32,
+    pub after: u32,
 }
 
 pub struct Display {
     pub id: usize,
     pub info: DisplayInfo,
+    options: DdcOptions,
     inner: Box<dyn DdcBackend>,
+    /// Raw capabilities string, kept after the first fetch.
+    capabilities: OnceLock<String>,
 }
 
 impl Display {
+    /// The backend is boxed rather than behind an `Arc`: each `Display` has
+    /// the monitor to itself, and DDC/CI requests to one monitor must not
+    /// interleave.
     pub fn new(id: usize, info: DisplayInfo, inner: Box<dyn DdcBackend>) -> Self {
-        Self { id, info, inner }
+        Self {
+            id,
+            info,
+            options: DdcOptions::default(),
+            inner,
+            capabilities: OnceLock::new(),
+        }
+    }
+
+    /// Seeds the capabilities cache with a string fetched earlier, so
+    /// `capabilities` does not have to ask the monitor again.
+    pub fn with_cached_capabilities(self, raw: String) -> Self {
+        let _ = self.capabilities.set(raw);
+        self
+    }
+
+    pub fn set_ddc_options(&mut self, options: DdcOptions) {
+        self.options = options;
     }
 
     pub fn name(&self) -> &str {
@@ -31,16 +239,365 @@ impl Display {
         &self.info.stable_id
     }
 
-    pub fn get_vcp_feature(&mut self, code: u8) -> Result<u32, DisplayError> {
-        self.inner.get_vcp(code)
+    pub fn geometry(&self) -> Option<MonitorGeometry> {
+        self.info.geometry
+    }
+
+    pub fn size_mm(&self) -> Option<(u32, u32)> {
+        self.info.size_mm
+    }
+
+    pub fn connection(&self) -> Option<ConnectionType> {
+        self.info.connection
+    }
+
+    /// Pixels per inch along the diagonal, from the physical size and the
+    /// native resolution in the EDID, rounded to one decimal. `None` if
+    /// either is unknown.
+    pub fn ppi(&mut self) -> Option<f64> {
+        let (width_mm, height_mm) = self.size_mm()?;
+        let edid = self.edid_info().ok()?;
+        if edid.native_width == 0 || edid.native_height == 0 {
+            return None;
+        }
+        let diagonal_px = f64::from(edid.native_width).hypot(f64::from(edid.native_height));
+        let diagonal_in = f64::from(width_mm).hypot(f64::from(height_mm)) / 25.4;
+        Some((diagonal_px / diagonal_in * 10.0).round() / 10.0)
+    }
+
+    /// Case-insensitive substring match against the display name.
+    pub fn name_matches(&self, pattern: &str) -> bool {
+        self.name().to_lowercase().contains(&pattern.to_lowercase())
+    }
+
+    /// Case-insensitive substring match against the model reported in the
+    /// monitor's capabilities string. Fetching capabilities is slow.
+    pub fn model_matches(&mut self, pattern: &str) -> Result<bool, DisplayError> {
+        let caps = self.capabilities_parsed()?;
+        Ok(caps
+            .model
+            .is_some_and(|model| model.to_lowercase().contains(&pattern.to_lowercase())))
+    }
+
+    pub fn get_vcp_feature(&mut self, code: u8) -> Result<VcpValue, DisplayError> {
+        self.with_retry(|inner| inner.get_vcp(code))
+    }
+
+    /// Like `get_vcp_feature`, but gives up once `timeout` has passed
+    /// instead of retrying further. A DDC/CI call already in progress
+    /// cannot be interrupted, so the read may overrun by one call.
+    pub fn get_vcp_feature_timeout(
+        &mut self,
+        code: u8,
+        timeout: Duration,
+    ) -> Result<VcpValue, DisplayError> {
+        let deadline = Instant::now() + timeout;
+        self.with_retry_until(Some(deadline), |inner| inner.get_vcp(code))
     }
 
     pub fn set_vcp_feature(&mut self, code: u8, value: u32) -> Result<(), DisplayError> {
-        self.inner.set_vcp(code, value)
+        self.with_retry(|inner| inner.set_vcp(code, value))
+    }
+
+    /// Writes `value` unless the monitor already reports it. Returns whether
+    /// a write was made.
+    pub fn set_vcp_feature_if_changed(
+        &mut self,
+        code: u8,
+        value: u32,
+    ) -> Result<bool, DisplayError> {
+        if self.get_vcp_feature(code)?.current == value {
+            return Ok(false);
+        }
+        self.set_vcp_feature(code, value)?;
+        Ok(true)
+    }
+
+    /// Writes `value`, waits `settle` for the monitor to apply it, and reads
+    /// it back. Some monitors acknowledge a write they then ignore, so the
+    /// read-back must be within `tolerance` of `value`. Returns the value
+    /// the monitor reports, which may differ from `value` if it clamps.
+    pub fn set_vcp_feature_checked(
+        &mut self,
+        code: u8,
+        value: u32,
+        tolerance: u32,
+        settle: Duration,
+    ) -> Result<u32, DisplayError> {
+        self.set_vcp_feature(code, value)?;
+        thread::sleep(settle);
+        self.verify_vcp_value(code, value, tolerance)
+    }
+
+    /// Reads `code` and fails with `VerificationFailed` unless it is within
+    /// `tolerance` of `expected`. Returns the value read.
+    pub fn verify_vcp_value(
+        &mut self,
+        code: u8,
+        expected: u32,
+        tolerance: u32,
+    ) -> Result<u32, DisplayError> {
+        let actual = self.get_vcp_feature(code)?.current;
+        if actual.abs_diff(expected) > tolerance {
+            return Err(DisplayError::VerificationFailed { expected, actual });
+        }
+        Ok(actual)
     }
 
+    /// Like `set_vcp_feature`, but first reads the feature's maximum and
+    /// refuses to send values above it.
+    pub fn set_vcp_feature_validated(&mut self, code: u8, value: u32) -> Result<(), DisplayError> {
+        self.validate_vcp_value(code, value)?;
+        self.set_vcp_feature(code, value)
+    }
+
+    /// Checks `value` against the maximum the monitor reports for `code`.
+    /// Features reporting a maximum of zero have no usable range and are not
+    /// checked.
+    pub fn validate_vcp_value(&mut self, code: u8, value: u32) -> Result<(), DisplayError> {
+        let max = self.get_vcp_feature(code)?.max;
+        if max != 0 && value > max {
+            return Err(DisplayError::InvalidValue { code, value, max });
+        }
+        Ok(())
+    }
+
+    /// The monitor's capabilities string. Fetching it takes two slow DDC/CI
+    /// round trips, so the result is cached for the lifetime of the
+    /// `Display` unless `DdcOptions::cache_capabilities` is off.
     pub fn capabilities(&mut self) -> Result<String, DisplayError> {
-        self.inner.capabilities()
+        if self.options.cache_capabilities
+            && let Some(raw) = self.capabilities.get()
+        {
+            return Ok(raw.clone());
+        }
+        let raw = self.with_retry(|inner| inner.capabilities())?;
+        if self.options.cache_capabilities {
+            let _ = self.capabilities.set(raw.clone());
+        }
+        Ok(raw)
+    }
+
+    pub fn capabilities_parsed(&mut self) -> Result<Capabilities, DisplayError> {
+        self.capabilities().map(|raw| Capabilities::parse(&raw))
+    }
+
+    /// Reads every VCP code listed in the (cached) capabilities string, in
+    /// code order. Codes the monitor fails to answer are collected in
+    /// `failed_codes` instead of failing the whole read.
+    pub fn get_all_vcp_features(&mut self) -> Result<VcpSnapshot, DisplayError> {
+        let mut codes: Vec<u8> = self
+            .capabilities_parsed()?
+            .vcp_features
+            .into_keys()
+            .collect();
+        codes.sort_unstable();
+        // A monitor that answers none of the codes still gets a snapshot,
+        // with every code listed as failed.
+        let values = self.get_vcp_feature_batch(&codes).unwrap_or_default();
+        let failed_codes = codes
+            .into_iter()
+            .filter(|code| !values.contains_key(code))
+            .collect();
+        Ok(VcpSnapshot {
+            display_name: self.name().to_string(),
+            stable_id: self.stable_id().to_string(),
+            values,
+            failed_codes,
+        })
+    }
+
+    /// Reads `codes` one after another over the same monitor handle, without
+    /// re-enumerating in between. DDC/CI has no multi-code read; the saving
+    /// is in not reopening the monitor for each code. A code the monitor
+    /// fails to answer is left out of the map rather than failing the
+    /// batch. The batch only fails, with the last error, if none of `codes`
+    /// could be read.
+    pub fn get_vcp_feature_batch(
+        &mut self,
+        codes: &[u8],
+    ) -> Result<HashMap<u8, VcpValue>, DisplayError> {
+        let mut values = HashMap::new();
+        let mut last_error = None;
+        for &code in codes {
+            match self.get_vcp_feature(code) {
+                Ok(value) => {
+                    values.insert(code, value);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(e) if values.is_empty() => Err(e),
+            _ => Ok(values),
+        }
+    }
+
+    /// Whether the monitor lists `feature` in its capabilities string.
+    pub fn supports_feature(&mut self, feature: VcpFeature) -> Result<bool, DisplayError> {
+        Ok(self.capabilities_parsed()?.is_feature_supported(feature))
+    }
+
+    /// Copies the value of each VCP code in `codes` from this display to
+    /// `target`. When the two monitors report different maximums for a
+    /// continuous feature, the value is scaled to the same fraction of the
+    /// target's range. Returns what changed on `target`, in `codes` order.
+    pub fn clone_settings_to(
+        &mut self,
+        target: &mut Display,
+        codes: &[u8],
+    ) -> Result<Vec<SettingChange>, DisplayError> {
+        let mut changes = Vec::with_capacity(codes.len());
+        for &code in codes {
+            let source = self.get_vcp_feature(code)?;
+            let before = target.get_vcp_feature(code)?;
+            let after = if source.is_continuous && source.max != 0 && before.max != source.max {
+                let scaled = (u64::from(source.current) * u64::from(before.max)
+                    + u64::from(source.max) / 2)
+                    / u64::from(source.max);
+                scaled as u32
+            } else {
+                source.current
+            };
+            if after != before.current {
+                target.set_vcp_feature(code, after)?;
+            }
+            changes.push(SettingChange {
+                code,
+                before: before.current,
+                after,
+            });
+        }
+        Ok(changes)
+    }
+
+    pub fn edid(&mut self) -> Result<Vec<u8>, DisplayError> {
+        self.inner.edid()
+    }
+
+    pub fn timing_report(&mut self) -> Result<TimingReport, DisplayError> {
+        self.with_retry(|inner| inner.timing_report())
+    }
+
+    pub fn edid_info(&mut self) -> Result<EdidInfo, DisplayError> {
+        EdidInfo::parse(&self.edid()?)
+    }
+
+    /// Checks that the monitor answers DDC/CI by reading brightness (VCP
+    /// 0x10). Some monitors behind passive adapters enumerate fine but fail
+    /// every DDC/CI command. Failed attempts are retried per `DdcOptions`,
+    /// which gives a monitor that is still waking up time to respond.
+    pub fn is_ddc_capable(&mut self) -> bool {
+        self.get_vcp_feature(0x10).is_ok()
+    }
+
+    /// The current input and the one `input next` (or, if `forward` is
+    /// false, `input prev`) switches to; see `InputSource::cycle`. Nothing
+    /// is written.
+    pub fn adjacent_input(
+        &mut self,
+        forward: bool,
+    ) -> Result<(InputSource, InputSource), DisplayError> {
+        let current = self.get_vcp_feature(0x60)?.current as u16;
+        let caps = self.capabilities_parsed()?;
+        let next = InputSource::cycle(&caps, current, forward).ok_or_else(|| {
+            DisplayError::FeatureNotSupported(format!(
+                "Display {} lists no input sources in its capabilities",
+                self.id
+            ))
+        })?;
+        Ok((InputSource::from_value(current), next))
+    }
+
+    pub fn get_power_mode(&mut self) -> Result<PowerMode, DisplayError> {
+        let value = self.get_vcp_feature(0xD6)?;
+        Ok(PowerMode::from_value(value.current as u16))
+    }
+
+    pub fn set_power_mode(&mut self, mode: PowerMode) -> Result<(), DisplayError> {
+        self.set_vcp_feature(0xD6, u32::from(mode.value()))
+    }
+
+    pub fn get_color_preset(&mut self) -> Result<ColorPreset, DisplayError> {
+        let value = self.get_vcp_feature(0x14)?;
+        Ok(ColorPreset::from_value(value.current as u16))
+    }
+
+    pub fn set_color_preset(&mut self, preset: ColorPreset) -> Result<(), DisplayError> {
+        self.set_vcp_feature(0x14, u32::from(preset.value()))
+    }
+
+    pub fn get_picture_preset(&mut self) -> Result<PicturePreset, DisplayError> {
+        let value = self.get_vcp_feature(0xDC)?;
+        Ok(PicturePreset::from_value(value.current as u16))
+    }
+
+    pub fn set_picture_preset(&mut self, preset: PicturePreset) -> Result<(), DisplayError> {
+        self.set_vcp_feature(0xDC, u32::from(preset.value()))
+    }
+
+    /// Asks the monitor to persist its current settings (VCP 0xB0, "Store
+    /// current settings").
+    pub fn save_current_settings(&mut self) -> Result<(), DisplayError> {
+        self.set_vcp_feature(0xB0, 0x01)
+    }
+
+    /// Restores all factory defaults (VCP 0x04). Any non-zero value triggers
+    /// the reset.
+    pub fn restore_factory_defaults(&mut self) -> Result<(), DisplayError> {
+        self.set_vcp_feature(0x04, 0x01)
+    }
+
+    /// Restores the factory color defaults only (VCP 0x08).
+    pub fn restore_factory_color_defaults(&mut self) -> Result<(), DisplayError> {
+        self.set_vcp_feature(0x08, 0x01)
+    }
+
+    /// Runs `op` against the backend, retrying DDC/CI communication failures
+    /// according to this display's `DdcOptions`. Other errors are returned
+    /// immediately since retrying them cannot succeed.
+    fn with_retry<T>(
+        &mut self,
+        op: impl FnMut(&mut dyn DdcBackend) -> Result<T, DisplayError>,
+    ) -> Result<T, DisplayError> {
+        self.with_retry_until(None, op)
+    }
+
+    /// Like `with_retry`, but no retry is started after `deadline`.
+    fn with_retry_until<T>(
+        &mut self,
+        deadline: Option<Instant>,
+        mut op: impl FnMut(&mut dyn DdcBackend) -> Result<T, DisplayError>,
+    ) -> Result<T, DisplayError> {
+        let options = self.options;
+        let mut attempt: u8 = 0;
+        loop {
+            match op(self.inner.as_mut()) {
+                Err(e @ DisplayError::DdcCommunicationFailed(_)) => {
+                    if deadline.is_some_and(|d| Instant::now() >= d) {
+                        return Err(e);
+                    }
+                    if attempt == options.retry_count {
+                        if attempt == 0 {
+                            return Err(e);
+                        }
+                        return Err(DisplayError::RetryExhausted {
+                            retries: attempt,
+                            source: Box::new(e),
+                        });
+                    }
+                    attempt += 1;
+                    if options.verbose {
+                        eprintln!(
+                            "Display {}: {}; retrying ({}/{})",
+                            self.id, e, attempt, options.retry_count
+                        );
+                    }
+                    thread::sleep(Duration::from_millis(options.retry_delay_ms));
+                }
+                result => return result,
+            }
+        }
     }
 }
 
@@ -50,10 +607,11 @@ impl Serialize for Display {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("Display", 3)?;
+        let mut state = serializer.serialize_struct("Display", 4)?;
         state.serialize_field("id", &self.id)?;
         state.serialize_field("name", &self.info.name)?;
         state.serialize_field("stable_id", &self.info.stable_id)?;
+        state.serialize_field("geometry", &self.info.geometry)?;
         state.end()
     }
 }
@@ -67,10 +625,43 @@ impl std::fmt::Debug for Display {
     }
 }
 
-pub trait DdcBackend {
-    fn get_vcp(&mut self, code: u8) -> Result<u32, DisplayError>;
+/// Backends must be `Send` so a `Display` can be handed to a worker thread
+/// (see the `async` feature). They need not be `Sync`: a backend owns its
+/// monitor handle, and every method takes `&mut self`, so it is never shared
+/// between threads, only moved.
+pub trait DdcBackend: Send {
+    fn get_vcp(&mut self, code: u8) -> Result<VcpValue, DisplayError>;
     fn set_vcp(&mut self, code: u8, value: u32) -> Result<(), DisplayError>;
     fn capabilities(&mut self) -> Result<String, DisplayError>;
+
+    /// Raw EDID as reported by the operating system. EDID is read from the
+    /// OS rather than over DDC/CI, so it is not retried.
+    fn edid(&mut self) -> Result<Vec<u8>, DisplayError> {
+        Err(DisplayError::FeatureNotSupported(
+            "EDID is not available on this platform".to_string(),
+        ))
+    }
+
+    /// The signal timing the monitor reports for its current input.
+    fn timing_report(&mut self) -> Result<TimingReport, DisplayError> {
+        Err(DisplayError::FeatureNotSupported(
+            "Timing reports are not available on this platform".to_string(),
+        ))
+    }
+}
+
+/// Returns the first display whose name contains `name`, ignoring case.
+pub fn find_by_name<'a>(displays: &'a mut [Display], name: &str) -> Option<&'a mut Display> {
+    displays.iter_mut().find(|d| d.name_matches(name))
+}
+
+/// Returns the first display whose capabilities report a model containing
+/// `model`, ignoring case. Displays whose capabilities cannot be read are
+/// skipped.
+pub fn find_by_model<'a>(displays: &'a mut [Display], model: &str) -> Option<&'a mut Display> {
+    displays
+        .iter_mut()
+        .find_map(|d| d.model_matches(model).unwrap_or(false).then_some(d))
 }
 
 pub fn enumerate() -> Result<Vec<Display>, DisplayError> {
@@ -87,3 +678,493 @@ pub fn enumerate() -> Result<Vec<Display>, DisplayError> {
         Err(DisplayError::UnsupportedPlatform)
     }
 }
+
+/// Attempts `enumerate_with_retry` makes by default.
+pub const ENUMERATE_RETRY_ATTEMPTS: u8 = 5;
+
+/// Wait before the first retry of `enumerate_with_retry`, doubled after each
+/// attempt.
+pub const ENUMERATE_RETRY_DELAY_MS: u64 = 200;
+
+/// Like `enumerate`, but enumerates again while no display is found or none
+/// of those found answers a DDC/CI ping. Shortly after the system resumes
+/// from sleep, Windows may report no monitors, or monitors whose handles do
+/// not work yet. Up to `max_attempts` enumerations are made, waiting
+/// `delay_ms` before the first retry and twice as long before each further
+/// one. `options` are applied to each display before it is pinged.
+///
+/// Displays that do not answer are still returned: once any display
+/// answers, the others are taken to lack DDC/CI rather than to be waking up.
+pub fn enumerate_with_retry(
+    max_attempts: u8,
+    delay_ms: u64,
+    options: &DdcOptions,
+) -> Result<Vec<Display>, DisplayError> {
+    retry_enumeration(max_attempts, delay_ms, options, enumerate)
+}
+
+fn retry_enumeration(
+    max_attempts: u8,
+    delay_ms: u64,
+    options: &DdcOptions,
+    mut enumerate: impl FnMut() -> Result<Vec<Display>, DisplayError>,
+) -> Result<Vec<Display>, DisplayError> {
+    let max_attempts = max_attempts.max(1);
+    let mut delay = Duration::from_millis(delay_ms);
+    let mut unanswered = Vec::new();
+    for attempt in 1..=max_attempts {
+        match enumerate() {
+            Ok(mut displays) => {
+                for d in displays.iter_mut() {
+                    d.set_ddc_options(*options);
+                }
+                if displays.iter_mut().any(|d| d.is_ddc_capable()) {
+                    return Ok(displays);
+                }
+                unanswered = displays;
+            }
+            Err(DisplayError::MonitorNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        if attempt < max_attempts {
+            thread::sleep(delay);
+            delay *= 2;
+        }
+    }
+    if unanswered.is_empty() {
+        return Err(DisplayError::MonitorNotFound(format!(
+            "no display found after {} attempt(s)",
+            max_attempts
+        )));
+    }
+    Ok(unanswered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockDdcBackend;
+    use super::*;
+
+    const CAPS: &str =
+        "(prot(monitor)type(lcd)model(U2722D)cmds(01 02 03)vcp(10 12 60(0F 11) D6)mccs_ver(2.1))";
+
+    fn with_retries(mut display: Display, retry_count: u8) -> Display {
+        display.set_ddc_options(DdcOptions {
+            retry_count,
+            retry_delay_ms: 0,
+            ..Default::default()
+        });
+        display
+    }
+
+    #[test]
+    fn get_vcp_feature_reads_backend_value() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100);
+        let mut display = mock.display(0, "Dell U2722D", "dell");
+        let value = display.get_vcp_feature(0x10).unwrap();
+        assert_eq!((value.current, value.max), (40, 100));
+        assert!(value.is_continuous);
+    }
+
+    #[test]
+    fn set_vcp_feature_writes_to_backend() {
+        let mock = MockDdcBackend::new().with_value(0x12, 50, 100);
+        let mut display = mock.display(0, "Dell U2722D", "dell");
+        display.set_vcp_feature(0x12, 75).unwrap();
+        assert_eq!(mock.state().writes, [(0x12, 75)]);
+        assert_eq!(display.get_vcp_feature(0x12).unwrap().current, 75);
+    }
+
+    #[test]
+    fn transient_failures_are_retried() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100).failing(2);
+        let mut display = with_retries(mock.display(0, "Dell", "dell"), 3);
+        assert_eq!(display.get_vcp_feature(0x10).unwrap().current, 40);
+        assert_eq!(mock.state().calls, 3);
+    }
+
+    #[test]
+    fn retries_stop_after_retry_count() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100).failing(10);
+        let mut display = with_retries(mock.display(0, "Dell", "dell"), 2);
+        let err = display.get_vcp_feature(0x10).unwrap_err();
+        assert!(matches!(
+            err,
+            DisplayError::RetryExhausted { retries: 2, .. }
+        ));
+        assert_eq!(mock.state().calls, 3);
+    }
+
+    #[test]
+    fn zero_retries_returns_the_original_error() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100).failing(1);
+        let mut display = with_retries(mock.display(0, "Dell", "dell"), 0);
+        let err = display.get_vcp_feature(0x10).unwrap_err();
+        assert!(matches!(err, DisplayError::DdcCommunicationFailed(_)));
+    }
+
+    #[test]
+    fn unsupported_features_are_not_retried() {
+        let mock = MockDdcBackend::new();
+        let mut display = with_retries(mock.display(0, "Dell", "dell"), 3);
+        let err = display.get_vcp_feature(0x87).unwrap_err();
+        assert!(matches!(err, DisplayError::FeatureNotSupported(_)));
+        assert_eq!(mock.state().calls, 1);
+    }
+
+    #[test]
+    fn timeout_stops_retrying() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100).failing(100);
+        let mut display = with_retries(mock.display(0, "Dell", "dell"), 50);
+        let err = display
+            .get_vcp_feature_timeout(0x10, Duration::ZERO)
+            .unwrap_err();
+        assert!(matches!(err, DisplayError::DdcCommunicationFailed(_)));
+        assert_eq!(mock.state().calls, 1);
+    }
+
+    #[test]
+    fn set_if_changed_skips_matching_value() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100);
+        let mut display = mock.display(0, "Dell", "dell");
+        assert!(!display.set_vcp_feature_if_changed(0x10, 40).unwrap());
+        assert!(mock.state().writes.is_empty());
+    }
+
+    #[test]
+    fn set_if_changed_writes_different_value() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100);
+        let mut display = mock.display(0, "Dell", "dell");
+        assert!(display.set_vcp_feature_if_changed(0x10, 70).unwrap());
+        assert_eq!(mock.state().writes, [(0x10, 70)]);
+    }
+
+    #[test]
+    fn set_if_changed_does_not_write_when_read_fails() {
+        let mock = MockDdcBackend::new();
+        let mut display = mock.display(0, "Dell", "dell");
+        let err = display.set_vcp_feature_if_changed(0x87, 5).unwrap_err();
+        assert!(matches!(err, DisplayError::FeatureNotSupported(_)));
+        assert!(mock.state().writes.is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_values_above_maximum() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100);
+        let mut display = mock.display(0, "Dell", "dell");
+        let err = display.set_vcp_feature_validated(0x10, 101).unwrap_err();
+        assert!(matches!(
+            err,
+            DisplayError::InvalidValue {
+                code: 0x10,
+                value: 101,
+                max: 100
+            }
+        ));
+        assert!(mock.state().writes.is_empty());
+    }
+
+    #[test]
+    fn validate_skips_features_without_maximum() {
+        let mock = MockDdcBackend::new().with_value(0x60, 0x0F, 0);
+        let mut display = mock.display(0, "Dell", "dell");
+        display.set_vcp_feature_validated(0x60, 0x11).unwrap();
+        assert_eq!(mock.state().writes, [(0x60, 0x11)]);
+    }
+
+    #[test]
+    fn checked_write_returns_value_read_back() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100);
+        let mut display = mock.display(0, "Dell", "dell");
+        let actual = display
+            .set_vcp_feature_checked(0x10, 60, 0, Duration::ZERO)
+            .unwrap();
+        assert_eq!(actual, 60);
+    }
+
+    #[test]
+    fn checked_write_detects_ignored_write() {
+        let mock = MockDdcBackend::new()
+            .with_value(0x10, 40, 100)
+            .ignoring_writes();
+        let mut display = mock.display(0, "Dell", "dell");
+        let err = display
+            .set_vcp_feature_checked(0x10, 60, 2, Duration::ZERO)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DisplayError::VerificationFailed {
+                expected: 60,
+                actual: 40
+            }
+        ));
+    }
+
+    #[test]
+    fn verify_accepts_values_within_tolerance() {
+        let mock = MockDdcBackend::new().with_value(0x10, 58, 100);
+        let mut display = mock.display(0, "Dell", "dell");
+        assert_eq!(display.verify_vcp_value(0x10, 60, 2).unwrap(), 58);
+    }
+
+    #[test]
+    fn capabilities_are_fetched_once_when_cached() {
+        let mock = MockDdcBackend::new().with_capabilities(CAPS);
+        let mut display = mock.display(0, "Dell", "dell");
+        display.capabilities().unwrap();
+        display.capabilities().unwrap();
+        assert_eq!(mock.state().capability_fetches, 1);
+    }
+
+    #[test]
+    fn capabilities_are_refetched_without_cache() {
+        let mock = MockDdcBackend::new().with_capabilities(CAPS);
+        let mut display = mock.display(0, "Dell", "dell");
+        display.set_ddc_options(DdcOptions {
+            cache_capabilities: false,
+            ..Default::default()
+        });
+        display.capabilities().unwrap();
+        display.capabilities().unwrap();
+        assert_eq!(mock.state().capability_fetches, 2);
+    }
+
+    #[test]
+    fn get_all_vcp_features_collects_failed_codes() {
+        let mock = MockDdcBackend::new()
+            .with_capabilities(CAPS)
+            .with_value(0x10, 40, 100)
+            .with_value(0x12, 70, 100);
+        let mut display = mock.display(0, "Dell", "dell");
+        let snapshot = display.get_all_vcp_features().unwrap();
+        assert_eq!(snapshot.values.len(), 2);
+        assert_eq!(snapshot.values[&0x12].current, 70);
+        assert_eq!(snapshot.failed_codes, [0x60, 0xD6]);
+        assert_eq!(snapshot.stable_id, "dell");
+    }
+
+    #[test]
+    fn vcp_feature_batch_skips_unreadable_codes() {
+        let mock = MockDdcBackend::new()
+            .with_value(0x10, 40, 100)
+            .with_value(0x12, 70, 100);
+        let mut display = mock.display(0, "Dell", "dell");
+        let values = display.get_vcp_feature_batch(&[0x10, 0x60, 0x12]).unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[&0x10].current, 40);
+        assert!(!values.contains_key(&0x60));
+        assert_eq!(mock.state().calls, 3);
+    }
+
+    #[test]
+    fn vcp_feature_batch_fails_when_nothing_is_readable() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100);
+        let mut display = mock.display(0, "Dell", "dell");
+        let err = display.get_vcp_feature_batch(&[0x60, 0x62]).unwrap_err();
+        assert!(matches!(err, DisplayError::FeatureNotSupported(_)));
+        assert!(display.get_vcp_feature_batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn supports_feature_uses_capabilities() {
+        let mock = MockDdcBackend::new().with_capabilities(CAPS);
+        let mut display = mock.display(0, "Dell", "dell");
+        assert!(display.supports_feature(VcpFeature::Brightness).unwrap());
+        assert!(!display.supports_feature(VcpFeature::Sharpness).unwrap());
+    }
+
+    #[test]
+    fn clone_settings_scales_to_target_range() {
+        let source = MockDdcBackend::new().with_value(0x10, 50, 100);
+        let target = MockDdcBackend::new().with_value(0x10, 0, 255);
+        let mut from = source.display(0, "A", "a");
+        let mut to = target.display(1, "B", "b");
+        let changes = from.clone_settings_to(&mut to, &[0x10]).unwrap();
+        assert_eq!(
+            changes,
+            [SettingChange {
+                code: 0x10,
+                before: 0,
+                after: 128
+            }]
+        );
+        assert_eq!(target.state().writes, [(0x10, 128)]);
+    }
+
+    #[test]
+    fn clone_settings_copies_non_continuous_values_unchanged() {
+        let input = VcpValue {
+            current: 0x11,
+            max: 0x12,
+            is_continuous: false,
+        };
+        let source = MockDdcBackend::new().with_vcp(0x60, input);
+        let target = MockDdcBackend::new().with_vcp(
+            0x60,
+            VcpValue {
+                current: 0x0F,
+                max: 0x20,
+                ..input
+            },
+        );
+        let mut from = source.display(0, "A", "a");
+        let mut to = target.display(1, "B", "b");
+        from.clone_settings_to(&mut to, &[0x60]).unwrap();
+        assert_eq!(target.state().writes, [(0x60, 0x11)]);
+    }
+
+    #[test]
+    fn clone_settings_skips_matching_values() {
+        let source = MockDdcBackend::new().with_value(0x12, 70, 100);
+        let target = MockDdcBackend::new().with_value(0x12, 70, 100);
+        let mut from = source.display(0, "A", "a");
+        let mut to = target.display(1, "B", "b");
+        from.clone_settings_to(&mut to, &[0x12]).unwrap();
+        assert!(target.state().writes.is_empty());
+    }
+
+    #[test]
+    fn find_by_name_ignores_case() {
+        let mut displays = vec![
+            MockDdcBackend::new().display(0, "Generic PnP Monitor", "a"),
+            MockDdcBackend::new().display(1, "DELL U2722D", "b"),
+        ];
+        let found = find_by_name(&mut displays, "u2722").unwrap();
+        assert_eq!(found.id, 1);
+        assert!(find_by_name(&mut displays, "lg").is_none());
+    }
+
+    #[test]
+    fn find_by_model_skips_displays_without_capabilities() {
+        let mut displays = vec![
+            MockDdcBackend::new().display(0, "Generic PnP Monitor", "a"),
+            MockDdcBackend::new()
+                .with_capabilities(CAPS)
+                .display(1, "Generic PnP Monitor", "b"),
+        ];
+        assert_eq!(find_by_model(&mut displays, "u2722d").unwrap().id, 1);
+    }
+
+    #[test]
+    fn ddc_capable_requires_brightness_reply() {
+        let mock = MockDdcBackend::new().with_value(0x10, 40, 100);
+        assert!(mock.display(0, "Dell", "dell").is_ddc_capable());
+        assert!(
+            !MockDdcBackend::new()
+                .display(1, "TV", "tv")
+                .is_ddc_capable()
+        );
+    }
+
+    #[test]
+    fn adjacent_input_follows_listed_inputs() {
+        let mock = MockDdcBackend::new().with_capabilities(CAPS).with_vcp(
+            0x60,
+            VcpValue {
+                current: 0x11,
+                max: 0x11,
+                is_continuous: false,
+            },
+        );
+        let mut display = mock.display(0, "Dell", "dell");
+        assert_eq!(
+            display.adjacent_input(true).unwrap(),
+            (InputSource::Hdmi1, InputSource::DisplayPort1)
+        );
+        assert_eq!(
+            display.adjacent_input(false).unwrap(),
+            (InputSource::Hdmi1, InputSource::DisplayPort1)
+        );
+        assert!(mock.state().writes.is_empty());
+    }
+
+    #[test]
+    fn adjacent_input_needs_listed_inputs() {
+        let mock = MockDdcBackend::new()
+            .with_capabilities("(vcp(10 60))")
+            .with_value(0x60, 0x11, 0x11);
+        let err = mock
+            .display(0, "Dell", "dell")
+            .adjacent_input(true)
+            .unwrap_err();
+        assert!(matches!(err, DisplayError::FeatureNotSupported(_)));
+    }
+
+    #[test]
+    fn power_mode_round_trips() {
+        let mock = MockDdcBackend::new().with_value(0xD6, 0x01, 0x05);
+        let mut display = mock.display(0, "Dell", "dell");
+        display.set_power_mode(PowerMode::Standby).unwrap();
+        assert_eq!(display.get_power_mode().unwrap(), PowerMode::Standby);
+        assert_eq!(mock.state().writes, [(0xD6, 0x02)]);
+    }
+
+    const NO_DELAY: DdcOptions = DdcOptions {
+        retry_count: 0,
+        retry_delay_ms: 0,
+        verbose: false,
+        cache_capabilities: true,
+        retry_on_empty: true,
+    };
+
+    #[test]
+    fn retry_enumeration_keeps_displays_without_ddc() {
+        let dell = MockDdcBackend::new().with_value(0x10, 40, 100);
+        let tv = MockDdcBackend::new();
+        let displays = retry_enumeration(5, 0, &NO_DELAY, || {
+            Ok(vec![
+                tv.display(0, "TV", "tv"),
+                dell.display(1, "Dell", "dell"),
+            ])
+        })
+        .unwrap();
+        assert_eq!(displays.len(), 2);
+    }
+
+    #[test]
+    fn retry_enumeration_retries_until_displays_appear() {
+        let dell = MockDdcBackend::new().with_value(0x10, 40, 100);
+        let mut attempts = 0;
+        let displays = retry_enumeration(5, 0, &NO_DELAY, || {
+            attempts += 1;
+            if attempts < 3 {
+                return Err(DisplayError::MonitorNotFound("none yet".to_string()));
+            }
+            Ok(vec![dell.display(0, "Dell", "dell")])
+        })
+        .unwrap();
+        assert_eq!((attempts, displays.len()), (3, 1));
+    }
+
+    #[test]
+    fn retry_enumeration_returns_silent_displays_after_last_attempt() {
+        let tv = MockDdcBackend::new();
+        let mut attempts = 0;
+        let displays = retry_enumeration(3, 0, &NO_DELAY, || {
+            attempts += 1;
+            Ok(vec![tv.display(0, "TV", "tv")])
+        })
+        .unwrap();
+        assert_eq!((attempts, displays.len()), (3, 1));
+    }
+
+    #[test]
+    fn retry_enumeration_fails_when_nothing_is_found() {
+        let err = retry_enumeration(2, 0, &NO_DELAY, || Ok(Vec::new())).unwrap_err();
+        assert!(matches!(err, DisplayError::MonitorNotFound(_)));
+    }
+
+    #[test]
+    fn retry_enumeration_pings_with_given_options() {
+        let dell = MockDdcBackend::new().with_value(0x10, 40, 100).failing(4);
+        let options = DdcOptions {
+            retry_count: 4,
+            ..NO_DELAY
+        };
+        let displays =
+            retry_enumeration(1, 0, &options, || Ok(vec![dell.display(0, "Dell", "dell")]))
+                .unwrap();
+        assert_eq!(displays[0].options.retry_count, 4);
+        assert_eq!(dell.state().calls, 5);
+    }
+}
@@ -1,41 +1,90 @@
-use super::{DdcBackend, Display, DisplayInfo};
+use super::{ConnectionType, DdcBackend, Display, DisplayInfo, MonitorGeometry, TimingReport};
+use crate::capabilities::is_balanced;
+use crate::display::normalize_wide_string;
 use crate::error::DisplayError;
-use std::ffi::OsString;
-use std::os::windows::ffi::OsStringExt;
+use crate::vcp::VcpValue;
 use windows::Win32::Devices::Display::{
-    CapabilitiesRequestAndCapabilitiesReply, DestroyPhysicalMonitors, GetCapabilitiesStringLength,
-    GetNumberOfPhysicalMonitorsFromHMONITOR, GetPhysicalMonitorsFromHMONITOR,
-    GetVCPFeatureAndVCPFeatureReply, PHYSICAL_MONITOR, SetVCPFeature,
+    CapabilitiesRequestAndCapabilitiesReply, DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+    DISPLAYCONFIG_MODE_INFO, DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EMBEDDED,
+    DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EXTERNAL,
+    DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_USB_TUNNEL, DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DVI,
+    DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HD15, DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HDMI,
+    DISPLAYCONFIG_PATH_INFO, DISPLAYCONFIG_SOURCE_DEVICE_NAME, DestroyPhysicalMonitors,
+    DisplayConfigGetDeviceInfo, GetCapabilitiesStringLength, GetDisplayConfigBufferSizes,
+    GetNumberOfPhysicalMonitorsFromHMONITOR, GetPhysicalMonitorsFromHMONITOR, GetTimingReport,
+    GetVCPFeatureAndVCPFeatureReply, MC_SET_PARAMETER, MC_TIMING_REPORT, MC_VCP_CODE_TYPE,
+    PHYSICAL_MONITOR, QDC_ONLY_ACTIVE_PATHS, QueryDisplayConfig, SetVCPFeature,
 };
 use windows::Win32::Foundation::{HANDLE, LPARAM, RECT};
 use windows::Win32::Graphics::Gdi::{
-    EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFOEXW,
+    CreateDCW, DISPLAY_DEVICEW, DeleteDC, EnumDisplayDevicesW, EnumDisplayMonitors, GetDeviceCaps,
+    GetMonitorInfoW, HDC, HMONITOR, HORZSIZE, MONITORINFOEXW, VERTSIZE,
 };
-use windows::core::BOOL;
+use windows::Win32::System::Registry::{HKEY_LOCAL_MACHINE, RRF_RT_REG_BINARY, RegGetValueW};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EDD_GET_DEVICE_INTERFACE_NAME, MONITORINFOF_PRIMARY,
+};
+use windows::core::{BOOL, HSTRING, PCWSTR, w};
 
 struct WindowsBackend {
     handle: HANDLE,
     physical_monitor: PHYSICAL_MONITOR,
+    hmonitor: HMONITOR,
+    /// Position of this physical monitor within `hmonitor`.
+    index: u32,
+}
+
+// SAFETY: the physical monitor handle is an opaque token owned exclusively by
+// this backend, and the HMONITOR is only passed back to thread-agnostic GDI
+// queries. The Monitor Configuration API does not tie it to the thread
+// that created it, and `&mut self` on every call keeps access serialized.
+unsafe impl Send for WindowsBackend {}
+
+/// How many times `capabilities` doubles the buffer when the reply looks
+/// truncated.
+const CAPABILITIES_RETRIES: usize = 3;
+
+impl WindowsBackend {
+    /// Asks the monitor for its capabilities string into a `size`-byte
+    /// buffer.
+    fn request_capabilities(&self, size: usize) -> Result<String, DisplayError> {
+        let mut buffer = vec![0u8; size];
+        let success = unsafe { CapabilitiesRequestAndCapabilitiesReply(self.handle, &mut buffer) };
+
+        if success == 0 {
+            return Err(DisplayError::last_ddc_error(
+                "CapabilitiesRequestAndCapabilitiesReply failed",
+            ));
+        }
+
+        let s = String::from_utf8_lossy(&buffer).to_string();
+        Ok(s.trim_matches(char::from(0)).to_string())
+    }
 }
 
 impl DdcBackend for WindowsBackend {
-    fn get_vcp(&mut self, code: u8) -> Result<u32, DisplayError> {
+    fn get_vcp(&mut self, code: u8) -> Result<VcpValue, DisplayError> {
+        let mut code_type = MC_VCP_CODE_TYPE::default();
         let mut current_value: u32 = 0;
         let mut max_value: u32 = 0;
         let success = unsafe {
             GetVCPFeatureAndVCPFeatureReply(
                 self.handle,
                 code,
-                None,
+                Some(&mut code_type),
                 &mut current_value,
                 Some(&mut max_value),
             )
         };
 
         if success != 0 {
-            Ok(current_value)
+            Ok(VcpValue {
+                current: current_value,
+                max: max_value,
+                is_continuous: code_type == MC_SET_PARAMETER,
+            })
         } else {
-            Err(DisplayError::DdcCommunicationFailed(format!(
+            Err(DisplayError::last_ddc_error(format!(
                 "GetVCPFeatureAndVCPFeatureReply failed for code 0x{:02X}",
                 code
             )))
@@ -48,7 +97,7 @@ impl DdcBackend for WindowsBackend {
         if success != 0 {
             Ok(())
         } else {
-            Err(DisplayError::DdcCommunicationFailed(format!(
+            Err(DisplayError::last_ddc_error(format!(
                 "SetVCPFeature failed for code 0x{:02X}",
                 code
             )))
@@ -60,26 +109,222 @@ impl DdcBackend for WindowsBackend {
         let success = unsafe { GetCapabilitiesStringLength(self.handle, &mut length) };
 
         if success == 0 {
-            return Err(DisplayError::DdcCommunicationFailed(
-                "GetCapabilitiesStringLength failed".to_string(),
-            ));
+            return Err(DisplayError::last_ddc_error("GetCapabilitiesStringLength failed"));
         }
 
-        let mut buffer = vec![0u8; length as usize];
-        let success_str =
-            unsafe { CapabilitiesRequestAndCapabilitiesReply(self.handle, &mut buffer) };
+        // Some monitors under-report the length and the reply comes back
+        // cut off, so retry with a larger buffer while it looks truncated.
+        let mut size = length as usize;
+        let mut raw = self.request_capabilities(size)?;
+        for _ in 0..CAPABILITIES_RETRIES {
+            if is_balanced(&raw) {
+                break;
+            }
+            size *= 2;
+            raw = self.request_capabilities(size)?;
+        }
 
-        if success_str == 0 {
-            return Err(DisplayError::DdcCommunicationFailed(
-                "CapabilitiesRequestAndCapabilitiesReply failed".to_string(),
+        if !is_balanced(&raw) {
+            return Err(DisplayError::CapabilitiesIncomplete(format!(
+                "parentheses still unbalanced with a {}-byte buffer",
+                size
+            )));
+        }
+        Ok(raw)
+    }
+
+    fn edid(&mut self) -> Result<Vec<u8>, DisplayError> {
+        read_edid(self.hmonitor, self.index)
+    }
+
+    fn timing_report(&mut self) -> Result<TimingReport, DisplayError> {
+        let mut report = MC_TIMING_REPORT::default();
+        let success = unsafe { GetTimingReport(self.handle, &mut report) };
+
+        // Drivers without timing support fail here instead of returning
+        // zeroes, so this is reported as unsupported rather than retried.
+        if success == 0 {
+            return Err(DisplayError::FeatureNotSupported(
+                "GetTimingReport failed; the monitor or driver may not provide timing reports"
+                    .to_string(),
             ));
         }
 
-        let s = String::from_utf8_lossy(&buffer).to_string();
-        Ok(s.trim_matches(char::from(0)).to_string())
+        Ok(TimingReport {
+            horizontal_freq_hz: report.dwHorizontalFrequencyInHZ,
+            vertical_freq_hz: report.dwVerticalFrequencyInHZ,
+            timing_status_code: report.bTimingStatusByte,
+        })
     }
 }
 
+/// Reads the EDID of the `index`-th monitor attached to `hmonitor` from the
+/// copy Windows keeps in the registry under
+/// `HKLM\SYSTEM\CurrentControlSet\Enum\DISPLAY`.
+pub fn read_edid(hmonitor: HMONITOR, index: u32) -> Result<Vec<u8>, DisplayError> {
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+    if unsafe { GetMonitorInfoW(hmonitor, &mut info as *mut _ as *mut _).0 == 0 } {
+        return Err(DisplayError::last_windows_error());
+    }
+
+    let mut device = DISPLAY_DEVICEW {
+        cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+        ..Default::default()
+    };
+    let found = unsafe {
+        EnumDisplayDevicesW(
+            PCWSTR(info.szDevice.as_ptr()),
+            index,
+            &mut device,
+            EDD_GET_DEVICE_INTERFACE_NAME,
+        )
+    };
+    if !found.as_bool() {
+        return Err(DisplayError::MonitorNotFound(format!(
+            "No display device {} on this monitor",
+            index
+        )));
+    }
+
+    // Interface names look like
+    // \\?\DISPLAY#DEL41A8#5&2ad4a1b3&0&UID4353#{e6f07b5f-...}; the middle
+    // parts name the device's registry key.
+    let interface = normalize_wide_string(&device.DeviceID);
+    let parts: Vec<&str> = interface.trim_start_matches(r"\\?\").split('#').collect();
+    if parts.len() < 3 {
+        return Err(DisplayError::FeatureNotSupported(format!(
+            "Unexpected monitor device path: {}",
+            interface
+        )));
+    }
+    let subkey = HSTRING::from(format!(
+        r"SYSTEM\CurrentControlSet\Enum\{}\{}\{}\Device Parameters",
+        parts[0], parts[1], parts[2]
+    ));
+
+    let mut size: u32 = 0;
+    unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            &subkey,
+            w!("EDID"),
+            RRF_RT_REG_BINARY,
+            None,
+            None,
+            Some(&mut size),
+        )
+    }
+    .ok()?;
+    let mut edid = vec![0u8; size as usize];
+    unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            &subkey,
+            w!("EDID"),
+            RRF_RT_REG_BINARY,
+            None,
+            Some(edid.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+    }
+    .ok()?;
+    edid.truncate(size as usize);
+    Ok(edid)
+}
+
+/// Returns the physical width and height of `hmonitor` in millimetres, as the
+/// display driver reports them through `GetDeviceCaps`.
+pub fn physical_size_mm(hmonitor: HMONITOR) -> Result<(u32, u32), DisplayError> {
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+    if unsafe { GetMonitorInfoW(hmonitor, &mut info as *mut _ as *mut _).0 == 0 } {
+        return Err(DisplayError::last_windows_error());
+    }
+
+    let hdc = unsafe {
+        CreateDCW(
+            w!("DISPLAY"),
+            PCWSTR(info.szDevice.as_ptr()),
+            PCWSTR::null(),
+            None,
+        )
+    };
+    if hdc.is_invalid() {
+        return Err(DisplayError::last_windows_error());
+    }
+
+    let (width, height) = unsafe {
+        let size = (
+            GetDeviceCaps(Some(hdc), HORZSIZE),
+            GetDeviceCaps(Some(hdc), VERTSIZE),
+        );
+        let _ = DeleteDC(hdc);
+        size
+    };
+
+    // Drivers without an EDID (or with a bogus one) report zero.
+    if width <= 0 || height <= 0 {
+        return Err(DisplayError::FeatureNotSupported(
+            "the display driver does not report a physical size".to_string(),
+        ));
+    }
+    Ok((width as u32, height as u32))
+}
+
+/// Looks up how the display with GDI device name `device_name` (e.g.
+/// `\\.\DISPLAY1`) is connected, from the output technology of its active
+/// path in the display configuration. Returns `None` if no active path
+/// drives that device.
+pub fn detect_connection_type(device_name: &str) -> Option<ConnectionType> {
+    let mut path_count = 0u32;
+    let mut mode_count = 0u32;
+    unsafe {
+        GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count)
+            .ok()
+            .ok()?;
+    }
+    let mut paths = vec![DISPLAYCONFIG_PATH_INFO::default(); path_count as usize];
+    let mut modes = vec![DISPLAYCONFIG_MODE_INFO::default(); mode_count as usize];
+    unsafe {
+        QueryDisplayConfig(
+            QDC_ONLY_ACTIVE_PATHS,
+            &mut path_count,
+            paths.as_mut_ptr(),
+            &mut mode_count,
+            modes.as_mut_ptr(),
+            None,
+        )
+        .ok()
+        .ok()?;
+    }
+    paths.truncate(path_count as usize);
+
+    paths.iter().find_map(|path| {
+        let mut source = DISPLAYCONFIG_SOURCE_DEVICE_NAME::default();
+        source.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
+        source.header.size = std::mem::size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32;
+        source.header.adapterId = path.sourceInfo.adapterId;
+        source.header.id = path.sourceInfo.id;
+        if unsafe { DisplayConfigGetDeviceInfo(&mut source.header) } != 0 {
+            return None;
+        }
+        let name = normalize_wide_string(&source.viewGdiDeviceName);
+        if name != device_name {
+            return None;
+        }
+        Some(match path.targetInfo.outputTechnology {
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HDMI => ConnectionType::Hdmi,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EXTERNAL
+            | DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EMBEDDED => ConnectionType::DisplayPort,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_USB_TUNNEL => ConnectionType::UsbC,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DVI => ConnectionType::Dvi,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HD15 => ConnectionType::Vga,
+            _ => ConnectionType::Unknown,
+        })
+    })
+}
+
 impl Drop for WindowsBackend {
     fn drop(&mut self) {
         unsafe {
@@ -99,14 +344,24 @@ unsafe extern "system" fn monitor_enum_proc(
     let mut info = MONITORINFOEXW::default();
     info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
     let mut device_name = String::from("Unknown");
+    let mut geometry = None;
 
     if unsafe { GetMonitorInfoW(hmonitor, &mut info as *mut _ as *mut _).0 != 0 } {
-        device_name = OsString::from_wide(&info.szDevice)
-            .to_string_lossy()
-            .trim_matches(char::from(0))
-            .to_string();
+        device_name = normalize_wide_string(&info.szDevice);
+
+        let rect = info.monitorInfo.rcMonitor;
+        geometry = Some(MonitorGeometry {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right.abs_diff(rect.left),
+            height: rect.bottom.abs_diff(rect.top),
+            is_primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+        });
     }
 
+    let size_mm = physical_size_mm(hmonitor).ok();
+    let connection = detect_connection_type(&device_name);
+
     let mut num_physical_monitors: u32 = 0;
     if unsafe { GetNumberOfPhysicalMonitorsFromHMONITOR(hmonitor, &mut num_physical_monitors).is_ok() }
         && num_physical_monitors > 0
@@ -114,13 +369,10 @@ unsafe extern "system" fn monitor_enum_proc(
         let mut physical_monitors = vec![PHYSICAL_MONITOR::default(); num_physical_monitors as usize];
 
         if unsafe { GetPhysicalMonitorsFromHMONITOR(hmonitor, &mut physical_monitors).is_ok() } {
-            for pm in physical_monitors {
+            for (index, pm) in physical_monitors.into_iter().enumerate() {
                 let id = displays.len();
                 let desc_array = pm.szPhysicalMonitorDescription;
-                let pm_desc = OsString::from_wide(&desc_array)
-                    .to_string_lossy()
-                    .trim_matches(char::from(0))
-                    .to_string();
+                let pm_desc = normalize_wide_string(&desc_array);
 
                 let name = if !pm_desc.is_empty() {
                     pm_desc
@@ -131,10 +383,18 @@ unsafe extern "system" fn monitor_enum_proc(
                 // stable_id: fall back to Windows device path until EDID extraction lands.
                 let stable_id = device_name.clone();
 
-                let info = DisplayInfo { name, stable_id };
+                let info = DisplayInfo {
+                    name,
+                    stable_id,
+                    geometry,
+                    size_mm,
+                    connection,
+                };
                 let backend = WindowsBackend {
                     handle: pm.hPhysicalMonitor,
                     physical_monitor: pm,
+                    hmonitor,
+                    index: index as u32,
                 };
 
                 displays.push(Display::new(id, info, Box::new(backend)));
@@ -1,19 +1,164 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
+use serde::{Deserialize, Serialize, Serializer};
 use crate::vcp::VcpFeature;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Capabilities {
     pub protocol: Option<String>,
     pub display_type: Option<String>,
     pub model: Option<String>,
     pub commands: Vec<String>,
+    #[serde(with = "hex_keys")]
     pub vcp_features: HashMap<u8, Vec<u16>>,
     pub mccs_version: Option<String>,
     pub raw: String,
 }
 
+/// (De)serializes the VCP feature map with `"0xNN"` string keys so JSON
+/// output reads like the codes users pass on the command line.
+mod hex_keys {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::{BTreeMap, HashMap};
+
+    pub fn serialize<S>(map: &HashMap<u8, Vec<u16>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let sorted: BTreeMap<String, &Vec<u16>> = map
+            .iter()
+            .map(|(code, values)| (format!("0x{:02X}", code), values))
+            .collect();
+        sorted.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<u8, Vec<u16>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = HashMap::<String, Vec<u16>>::deserialize(deserializer)?;
+        raw.into_iter()
+            .map(|(key, values)| {
+                let hex = key.strip_prefix("0x").unwrap_or(&key);
+                u8::from_str_radix(hex, 16)
+                    .map(|code| (code, values))
+                    .map_err(|_| D::Error::custom(format!("invalid VCP code key: {}", key)))
+            })
+            .collect()
+    }
+}
+
+/// Differences between the VCP features advertised by two monitors, keyed by
+/// VCP code. Produced by `Capabilities::diff`.
+#[derive(Debug, Default, Serialize)]
+pub struct CapabilitiesDiff {
+    #[serde(serialize_with = "hex_code_list")]
+    pub only_in_a: Vec<u8>,
+    #[serde(serialize_with = "hex_code_list")]
+    pub only_in_b: Vec<u8>,
+    pub different_values: Vec<ValueDifference>,
+}
+
+/// A VCP code supported by both monitors with different enumerated values.
+#[derive(Debug, Serialize)]
+pub struct ValueDifference {
+    #[serde(serialize_with = "hex_code")]
+    pub code: u8,
+    pub a: Vec<u16>,
+    pub b: Vec<u16>,
+}
+
+impl CapabilitiesDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.different_values.is_empty()
+    }
+}
+
+/// How one monitor's supported VCP features changed between two reads of
+/// its capabilities string, as found by `capability_diff`.
+#[derive(Debug, Default)]
+pub struct CapabilityChange {
+    pub added_codes: Vec<u8>,
+    pub removed_codes: Vec<u8>,
+    /// (code, value) pairs newly listed for a code present in both reads.
+    pub added_values: Vec<(u8, u16)>,
+    pub removed_values: Vec<(u8, u16)>,
+}
+
+/// Compares two capabilities strings read from the same monitor, returning
+/// `None` if they list the same VCP codes and values. Callers should check
+/// both strings with `is_balanced` first; a truncated read looks like a
+/// change.
+pub fn capability_diff(old: &str, new: &str) -> Option<CapabilityChange> {
+    let diff = Capabilities::diff(&Capabilities::parse(old), &Capabilities::parse(new));
+    if diff.is_empty() {
+        return None;
+    }
+    let mut change = CapabilityChange {
+        added_codes: diff.only_in_b,
+        removed_codes: diff.only_in_a,
+        ..Default::default()
+    };
+    for d in &diff.different_values {
+        let added = d.b.iter().filter(|v| !d.a.contains(v));
+        change.added_values.extend(added.map(|&v| (d.code, v)));
+        let removed = d.a.iter().filter(|v| !d.b.contains(v));
+        change.removed_values.extend(removed.map(|&v| (d.code, v)));
+    }
+    Some(change)
+}
+
+fn hex_code<S: Serializer>(code: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{:02X}", code))
+}
+
+fn hex_code_list<S: Serializer>(codes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(codes.iter().map(|code| format!("0x{:02X}", code)))
+}
+
 impl Capabilities {
+    /// The VCP features the monitor advertises, ordered by code.
+    pub fn supported_vcp_features(&self) -> Vec<VcpFeature> {
+        let mut features: Vec<VcpFeature> =
+            self.vcp_features.keys().map(|&code| VcpFeature::from_code(code)).collect();
+        features.sort_by_key(VcpFeature::code);
+        features
+    }
+
+    pub fn is_feature_supported(&self, feature: VcpFeature) -> bool {
+        self.vcp_features.contains_key(&feature.code())
+    }
+
+    /// Compares the VCP features of two monitors by code. Value lists are
+    /// compared as sets, so ordering differences in the capabilities strings
+    /// are ignored.
+    pub fn diff(a: &Capabilities, b: &Capabilities) -> CapabilitiesDiff {
+        let codes: BTreeSet<u8> = a.vcp_features.keys().chain(b.vcp_features.keys()).copied().collect();
+        let mut diff = CapabilitiesDiff::default();
+
+        for code in codes {
+            match (a.vcp_features.get(&code), b.vcp_features.get(&code)) {
+                (Some(_), None) => diff.only_in_a.push(code),
+                (None, Some(_)) => diff.only_in_b.push(code),
+                (Some(va), Some(vb)) => {
+                    let sa: BTreeSet<u16> = va.iter().copied().collect();
+                    let sb: BTreeSet<u16> = vb.iter().copied().collect();
+                    if sa != sb {
+                        diff.different_values.push(ValueDifference {
+                            code,
+                            a: sa.into_iter().collect(),
+                            b: sb.into_iter().collect(),
+                        });
+                    }
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+
+        diff
+    }
+
     pub fn parse(raw: &str) -> Self {
         let mut caps = Capabilities {
             raw: raw.to_string(),
@@ -69,13 +214,131 @@ impl Capabilities {
 
         caps
     }
+
+    /// The `mccs_ver(...)` entry as a version number, if present and in
+    /// `MAJOR.MINOR` form.
+    pub fn mccs_version_parsed(&self) -> Option<MccsVersion> {
+        self.mccs_version.as_deref().and_then(MccsVersion::parse)
+    }
+
+    /// The `cmds(...)` entries with their names, in the order the monitor
+    /// lists them.
+    pub fn command_list(&self) -> Vec<DdcCommand> {
+        self.commands
+            .iter()
+            .map(|code| DdcCommand {
+                code: code.to_uppercase(),
+                name: name_for_command_code(code),
+            })
+            .collect()
+    }
+
+    /// Whether the raw string's parentheses balance. A truncated reply
+    /// usually ends inside the `vcp(...)` list and fails this check.
+    pub fn is_complete(&self) -> bool {
+        is_balanced(&self.raw)
+    }
+}
+
+/// The MCCS version a monitor implements, ordered so that
+/// `version >= MccsVersion::V3_0` works as expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct MccsVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl MccsVersion {
+    /// MCCS 3.0 redefines some codes and adds others, so its features
+    /// should only be used on monitors that report at least this version.
+    pub const V3_0: MccsVersion = MccsVersion { major: 3, minor: 0 };
+
+    /// Parses `2.1`, tolerating surrounding whitespace.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (major, minor) = s.trim().split_once('.')?;
+        Some(MccsVersion {
+            major: major.trim().parse().ok()?,
+            minor: minor.trim().parse().ok()?,
+        })
+    }
+
+    /// What a monitor reporting this version can be expected to support.
+    pub fn supported_features(&self) -> &'static str {
+        if *self >= MccsVersion::V3_0 {
+            "basic VCP, capabilities string, MCCS 3.0 codes"
+        } else {
+            "basic VCP, capabilities string"
+        }
+    }
+}
+
+impl fmt::Display for MccsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// A DDC/CI command from the `cmds(...)` section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DdcCommand {
+    /// The code as listed, e.g. `01`.
+    pub code: String,
+    pub name: &'static str,
+}
+
+impl fmt::Display for DdcCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{} ({})", self.code, self.name)
+    }
+}
+
+/// Names a DDC/CI command code as it appears in `cmds(...)` (two hex
+/// digits, e.g. `"01"` for VCP Request). Unknown codes are `"Unknown"`.
+pub fn name_for_command_code(code: &str) -> &'static str {
+    match u8::from_str_radix(code, 16) {
+        Ok(0x01) => "VCP Request",
+        Ok(0x02) => "VCP Reply",
+        Ok(0x03) => "VCP Set",
+        Ok(0x06) => "Timing Reply",
+        Ok(0x07) => "Timing Request",
+        Ok(0x09) => "VCP Reset",
+        Ok(0x0C) => "Save Current Settings",
+        Ok(0xA1) => "Self Test Reply",
+        Ok(0xB1) => "Self Test Request",
+        Ok(0xE1) => "Identification Reply",
+        Ok(0xE2) => "Table Read Request",
+        Ok(0xE3) => "Capabilities Reply",
+        Ok(0xE4) => "Table Read Reply",
+        Ok(0xE7) => "Table Write",
+        Ok(0xF1) => "Identification Request",
+        Ok(0xF3) => "Capabilities Request",
+        Ok(0xF5) => "Enable Application Report",
+        _ => "Unknown",
+    }
+}
+
+/// Checks that every `(` in `raw` is closed and no `)` comes before its
+/// opening parenthesis.
+pub fn is_balanced(raw: &str) -> bool {
+    let mut depth: usize = 0;
+    for c in raw.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
 }
 
 fn parse_paren_content(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
     let mut content = String::new();
     let mut depth = 1;
 
-    while let Some(c) = chars.next() {
+    for c in chars.by_ref() {
         match c {
             '(' => {
                 depth += 1;
@@ -159,6 +422,13 @@ impl fmt::Display for Capabilities {
         if let Some(mccs) = &self.mccs_version {
             writeln!(f, "  MCCS Version: {}", mccs)?;
         }
+
+        if !self.commands.is_empty() {
+            writeln!(f, "\nSupported Commands:")?;
+            for command in self.command_list() {
+                writeln!(f, "  {}", command)?;
+            }
+        }
         
         writeln!(f, "\nSupported VCP Features:")?;
         let mut codes: Vec<_> = self.vcp_features.keys().collect();
@@ -170,6 +440,9 @@ impl fmt::Display for Capabilities {
             let values = &self.vcp_features[code];
             
             write!(f, "  0x{:02X} ({})", code, name)?;
+            if feature.is_typically_read_only() {
+                write!(f, " [RO]")?;
+            }
             
             if !values.is_empty() {
                 write!(f, " -> Supported Values: [")?;
@@ -191,3 +464,110 @@ impl fmt::Display for Capabilities {
         Ok(())
     }
 }
+
+/// Rebuilds a capabilities string from the parsed fields, in the usual
+/// `(prot(...)type(...)model(...)cmds(...)vcp(...)mccs_ver(...))` order with
+/// VCP codes sorted. `raw` is ignored, so parsing the result yields the same
+/// fields even if the original string was laid out differently.
+impl From<&Capabilities> for String {
+    fn from(caps: &Capabilities) -> Self {
+        let mut out = String::from("(");
+        if let Some(prot) = &caps.protocol {
+            out.push_str(&format!("prot({})", prot));
+        }
+        if let Some(dtype) = &caps.display_type {
+            out.push_str(&format!("type({})", dtype));
+        }
+        if let Some(model) = &caps.model {
+            out.push_str(&format!("model({})", model));
+        }
+        if !caps.commands.is_empty() {
+            out.push_str(&format!("cmds({})", caps.commands.join(" ")));
+        }
+        if !caps.vcp_features.is_empty() {
+            let mut codes: Vec<_> = caps.vcp_features.keys().collect();
+            codes.sort();
+            let entries: Vec<String> = codes
+                .into_iter()
+                .map(|code| {
+                    let values = &caps.vcp_features[code];
+                    if values.is_empty() {
+                        format!("{:02X}", code)
+                    } else {
+                        let values: Vec<String> = values.iter().map(|v| format!("{:02X}", v)).collect();
+                        format!("{:02X}({})", code, values.join(" "))
+                    }
+                })
+                .collect();
+            out.push_str(&format!("vcp({})", entries.join(" ")));
+        }
+        if let Some(mccs) = &caps.mccs_version {
+            out.push_str(&format!("mccs_ver({})", mccs));
+        }
+        out.push(')');
+        out
+    }
+}
+
+impl fmt::Display for CapabilitiesDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences in supported VCP features.");
+        }
+
+        writeln!(f, "Only in A:")?;
+        if self.only_in_a.is_empty() { writeln!(f, "  (none)")?; }
+        for code in &self.only_in_a {
+            writeln!(f, "  0x{:02X} ({})", code, VcpFeature::from_code(*code).name())?;
+        }
+
+        writeln!(f, "\nOnly in B:")?;
+        if self.only_in_b.is_empty() { writeln!(f, "  (none)")?; }
+        for code in &self.only_in_b {
+            writeln!(f, "  0x{:02X} ({})", code, VcpFeature::from_code(*code).name())?;
+        }
+
+        writeln!(f, "\nDifferent values:")?;
+        if self.different_values.is_empty() { writeln!(f, "  (none)")?; }
+        for d in &self.different_values {
+            writeln!(f, "  0x{:02X} ({})", d.code, VcpFeature::from_code(d.code).name())?;
+            writeln!(f, "    A: {}", format_values(&d.a))?;
+            writeln!(f, "    B: {}", format_values(&d.b))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// One line per change, e.g. `VCP 0x60 added value 0x11 (Hdmi1)`.
+impl fmt::Display for CapabilityChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for code in &self.added_codes {
+            writeln!(f, "VCP 0x{:02X} ({}) added", code, VcpFeature::from_code(*code).name())?;
+        }
+        for code in &self.removed_codes {
+            writeln!(f, "VCP 0x{:02X} ({}) removed", code, VcpFeature::from_code(*code).name())?;
+        }
+        for &(code, value) in &self.added_values {
+            writeln!(f, "VCP 0x{:02X} added value {}", code, format_listed_value(code, value))?;
+        }
+        for &(code, value) in &self.removed_values {
+            writeln!(f, "VCP 0x{:02X} removed value {}", code, format_listed_value(code, value))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a value from a VCP code's value list, naming input sources.
+fn format_listed_value(code: u8, value: u16) -> String {
+    if code == 0x60 {
+        format!("0x{:X} ({})", value, crate::vcp::InputSource::from_value(value))
+    } else {
+        format!("0x{:X}", value)
+    }
+}
+
+fn format_values(values: &[u16]) -> String {
+    let items: Vec<String> = values.iter().map(|v| format!("0x{:X}", v)).collect();
+    format!("[{}]", items.join(", "))
+}
@@ -1,8 +1,10 @@
 // This module is intentionally free of crate-internal dependencies so that
 // `build.rs` can `include!` it via a path without pulling in the rest of the
-// crate. Only `clap` is used here.
+// crate. Only `clap` and `clap_complete` are used here.
 
-use clap::{Parser, Subcommand};
+use clap::builder::{PossibleValue, RangedU64ValueParser, StringValueParser, TypedValueParser};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use std::ffi::OsStr;
 
 #[derive(Parser)]
 #[command(name = "dispman", version)]
@@ -11,7 +13,20 @@ use clap::{Parser, Subcommand};
 It can switch input sources, adjust brightness and contrast, change volume, query a \
 monitor's capabilities string, and save or restore groups of settings as named profiles.\n\n\
 Monitors are addressed by a zero-based index assigned at enumeration time. \
-If no display is given, commands operate on display 0.")]
+If no display is given, commands operate on display 0. Because indices can change when \
+monitors are reconnected, --display-name selects a monitor by a substring of its name \
+instead; `get`, `set`, `power` and `inspect` also accept --all-displays to act on every \
+connected monitor.")]
+#[command(after_help = "Exit codes:\n  \
+0  success\n  \
+1  other error\n  \
+2  monitor not found\n  \
+3  DDC/CI communication failed\n  \
+4  feature not supported\n  \
+5  configuration error\n  \
+6  I/O error\n  \
+7  serialization error\n  \
+10 get --expect did not match")]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
@@ -19,6 +34,63 @@ pub struct Cli {
     /// Enable verbose output
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Print nothing on success; errors still go to stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Apply `get`, `set`, `power` or `inspect` to every connected display
+    #[arg(short, long, global = true)]
+    pub all_displays: bool,
+
+    /// Select the display whose name (or, failing that, model) contains this text
+    #[arg(long, global = true, value_name = "SUBSTR")]
+    pub display_name: Option<String>,
+
+    /// With --display-name, use the first match when several displays match
+    #[arg(long, global = true, requires = "display_name")]
+    pub first_match: bool,
+
+    /// Number of times to retry a failed DDC/CI command [default: 3, or ddc_retry_count]
+    #[arg(long, global = true)]
+    pub ddc_retries: Option<u8>,
+
+    /// Delay between DDC/CI retries, in milliseconds [default: 50, or ddc_delay_ms]
+    #[arg(long, global = true)]
+    pub ddc_delay_ms: Option<u64>,
+
+    /// Print the changes a command would make without sending them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Output format for detect, get, inspect, capabilities, and profile list
+    #[arg(
+        short = 'F',
+        long,
+        global = true,
+        value_enum,
+        env = "DISPMAN_OUTPUT_FORMAT",
+        default_value_t = OutputFormat::Text
+    )]
+    pub output_format: OutputFormat,
+
+    /// Always fetch capabilities from the monitor instead of reusing them
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Use this config file instead of the default (also: DISPMAN_CONFIG)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config_path: Option<std::path::PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// Pretty-printed JSON
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
 }
 
 #[derive(Subcommand)]
@@ -26,64 +98,923 @@ pub enum Commands {
     /// Detect available displays
     #[command(long_about = "List all connected monitors that dispman can talk to. \
 Each entry shows its numeric ID, a human-readable name, and the stable \
-identifier used for profile lookup.")]
-    Detect {
-        /// Output in JSON format
-        #[arg(long)]
-        json: bool,
-    },
+identifier used for profile lookup. Where the platform reports it, the monitor's \
+position and size in the virtual desktop are shown as well. With --json (or \
+--output-format json/csv), each monitor is also checked for a DDC/CI response and \
+reported as `ddc_capable`; --ddc-delay-ms sets the pause between ping attempts.\n\n\
+With --wait, enumeration is retried every 250 ms until a monitor answers (or \
+--min-displays monitors do) or the timeout expires, which is useful in scripts that run \
+right after the machine wakes from sleep.\n\n\
+--ready is stricter: it waits until every enumerated monitor answers a DDC/CI ping (or \
+--min-ready of them do), printing which monitors are still being waited for on stderr. \
+If the timeout expires first, dispman exits with code 2.\n\n\
+With --full, every monitor is pinged and its capabilities string is read to report the \
+model as well, in any output format. This takes a second or two per monitor.\n\n\
+--filter lists only the monitors whose name contains the given text, ignoring case. \
+--filter-model does the same for the model in the capabilities string, which has to be \
+read from every monitor first; monitors whose capabilities cannot be read do not match. \
+When both are given, a monitor has to match both. --invert lists the monitors that do not \
+match instead. Display IDs are not renumbered.")]
+    Detect(DetectArgs),
 
     /// Get capabilities of a display
     #[command(long_about = "Query the MCCS capabilities string from a monitor and print a \
-parsed summary including model, protocol, MCCS version, and supported VCP feature codes.")]
-    Capabilities {
+parsed summary including model, protocol, MCCS version, and supported VCP feature codes. \
+With --json, the parsed capabilities are printed as JSON with VCP codes as \"0xNN\" keys.\n\n\
+With --diff A B, the capabilities of two displays are compared by VCP code instead, listing \
+the features only A supports, those only B supports, and those both support with different \
+enumerated values.\n\n\
+With --commands-only, only the DDC/CI commands from the `cmds(...)` section are printed, \
+each with its name (e.g. `0x01 (VCP Request)`).\n\n\
+Some monitors advertise different capabilities depending on the active input. With \
+--watch, the capabilities string is read again every --interval-secs seconds (default 5) \
+and each change is printed with a timestamp, e.g. \
+`[14:32:01] Capabilities changed: VCP 0x60 added value 0x11 (Hdmi1)`. Truncated replies \
+are ignored. Press Ctrl+C to stop.\n\n\
+Features that MCCS defines as read-only, such as the signal frequencies (0xAC, 0xAE) or \
+the firmware level (0xC9), are marked [RO]; the JSON output maps each code to true or \
+false under `writable`. --filter-writable leaves them out. The classification comes from \
+the MCCS tables, not from the monitor, so nothing is written to find out.")]
+    Capabilities(CapabilitiesArgs),
+
+    /// Get a VCP feature value
+    #[command(long_about = "Read the current value of a VCP feature. FEATURE may be a \
+well-known name (brightness, contrast, volume, input, power, color-temp, red-gain, \
+green-gain, blue-gain, hue, saturation, sharpness) or a raw code given as a \
+hex literal (0xNN) or decimal integer. The current and maximum values are printed in \
+both decimal and hex; with --percent, the current value is also shown as a percentage of \
+the maximum.\n\n\
+With --check-support, a warning is printed if the monitor's capabilities string does not \
+list the feature. The read is still attempted.\n\n\
+For health checks, --expect VALUE or --expect-percent PCT compares the reading against a \
+known state. The value is printed with a [PASS] or [FAIL] prefix, and dispman exits with \
+code 10 if it does not match. --tolerance N lets values within N of the expected one pass.")]
+    Get(GetArgs),
+
+    /// Set a VCP feature value
+    #[command(long_about = "Write a new value to a VCP feature. FEATURE accepts the same \
+forms as `dispman get`. VALUE is a non-negative integer in decimal or hex (0xNN). For the \
+input feature, VALUE may also be a source name such as hdmi1, dp-2, or usb-c (see \
+`dispman input list`). Input-source codes are not standardized across monitor vendors; \
+use `dispman capabilities` to discover the values your monitor accepts for code 0x60.\n\n\
+Instead of VALUE, exactly one of --percent, --increment, or --decrement may be given. \
+--percent PCT sets the feature to PCT percent (0-100) of the maximum the monitor reports, \
+rounded to the nearest step; it fails for features that report a maximum of zero.\n\n\
+--increment and --decrement adjust the current value by the given delta, clamped to the \
+range 0..=max reported by the same read.\n\n\
+With --verify, the value is read back after writing and the command fails if the \
+monitor reports something else (allowing for --verify-tolerance). Some monitors report \
+success for writes they then ignore. The read happens --verify-delay-ms (default 50) after \
+the write, and the value printed is the one read back, which may differ from VALUE if \
+the monitor clamps it.\n\n\
+With --transition-ms, the value is moved linearly from the current value to the new one \
+over the given time, with at most one write every 50 ms (or --steps writes). This only \
+works for continuous features such as brightness and contrast.\n\n\
+With --validate, the feature's maximum is read first and values above it are rejected \
+without being sent. Some monitor firmware misbehaves when given out-of-range values.\n\n\
+With --check-support, the monitor's capabilities string is fetched first and a warning is \
+printed if it does not list the feature. The write is still attempted, since some \
+monitors accept codes they do not advertise.\n\n\
+With --dry-run, the write is printed instead of sent. No DDC/CI commands are issued, so \
+percentages and relative changes are shown unresolved.")]
+    #[command(override_usage = "dispman set [OPTIONS] <FEATURE> \
+<VALUE|--percent <PCT>|--increment <DELTA>|--decrement <DELTA>>")]
+    Set(SetArgs),
+
+    /// Set several features at once, e.g. brightness=60 contrast=70
+    #[command(long_about = "Set several VCP features in one go. Each SETTING is \
+FEATURE=VALUE, where FEATURE is a name or code as for `set` and VALUE is decimal or 0xNN. \
+Every pair is parsed before anything is written, then the writes are sent back-to-back \
+with no delay in between to keep intermediate states on screen as short as possible. \
+DDC/CI has no transactions: if a write fails, the earlier ones stay applied.")]
+    SetMulti {
+        /// FEATURE=VALUE pairs (e.g. brightness=60 0x87=50)
+        #[arg(required = true, value_name = "SETTING")]
+        settings: Vec<String>,
         /// Display ID (index)
-        #[arg(short, long)]
+        #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
         display: Option<usize>,
+        /// Wait this many milliseconds after the last write
+        #[arg(long, value_name = "MS", default_value_t = 0)]
+        post_delay_ms: u64,
     },
 
-    /// Get a VCP feature value
-    #[command(long_about = "Read the current value of a VCP feature. FEATURE may be a \
-well-known name (brightness, contrast, volume, input, power) or a raw code given as a \
-hex literal (0xNN) or decimal integer. The value is printed in both decimal and hex.")]
-    Get {
-        /// Feature code (hex) or name (brightness, contrast, volume, input, power)
-        feature: String,
+    /// Run get and set commands from a file
+    #[command(long_about = "Read FILE and run each line as a `get` or `set` command, e.g. \
+`set brightness 80 --display 0` or `get contrast -d 1`. Displays are enumerated once, \
+before the first command, which makes long scripts much faster than calling dispman once \
+per setting. Blank lines and lines starting with `#` are ignored.\n\n\
+Every line is parsed before anything is run, so a typo fails the whole batch without \
+touching a monitor. Only FEATURE, VALUE and --display are accepted on a line; global flags \
+such as --display-name and --dry-run apply to every command. With --verbose, each command \
+is printed before it runs.\n\n\
+Failed commands are reported on stderr and the rest still run, unless --stop-on-error is \
+given. A summary of successes and failures is printed at the end, and dispman exits with \
+an error if any command failed.")]
+    Batch {
+        /// File with one command per line
+        file: std::path::PathBuf,
+        /// Stop at the first command that fails
+        #[arg(long)]
+        stop_on_error: bool,
+    },
+
+    /// Show the brightness, or set it to VALUE
+    #[command(long_about = "Shortcut for `dispman get brightness` or, when VALUE is given, \
+`dispman set brightness VALUE`. With --percent, VALUE is a percentage (0-100) of the \
+monitor's maximum, and the current value is shown as a percentage.")]
+    Brightness {
+        /// New value (omit to print the current value)
+        value: Option<u32>,
         /// Display ID (index)
-        #[arg(short, long)]
+        #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
         display: Option<usize>,
+        /// Treat VALUE, and show the current value, as a percentage of the maximum
+        #[arg(long)]
+        percent: bool,
     },
 
-    /// Set a VCP feature value
-    #[command(long_about = "Write a new value to a VCP feature. FEATURE accepts the same \
-forms as `dispman get`. VALUE is a non-negative integer. Input-source codes are not \
-standardized across monitor vendors; use `dispman capabilities` to discover the values \
-your monitor accepts for code 0x60.")]
-    Set {
-        /// Feature code (hex) or name (brightness, contrast, volume, input, power)
+    /// Show the contrast, or set it to VALUE
+    #[command(long_about = "Shortcut for `dispman get contrast` or, when VALUE is given, \
+`dispman set contrast VALUE`. With --percent, VALUE is a percentage (0-100) of the \
+monitor's maximum, and the current value is shown as a percentage.")]
+    Contrast {
+        /// New value (omit to print the current value)
+        value: Option<u32>,
+        /// Display ID (index)
+        #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+        display: Option<usize>,
+        /// Treat VALUE, and show the current value, as a percentage of the maximum
+        #[arg(long)]
+        percent: bool,
+    },
+
+    /// Show the speaker volume, or set it to VALUE
+    #[command(long_about = "Shortcut for `dispman get volume` or, when VALUE is given, \
+`dispman set volume VALUE`. With --percent, VALUE is a percentage (0-100) of the \
+monitor's maximum, and the current value is shown as a percentage.")]
+    Volume {
+        /// New value (omit to print the current value)
+        value: Option<u32>,
+        /// Display ID (index)
+        #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+        display: Option<usize>,
+        /// Treat VALUE, and show the current value, as a percentage of the maximum
+        #[arg(long)]
+        percent: bool,
+    },
+
+    /// Show a display's geometry settings, or run auto setup
+    #[command(long_about = "Read the MCCS geometry features of a display and print them as \
+a table: horizontal and vertical position and size, the horizontal and vertical frequency \
+of the input signal, and the auto setup control. Features the display does not answer are \
+shown as `-`. Most flat panels only implement these for analog inputs, if at all.\n\n\
+`geometry reset` writes 0x01 to VCP 0x1E (Auto Setup), which makes the display fit the \
+image to the current signal.")]
+    Geometry {
+        #[command(subcommand)]
+        action: Option<GeometryAction>,
+        /// Display ID (index)
+        #[arg(
+            short,
+            long,
+            global = true,
+            value_parser = DisplayIdParser,
+            hide_possible_values = true
+        )]
+        display: Option<usize>,
+    },
+
+    /// Query or change a display's color temperature preset
+    #[command(long_about = "Read or change VCP 0x14 (Select Color Preset) using friendly \
+names. `color-temp set` accepts srgb, native, 5000k, 6500k, 7500k, 9300k, or user \
+(case-insensitive), or a raw value in hex (0xNN) or decimal. Without a subcommand, the \
+current preset is printed.\n\n\
+`color-temp list` shows every recognized preset and marks the ones the display lists in \
+its capabilities string, including any vendor-specific values.")]
+    ColorTemp {
+        #[command(subcommand)]
+        action: Option<ColorTempAction>,
+        /// Display ID (index)
+        #[arg(
+            short,
+            long,
+            global = true,
+            value_parser = DisplayIdParser,
+            hide_possible_values = true
+        )]
+        display: Option<usize>,
+    },
+
+    /// Query or change a display's picture mode
+    #[command(long_about = "Read or change VCP 0xDC (Display Application), the picture \
+mode many monitors offer as Standard, Movie, Game and so on. `preset set` accepts standard, \
+productivity, mixed, movie, user, game, sports, professional, intermediate-power, \
+low-power, demo, or dynamic-contrast (case-insensitive), or a raw value in hex (0xNN) or \
+decimal for vendor-specific modes. Without a subcommand, the current mode is printed.\n\n\
+`preset list` shows the modes the display lists for VCP 0xDC in its capabilities string, \
+including vendor-specific values. If the capabilities cannot be read, every named mode is \
+listed instead.")]
+    Preset {
+        #[command(subcommand)]
+        action: Option<PresetAction>,
+        /// Display ID (index)
+        #[arg(
+            short,
+            long,
+            global = true,
+            value_parser = DisplayIdParser,
+            hide_possible_values = true
+        )]
+        display: Option<usize>,
+    },
+
+    /// Watch a VCP feature for changes
+    #[command(long_about = "Poll a VCP feature every --interval-ms milliseconds and print a \
+timestamped line whenever its value changes. Runs until interrupted with Ctrl-C, or until \
+--count changes have been seen. Useful for spotting monitors that adjust brightness on \
+their own in response to ambient light.")]
+    Watch {
+        /// Feature code (hex) or name (e.g. brightness, contrast, input, sharpness)
+        #[arg(value_parser = FeatureParser, hide_possible_values = true)]
         feature: String,
-        /// Value to set
-        value: u32,
         /// Display ID (index)
-        #[arg(short, long)]
+        #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+        display: Option<usize>,
+        /// Polling interval in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        /// Exit after this many changes
+        #[arg(long)]
+        count: Option<usize>,
+    },
+
+    /// Copy settings from one display to another
+    #[command(long_about = "Read FEATURES from the SOURCE display and write them to the \
+TARGET display, e.g. to give two monitors the same brightness. FEATURES is a \
+comma-separated list of names or codes and defaults to brightness, contrast, sharpness, \
+and color-temp.\n\n\
+If the monitors report different maximums for a continuous feature, the value is scaled \
+so the target ends up at the same fraction of its range. Each feature is printed with its \
+old and new value on the target.")]
+    Clone {
+        /// Display ID to copy from
+        #[arg(value_parser = DisplayIdParser, hide_possible_values = true)]
+        source: usize,
+        /// Display ID to copy to
+        #[arg(value_parser = DisplayIdParser, hide_possible_values = true)]
+        target: usize,
+        /// Features to copy, comma-separated (e.g. brightness,contrast,0x87)
+        #[arg(long, value_name = "FEATURES")]
+        features: Option<String>,
+    },
+
+    /// Restore factory defaults on a display
+    #[command(long_about = "Send an MCCS restore command to a monitor. --factory restores \
+every setting to its factory default (VCP 0x04); --color-only restores just the color \
+settings (VCP 0x08). One of the two must be given. With --confirm, dispman asks for \
+confirmation on the terminal before sending anything.")]
+    Reset {
+        /// Restore all factory defaults
+        #[arg(long, required_unless_present = "color_only", conflicts_with = "color_only")]
+        factory: bool,
+        /// Restore only the factory color defaults
+        #[arg(long)]
+        color_only: bool,
+        /// Display ID (index)
+        #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+        display: Option<usize>,
+        /// Ask for confirmation before resetting
+        #[arg(long)]
+        confirm: bool,
+    },
+
+    /// Show a display's EDID
+    #[command(long_about = "Read the monitor's EDID from the operating system and print \
+the manufacturer ID, product code, serial number, year of manufacture, and native \
+resolution. EDID is not read over DDC/CI, so it works even when DDC/CI is disabled in the \
+monitor's menu. With --raw, the EDID bytes are printed as a hex dump instead.")]
+    Edid {
+        /// Display ID (index)
+        #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+        display: Option<usize>,
+        /// Print the raw EDID bytes as hex
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Query or change a display's power mode
+    #[command(long_about = "Read or change VCP 0xD6 (Power Mode). When changing the mode, \
+the previous state is included in the message, e.g. `Display 0: power mode set to Off \
+(was On)`. With --all-displays, every connected monitor is changed; failures are listed \
+at the end. Note that a monitor in a deep power-off state may \
+stop answering DDC/CI until it is woken by other means.")]
+    Power {
+        #[command(subcommand)]
+        action: PowerAction,
+        /// Display ID (index)
+        #[arg(
+            short,
+            long,
+            global = true,
+            value_parser = DisplayIdParser,
+            hide_possible_values = true
+        )]
         display: Option<usize>,
     },
 
+    /// Query or switch a display's input source
+    #[command(long_about = "Read or change VCP 0x60 (Input Source) using friendly names. \
+`input switch` accepts names such as hdmi1, dp2, displayport1, dvi1, vga1, or usb-c \
+(case-insensitive), or a raw value in hex (0xNN) or decimal for vendor-specific inputs. \
+`input list` shows every recognized name and the value it maps to.\n\n\
+Input values are not standardized across vendors; in particular, USB-C inputs often use \
+a DisplayPort or vendor-specific value. `input switch usb-c` uses the highest \
+vendor-specific value the monitor advertises for VCP 0x60, falling back to 0x13. Use \
+`dispman capabilities` to see the values your monitor accepts.\n\n\
+`input next` and `input prev` step through the inputs in the order the capabilities \
+string lists them for VCP 0x60, wrapping around at either end. If the current input is \
+not listed, they switch to the first listed one.")]
+    Input {
+        #[command(subcommand)]
+        action: InputAction,
+        /// Display ID (index)
+        #[arg(
+            short,
+            long,
+            global = true,
+            value_parser = DisplayIdParser,
+            hide_possible_values = true
+        )]
+        display: Option<usize>,
+    },
+
+    /// Reference information about VCP features
+    #[command(long_about = "Offline reference for the VCP features dispman knows by name. \
+No monitor is contacted.")]
+    Vcp {
+        #[command(subcommand)]
+        command: VcpCommands,
+    },
+
+    /// Generate a shell completion script
+    #[command(long_about = "Write a completion script for SHELL to stdout. For example:\n\n\
+    dispman completions bash >> ~/.bash_completion\n\
+    dispman completions zsh > ~/.zfunc/_dispman\n\
+    dispman completions fish > ~/.config/fish/completions/dispman.fish\n\
+    dispman completions powershell >> $PROFILE")]
+    Completions {
+        /// Shell to generate completions for
+        shell: clap_complete::Shell,
+    },
+
+    /// Manage the config file
+    #[command(long_about = "Maintenance commands for the user config file. Config files \
+written by older versions of dispman are upgraded automatically on load; the original is \
+kept as config.toml.bak next to it.")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
     /// Manage profiles
     Profile {
         #[command(subcommand)]
         command: ProfileCommands,
     },
 
+    /// Save and restore the desktop arrangement (Windows)
+    Layout {
+        #[command(subcommand)]
+        command: LayoutCommands,
+    },
+
+    /// Summarize every connected display, e.g. for a bug report
+    #[command(long_about = "Print an overview of every connected monitor: its ID, name, \
+stable identifier and connection, whether it answers DDC/CI, its current brightness, \
+contrast, input source and power mode, and the saved profiles that have values for it. \
+Monitors that do not answer DDC/CI are not queried further. The output starts with the \
+dispman version and platform, so it can be pasted into a bug report as is.\n\n\
+With --json, the monitors are printed as a JSON array instead.")]
+    Info {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Load profiles automatically when the system sleeps or wakes (Windows)
+    #[command(long_about = "Stay running and load the profiles named in the [daemon] section \
+of the config file on power events: wake_profile after the system resumes from sleep or \
+hibernation, suspend_profile just before it sleeps. Either key may be omitted. The config \
+is re-read on every event, so profile edits take effect without restarting the daemon.\n\n\
+Only supported on Windows. To start it at logon without a console window, register it as \
+a service (e.g. with NSSM or `sc create`) or as a scheduled task triggered at logon.")]
+    Daemon,
+
+    /// Load profiles at set times of day
+    #[command(long_about = "Keep a list of profiles to load at set times, stored in the \
+config file. dispman does not stay running to watch the clock: `schedule run` loads the \
+profiles due in the current minute and exits, so register it with Task Scheduler (or \
+cron) to run every minute.")]
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommands,
+    },
+
     /// Inspect all settings for a display
     #[command(long_about = "Read and print the current values of the most common VCP \
 features (brightness, contrast, input source, volume, power mode) for a single display. \
-Features the monitor does not report are labelled `Not supported`.")]
-    Inspect {
+Features the monitor does not report are labelled `Not supported`. The MCCS version from \
+the capabilities string is printed first, e.g. \
+`MCCS 2.1 (supports: basic VCP, capabilities string)`.\n\n\
+With --all-codes, every VCP code from 0x00 to 0xFF is queried and only the codes the \
+monitor answers are listed. A full scan is slow, mostly because codes the monitor does \
+not implement are retried. --timeout-ms stops retrying a code once the given number of \
+milliseconds have passed and treats it as unsupported; a single DDC/CI call cannot be cut \
+short, so a read may overrun by one call. --ddc-retries 0 avoids the retries entirely.\n\n\
+With --json, the features that answered are printed as an array of objects with the \
+fields code (\"0xNN\"), name, current, max, and is_continuous. Unsupported features are \
+left out. --json is shorthand for --output-format json; --output-format csv prints the \
+same fields as CSV. --output writes the JSON or CSV to a file, keeping it apart from \
+warnings on stderr.\n\n\
+With --timing, the text output also includes the monitor's timing report (horizontal and \
+vertical sync frequency of the current signal), which helps diagnose refresh rate \
+problems. Not every monitor or driver provides one.\n\n\
+With --changes-since PROFILE, every feature the profile stores for the display is read \
+and shown next to its saved value, marked [OK] or [CHANGED], followed by the percentage \
+of settings that still match.\n\n\
+With --snapshot FILE, every feature listed in the monitor's capabilities string is read \
+and saved to FILE as JSON: `display_name` and `stable_id` identify the monitor, `values` \
+maps each code (\"0xNN\") to its current and maximum value, and `failed_codes` lists the \
+codes that could not be read. `profile save --from-snapshot` turns the file into a profile.\n\n\
+--feature reads only the listed features instead of the common ones, given as a \
+comma-separated list of names or codes (e.g. `brightness,0x12,0x87`). Combined with \
+--all-displays, the features are read from every connected monitor and shown as one \
+table with a column per display.\n\n\
+With --batch, every feature is read before anything is printed, one after another over \
+the same monitor handle, instead of printing each value as it arrives.")]
+    Inspect(InspectArgs),
+}
+
+/// Arguments of `dispman detect`.
+#[derive(Args)]
+pub struct DetectArgs {
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+    /// Keep retrying for up to this many milliseconds until displays appear
+    #[arg(long, value_name = "TIMEOUT_MS")]
+    pub wait: Option<u64>,
+    /// With --wait, the number of displays to wait for
+    #[arg(
+        long,
+        default_value_t = 1,
+        requires = "wait",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub min_displays: usize,
+    /// Also check for DDC/CI and read each monitor's model (slow)
+    #[arg(long)]
+    pub full: bool,
+    /// Keep retrying for up to this many milliseconds until every display answers DDC/CI
+    #[arg(long, value_name = "TIMEOUT_MS", conflicts_with = "wait")]
+    pub ready: Option<u64>,
+    /// With --ready, succeed once this many displays answer
+    #[arg(
+        long,
+        requires = "ready",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub min_ready: Option<usize>,
+    /// Only list displays whose name contains this text (case-insensitive)
+    #[arg(long, value_name = "PATTERN")]
+    pub filter: Option<String>,
+    /// Only list displays whose model contains this text (reads capabilities)
+    #[arg(long, value_name = "PATTERN")]
+    pub filter_model: Option<String>,
+    /// List the displays that do not match --filter or --filter-model
+    #[arg(long)]
+    pub invert: bool,
+}
+
+/// Arguments of `dispman capabilities`.
+#[derive(Args)]
+pub struct CapabilitiesArgs {
+    /// Display ID (index)
+    #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+    pub display: Option<usize>,
+    /// Compare the capabilities of two displays
+    #[arg(long, num_args = 2, value_names = ["A", "B"], conflicts_with = "display")]
+    pub diff: Option<Vec<usize>>,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+    /// Only list the supported DDC/CI commands
+    #[arg(long, conflicts_with = "diff")]
+    pub commands_only: bool,
+    /// Keep polling and print changes to the capabilities
+    #[arg(long, conflicts_with_all = ["diff", "commands_only", "json"])]
+    pub watch: bool,
+    /// Seconds between polls with --watch
+    #[arg(long, value_name = "N", default_value_t = 5, requires = "watch")]
+    pub interval_secs: u64,
+    /// Leave out features MCCS defines as read-only
+    #[arg(long, conflicts_with_all = ["diff", "commands_only", "watch"])]
+    pub filter_writable: bool,
+}
+
+/// Arguments of `dispman get`.
+#[derive(Args)]
+pub struct GetArgs {
+    /// Feature code (hex) or name (e.g. brightness, contrast, input, sharpness)
+    #[arg(value_parser = FeatureParser, hide_possible_values = true)]
+    pub feature: String,
+    /// Display ID (index)
+    #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+    pub display: Option<usize>,
+    /// Show the current value as a percentage of the maximum
+    #[arg(long)]
+    pub percent: bool,
+    /// Warn if the monitor's capabilities do not list the feature
+    #[arg(long)]
+    pub check_support: bool,
+    /// Exit with code 10 unless the value equals VALUE (decimal or 0xNN)
+    #[arg(long, value_name = "VALUE", group = "expectation")]
+    pub expect: Option<String>,
+    /// Exit with code 10 unless the value is PCT percent of the maximum
+    #[arg(long, value_name = "PCT", group = "expectation", value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub expect_percent: Option<u32>,
+    /// How far the value may be from the expected one and still pass
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "expectation")]
+    pub tolerance: u32,
+}
+
+/// Arguments of `dispman set`.
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("target")
+        .required(true)
+        .args(["value", "percent", "increment", "decrement"])
+))]
+pub struct SetArgs {
+    /// Feature code (hex) or name (e.g. brightness, contrast, input, sharpness)
+    #[arg(value_parser = FeatureParser, hide_possible_values = true)]
+    pub feature: String,
+    /// Value to set (decimal or 0xNN; input sources also accept names like hdmi1)
+    pub value: Option<String>,
+    /// Display ID (index)
+    #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+    pub display: Option<usize>,
+    /// Set the feature to PCT percent of its maximum
+    #[arg(long, value_name = "PCT", value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub percent: Option<u32>,
+    /// Raise the current value by DELTA instead of setting VALUE
+    #[arg(long, value_name = "DELTA")]
+    pub increment: Option<u32>,
+    /// Lower the current value by DELTA instead of setting VALUE
+    #[arg(long, value_name = "DELTA")]
+    pub decrement: Option<u32>,
+    /// Read the value back after writing and fail if it differs
+    #[arg(long)]
+    pub verify: bool,
+    /// Allowed difference between the written and read-back value
+    #[arg(long, default_value_t = 0, requires = "verify")]
+    pub verify_tolerance: u32,
+    /// Milliseconds to wait before reading the value back
+    #[arg(long, value_name = "MS", default_value_t = 50, requires = "verify")]
+    pub verify_delay_ms: u64,
+    /// Fade to the new value over this many milliseconds
+    #[arg(long, value_name = "MS")]
+    pub transition_ms: Option<u64>,
+    /// Number of intermediate writes for --transition-ms
+    #[arg(long, requires = "transition_ms", value_parser = clap::value_parser!(u32).range(1..))]
+    pub steps: Option<u32>,
+    /// Refuse values above the maximum the monitor reports
+    #[arg(long)]
+    pub validate: bool,
+    /// Warn if the monitor's capabilities do not list the feature
+    #[arg(long)]
+    pub check_support: bool,
+}
+
+/// Arguments of `dispman inspect`.
+#[derive(Args)]
+pub struct InspectArgs {
+    /// Display ID (index)
+    #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+    pub display: Option<usize>,
+    /// Scan every VCP code (0x00-0xFF) instead of the common ones
+    #[arg(long)]
+    pub all_codes: bool,
+    /// Only read these features, comma-separated (e.g. brightness,0x12)
+    #[arg(
+        long,
+        value_name = "FEATURES",
+        conflicts_with_all = ["all_codes", "changes_since", "snapshot"]
+    )]
+    pub feature: Option<String>,
+    /// Stop retrying a code after this many milliseconds
+    #[arg(long, requires = "all_codes")]
+    pub timeout_ms: Option<u64>,
+    /// Print the readings as JSON
+    #[arg(long)]
+    pub json: bool,
+    /// Write JSON or CSV output to FILE instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<std::path::PathBuf>,
+    /// Also show the horizontal and vertical frequency of the input signal
+    #[arg(long)]
+    pub timing: bool,
+    /// Compare the current values with those saved in PROFILE
+    #[arg(long, value_name = "PROFILE", conflicts_with_all = ["all_codes", "json", "output", "timing"])]
+    pub changes_since: Option<String>,
+    /// Save every feature listed in the capabilities string to FILE as JSON
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["all_codes", "json", "output", "timing", "changes_since"]
+    )]
+    pub snapshot: Option<std::path::PathBuf>,
+    /// Read every feature first, over the same monitor handle, then print them
+    #[arg(long, conflicts_with_all = ["all_codes", "changes_since", "snapshot"])]
+    pub batch: bool,
+}
+
+#[derive(Subcommand)]
+pub enum PowerAction {
+    /// Print the current power mode
+    Get,
+    /// Put the display in standby
+    Standby,
+    /// Suspend the display
+    Suspend,
+    /// Turn the display off
+    Off,
+    /// Turn the display on
+    On,
+}
+
+#[derive(Subcommand)]
+pub enum InputAction {
+    /// Print the current input source
+    Get,
+    /// Switch to another input source
+    Switch {
+        /// Source name (e.g. hdmi1, dp1, usb-c) or raw value (e.g. 0x11)
+        source: String,
+    },
+    /// List recognized input source names
+    List,
+    /// Switch to the next input the display advertises
+    Next,
+    /// Switch to the previous input the display advertises
+    Prev,
+}
+
+/// One line of a `batch` file.
+#[derive(Parser)]
+#[command(name = "batch", no_binary_name = true)]
+pub struct BatchLine {
+    #[command(subcommand)]
+    pub command: BatchCommand,
+}
+
+#[derive(Subcommand)]
+pub enum BatchCommand {
+    /// Read a VCP feature
+    Get {
+        /// Feature name or code (e.g. brightness, 0x10)
+        feature: String,
         /// Display ID (index)
         #[arg(short, long)]
         display: Option<usize>,
     },
+    /// Write a VCP feature
+    Set {
+        /// Feature name or code (e.g. brightness, 0x10)
+        feature: String,
+        /// Value in decimal or 0xNN, or an input source name
+        value: String,
+        /// Display ID (index)
+        #[arg(short, long)]
+        display: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GeometryAction {
+    /// Print the geometry features as a table
+    Show,
+    /// Run the display's auto setup (VCP 0x1E)
+    Reset,
+}
+
+#[derive(Subcommand)]
+pub enum PresetAction {
+    /// Print the current picture mode
+    Get,
+    /// Switch to another picture mode
+    Set {
+        /// Mode name (e.g. movie, game, user) or raw value (e.g. 0x03)
+        preset: String,
+    },
+    /// List the picture modes the display supports
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ColorTempAction {
+    /// Print the current color preset
+    Get,
+    /// Switch to another color preset
+    Set {
+        /// Preset name (e.g. srgb, 6500k, user) or raw value (e.g. 0x05)
+        preset: String,
+    },
+    /// List recognized presets and mark the ones the display supports
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ScheduleCommands {
+    /// Load a profile at a time of day
+    #[command(long_about = "Load PROFILE (a profile or chain) at TIME, given as HH:MM in \
+24-hour local time. --days limits the entry to the listed days, comma-separated (e.g. \
+mon,tue or monday); `weekdays` and `weekends` are accepted as well. Without --days the \
+entry applies every day.")]
+    Add {
+        /// Profile or chain to load
+        profile: String,
+        /// Time of day, HH:MM (e.g. 08:30)
+        time: String,
+        /// Days to run on, comma-separated (e.g. mon,wed,fri or weekdays)
+        #[arg(long)]
+        days: Option<String>,
+    },
+    /// List schedule entries
+    List,
+    /// Remove a schedule entry
+    Remove {
+        /// Entry ID, as shown by `schedule list`
+        id: usize,
+    },
+    /// Load the profiles due now and exit
+    #[command(long_about = "Load every scheduled profile whose time falls in the current \
+minute and whose days include today, then exit. Meant to be run every minute by Task \
+Scheduler or cron. A profile that fails to load does not stop the others.")]
+    Run,
+}
+
+#[derive(Subcommand)]
+pub enum LayoutCommands {
+    /// Save the position, resolution, refresh rate and rotation of every display
+    #[command(long_about = "Save the current desktop arrangement under NAME: the position, \
+resolution, refresh rate and rotation of every display attached to the desktop. Unlike \
+profiles, layouts are applied through the Windows display settings, not DDC/CI.")]
+    Save {
+        /// Layout name
+        name: String,
+    },
+    /// Restore a saved arrangement
+    #[command(long_about = "Restore the desktop arrangement saved as NAME. Every display in \
+the layout must be connected; otherwise nothing is changed. The new settings are \
+written to the registry, so they persist across reboots.\n\n\
+With --also-profile, the given DDC/CI profile is loaded once the layout is applied, e.g. \
+to switch both arrangement and brightness when docking.")]
+    Apply {
+        /// Layout name
+        name: String,
+        /// Load this profile after applying the layout
+        #[arg(long, value_name = "PROFILE")]
+        also_profile: Option<String>,
+    },
+    /// List saved layouts
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ChainCommands {
+    /// Save a sequence of profiles under a name
+    #[command(long_about = "Save PROFILES (comma-separated, applied in order) as the chain \
+NAME. Afterwards `dispman profile load NAME` loads the whole chain. NAME must not clash \
+with an existing profile.")]
+    Save {
+        /// Chain name
+        name: String,
+        /// Profile names, comma-separated (e.g. base,color)
+        profiles: String,
+    },
+    /// Delete a saved chain
+    Delete {
+        /// Chain name
+        name: String,
+    },
+    /// List saved chains
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum VcpCommands {
+    /// List the known VCP features
+    #[command(long_about = "Print a table of every VCP feature dispman knows by name, with \
+its code, category, and whether MCCS defines it as continuous or non-continuous. \
+--category restricts the table to one category.")]
+    List {
+        /// Only list features in this category (image, color, geometry, audio, misc)
+        #[arg(long)]
+        category: Option<String>,
+        /// Sort the table by code or by name
+        #[arg(long, value_enum, default_value_t = VcpSortKey::Code)]
+        sort_by: VcpSortKey,
+    },
+
+    /// Describe a single VCP feature
+    #[command(long_about = "Print the name, category, type, and a description of a VCP \
+feature. CODE accepts the same forms as `dispman get`.")]
+    Describe {
+        /// Feature code (hex) or name
+        #[arg(value_parser = FeatureParser, hide_possible_values = true)]
+        code: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OnFailure {
+    /// Write the remaining values, then exit with an error
+    Continue,
+    /// Stop at the first failed write
+    Abort,
+    /// Write the remaining values and exit successfully
+    Warn,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum VcpSortKey {
+    Code,
+    Name,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Copy the config file to config.toml.bak
+    Backup,
+
+    /// Delete all saved profiles
+    #[command(long_about = "Remove every profile from the config file. Without --force, \
+the number of profiles that would be deleted is printed and confirmation is read from \
+stdin.")]
+    Reset {
+        /// Delete without asking for confirmation
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Print the path of the config file
+    Path,
+
+    /// Print the full config as TOML
+    Show,
+
+    /// Print a JSON Schema for the config file
+    #[command(long_about = "Print a JSON Schema (draft 2020-12) describing the config file, \
+with a description for each field. Editors can use it to validate a hand-edited \
+config.toml; with the Even Better TOML extension for VS Code, save the output as \
+dispman.schema.json and add `#:schema ./dispman.schema.json` as the first line of the \
+config file. --yaml prints the same schema as YAML.")]
+    Schema {
+        /// Print YAML instead of JSON
+        #[arg(long)]
+        yaml: bool,
+    },
+
+    /// Print the value of a setting
+    #[command(long_about = "Print the current value of a global setting, or its built-in \
+default if it has not been set. KEY is one of default_display, ddc_retry_count, \
+ddc_delay_ms, default_delay_between_sets_ms, or retry_on_empty.")]
+    Get {
+        /// Setting name
+        key: String,
+    },
+
+    /// Change a setting
+    #[command(long_about = "Store a global setting in the [settings] section of the config \
+file. default_display is the display ID used when a command is given no --display \
+(the DISPMAN_DEFAULT_DISPLAY environment variable takes precedence over it); \
+ddc_retry_count and ddc_delay_ms are used when --ddc-retries and --ddc-delay-ms are not \
+given.\n\n\
+With retry_on_empty set to true, dispman looks for the monitors again if none are found \
+or none of them answers DDC/CI, up to 5 times, waiting 200 ms and then twice as long each \
+time. This helps in scripts that run right after the system wakes up, when monitors are \
+briefly missing or unresponsive.")]
+    Set {
+        /// Setting name
+        key: String,
+        /// New value
+        value: String,
+    },
+
+    /// Print every setting and its value
+    List,
 }
 
 #[derive(Subcommand)]
@@ -91,22 +1022,329 @@ pub enum ProfileCommands {
     /// Save current settings as a profile
     #[command(long_about = "Capture the current brightness, contrast, input source, and \
 volume of every connected monitor and store them under NAME in the user config file. \
-If NAME already exists it is overwritten.")]
+If NAME already exists it is overwritten.\n\n\
+--include adds further features (hex codes or names, comma-separated) to the default set. \
+--include-all instead reads every feature listed in each monitor's capabilities string; \
+this can take a while, and progress is shown on stderr with --verbose. Only features that \
+can be read are stored. --continuous-only also leaves out non-continuous features (input \
+source, power mode, factory resets, ...), keeping only values such as brightness that are \
+safe to replay. --batch reads each monitor's features in one batch over the same monitor \
+handle instead of one at a time.\n\n\
+With --display or --display-name, only that monitor is saved. With --append, the monitors \
+being saved are merged into an existing profile NAME, replacing their own entries and \
+keeping those of other monitors.\n\n\
+--interactive reads every feature as --include-all does, then shows each value and asks \
+whether to include it (Enter means yes). --yes-to-all answers yes to every prompt.\n\n\
+With --use-alias, monitors that have a label in the [aliases] section of the config file \
+are saved under that label, so the profile still applies after the monitor moves to \
+another port. `profile load` looks up a monitor's alias before its stable identifier.\n\n\
+--exclude leaves the given features out, even if --include or --include-all would save \
+them. The list is stored with the profile, and `profile load` skips those features if a \
+value for them is stored anyway (for example from an earlier save with --append).\n\n\
+--name-from-hostname saves the profile as NAME-HOSTNAME, so machines sharing a synced \
+config file keep their own copies; `profile load --name-from-hostname NAME` picks this \
+machine's copy. The hostname is recorded with every saved profile and shown by \
+`profile list --verbose`.\n\n\
+--timestamp saves the profile as NAME-YYYYMMDDTHHMMSS (the current UTC time) instead of \
+overwriting NAME, so earlier versions are kept. --keep N then deletes all but the N newest \
+timestamped versions of NAME.\n\n\
+--from-snapshot reads a file written by `inspect --snapshot` instead of the monitors, so \
+no monitor needs to be connected. The values in its `values` map (only the continuous ones \
+with --continuous-only) are saved for the monitor whose stable identifier the snapshot \
+records; `failed_codes` are ignored. Remove codes from the JSON first to leave them out of \
+the profile.")]
     Save {
         /// Profile name
         name: String,
+        /// Save every readable feature listed in the monitor's capabilities
+        #[arg(long)]
+        include_all: bool,
+        /// Additional features to save, comma-separated (e.g. 0x87,0x8A)
+        #[arg(long, value_name = "CODES")]
+        include: Option<String>,
+        /// Leave out non-continuous features such as input source and power mode
+        #[arg(long)]
+        continuous_only: bool,
+        /// Only save this display ID (index)
+        #[arg(
+            short,
+            long,
+            visible_alias = "on-display",
+            value_parser = DisplayIdParser,
+            hide_possible_values = true
+        )]
+        display: Option<usize>,
+        /// Merge into an existing profile instead of replacing it
+        #[arg(long)]
+        append: bool,
+        /// Ask which of the readable features to include
+        #[arg(short, long)]
+        interactive: bool,
+        /// With --interactive, include every readable feature without asking
+        #[arg(long, requires = "interactive")]
+        yes_to_all: bool,
+        /// Key each display by its [aliases] label instead of its stable ID
+        #[arg(long)]
+        use_alias: bool,
+        /// Features never to save or load, comma-separated (e.g. power,0xD6)
+        #[arg(long, value_name = "CODES")]
+        exclude: Option<String>,
+        /// Append this machine's hostname to NAME (e.g. work-DESKTOP-ABC123)
+        #[arg(long)]
+        name_from_hostname: bool,
+        /// Append the current time to NAME (e.g. work-20240115T143022)
+        #[arg(long, conflicts_with = "append")]
+        timestamp: bool,
+        /// With --timestamp, keep only the N newest versions of NAME
+        #[arg(
+            long,
+            value_name = "N",
+            requires = "timestamp",
+            value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        keep: Option<usize>,
+        /// Create the profile from an `inspect --snapshot` file
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["include_all", "include", "display", "interactive", "use_alias"]
+        )]
+        from_snapshot: Option<std::path::PathBuf>,
+        /// Read each display's features in one batch over the same monitor handle
+        #[arg(long, conflicts_with = "from_snapshot")]
+        batch: bool,
     },
 
     /// Load/apply a profile
     #[command(long_about = "Apply a previously saved profile. For each monitor currently \
 connected, dispman looks up the monitor's stable identifier in the profile and writes \
-back the stored VCP values. Monitors not present in the profile are skipped; failures on \
-individual features are reported on stderr but do not abort the command.")]
+back the stored VCP values. Monitors not present in the profile are skipped. A summary \
+such as `Applied 12/14 settings (2 failed)` is printed at the end.\n\n\
+Failed writes are reported on stderr. --on-failure decides what happens next: `continue` \
+(the default) writes the remaining values and then exits with an error, `abort` stops at \
+the first failure, and `warn` writes the remaining values and exits successfully.\n\n\
+NAME may also be a chain saved with `profile chain save`. --chain applies several profiles \
+in order instead; when two profiles set the same feature on the same monitor, the later \
+one wins.\n\n\
+Before writing, every monitor in the profile is checked for a DDC/CI response. If any \
+monitor does not answer, nothing is changed unless --skip-unresponsive is given, in which \
+case those monitors are skipped with a warning.\n\n\
+With --display or --display-name, only the profile entry for that monitor is applied; \
+the command fails if the profile has no entry for it.\n\n\
+With --concurrent, all monitors are written at the same time instead of one after another. \
+This requires a build with the `async` feature. With --dry-run, every write is printed \
+instead of sent, followed by a summary count.\n\n\
+--preview reads the current value of every feature in the profile and prints it next to \
+the profile's value (e.g. `0x10 Brightness: 63 -> 80`), marking values that already match \
+with [unchanged]. Nothing is written. dispman exits with 0 if loading would change \
+nothing and 1 otherwise, so scripts can check whether a profile is already applied.\n\n\
+With --verbose, each write is printed on stderr as it is made, e.g. \
+`[1/8] Display 0 (DEL U2722D): setting Brightness to 80...`. --progress instead shows a \
+progress bar on stderr after a `Loading profile 'work' (8 settings across 2 displays)` \
+line; combined with --verbose, the per-write lines take the place of the bar. --quiet \
+suppresses both.\n\n\
+--skip-unchanged reads every feature before writing it and leaves it alone if the \
+monitor already reports the profile's value, avoiding the delay and, on some monitors, \
+the brief flicker of a write. The summary counts these, e.g. \
+`Applied 3/8 settings (5 already set)`.")]
     Load {
+        /// Profile or chain name
+        #[arg(required_unless_present = "chain", conflicts_with = "chain")]
+        name: Option<String>,
+        /// Apply several profiles in order, comma-separated (e.g. base,color)
+        #[arg(long, value_name = "PROFILES")]
+        chain: Option<String>,
+        /// Only apply the profile to this display ID (index)
+        #[arg(short, long, value_parser = DisplayIdParser, hide_possible_values = true)]
+        display: Option<usize>,
+        /// Write to all displays in parallel
+        #[arg(long)]
+        concurrent: bool,
+        /// Skip displays that do not answer DDC/CI instead of aborting
+        #[arg(long)]
+        skip_unresponsive: bool,
+        /// What to do when writing a value fails
+        #[arg(
+            long,
+            value_enum,
+            value_name = "POLICY",
+            default_value_t = OnFailure::Continue,
+            conflicts_with = "concurrent"
+        )]
+        on_failure: OnFailure,
+        /// Prefer NAME suffixed with this machine's hostname, if saved
+        #[arg(long, conflicts_with = "chain")]
+        name_from_hostname: bool,
+        /// Show current and profile values side by side without writing
+        #[arg(long, conflicts_with_all = ["concurrent", "on_failure"])]
+        preview: bool,
+        /// Show a progress bar on stderr while writing
+        #[arg(long, conflicts_with_all = ["concurrent", "preview"])]
+        progress: bool,
+        /// Read each value first and only write the ones that differ
+        #[arg(long, conflicts_with_all = ["concurrent", "preview"])]
+        skip_unchanged: bool,
+    },
+
+    /// Manage named chains of profiles
+    Chain {
+        #[command(subcommand)]
+        command: ChainCommands,
+    },
+
+    /// List available profiles
+    #[command(long_about = "List saved profiles in alphabetical order, with the number of \
+displays and values each one holds and when it was first saved. Profiles saved by \
+versions of dispman that did not record a creation time show `-`.\n\n\
+With --verbose, each profile is followed by the values it stores for every display, \
+decoded by feature (e.g. `brightness=80, input=Hdmi1`). Combined with --json, the \
+profiles and their values are printed as a JSON array.")]
+    List {
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Delete a saved profile
+    #[command(long_about = "Remove the profile NAME from the user config file. Fails if \
+no profile with that name exists.")]
+    Delete {
         /// Profile name
         name: String,
     },
 
-    /// List available profiles
-    List,
+    /// Compare two saved profiles
+    #[command(long_about = "Show every VCP value that differs between profiles A and B, \
+grouped by display. Values missing from one profile are shown as `—`.")]
+    Diff {
+        /// First profile name
+        a: String,
+        /// Second profile name
+        b: String,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check a profile against the connected displays' capabilities
+    #[command(long_about = "Compare the values NAME stores for each connected monitor with \
+the monitor's capabilities string, without writing anything. A value is reported if its \
+feature is not listed in the capabilities, if the feature lists its allowed values and \
+the stored one is not among them, or if it is above the maximum the monitor reports. \
+Monitors the profile has values for but that are not connected are listed as skipped.\n\n\
+dispman exits with an error if any value would be rejected.")]
+    Validate {
+        /// Profile name
+        name: String,
+    },
+
+    /// Pause between writes when loading a profile
+    #[command(long_about = "Make `profile load` wait MS milliseconds between consecutive \
+writes to the same monitor. Some monitors apply rapid back-to-back DDC/CI writes in the \
+wrong order. Without MS, the profile's own delay is removed and the \
+default_delay_between_sets_ms setting (see `config set`) applies again. The delay is kept \
+when the profile is saved again.")]
+    SetDelay {
+        /// Profile name
+        name: String,
+        /// Delay in milliseconds (omit to use the default)
+        ms: Option<u64>,
+    },
+
+    /// Copy a saved profile under a new name
+    #[command(long_about = "Save a copy of the profile SRC as DST, e.g. to use it as a \
+template. The copy is independent of SRC and records the time it was made as its \
+creation time. Fails if SRC does not exist, or if DST already exists and --force is not \
+given.")]
+    Copy {
+        /// Profile to copy
+        src: String,
+        /// Name of the copy
+        dst: String,
+        /// Overwrite DST if it already exists
+        #[arg(long, visible_alias = "overwrite")]
+        force: bool,
+    },
+
+    /// Rename a saved profile
+    #[command(long_about = "Rename the profile OLD to NEW. Fails if OLD does not exist, \
+or if NEW already exists and --force is not given.")]
+    Rename {
+        /// Current profile name
+        old: String,
+        /// New profile name
+        new: String,
+        /// Overwrite NEW if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Feature names offered by shell completion. Raw codes are accepted as well,
+/// so these are hints rather than the full set of valid values.
+const FEATURE_NAMES: [&str; 20] = [
+    "brightness",
+    "contrast",
+    "volume",
+    "input",
+    "power",
+    "color-temp",
+    "color-preset",
+    "red-gain",
+    "green-gain",
+    "blue-gain",
+    "hue",
+    "saturation",
+    "sharpness",
+    "h-position",
+    "h-size",
+    "v-position",
+    "v-size",
+    "h-frequency",
+    "v-frequency",
+    "auto-setup",
+];
+
+/// Accepts any feature string but advertises `FEATURE_NAMES` to shell
+/// completion.
+#[derive(Clone)]
+struct FeatureParser;
+
+impl TypedValueParser for FeatureParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<String, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(FEATURE_NAMES.iter().map(PossibleValue::new)))
+    }
+}
+
+/// Parses a display index, suggesting the first few indices to shell
+/// completion since the real ones are only known at runtime.
+#[derive(Clone)]
+struct DisplayIdParser;
+
+impl TypedValueParser for DisplayIdParser {
+    type Value = usize;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<usize, clap::Error> {
+        RangedU64ValueParser::<usize>::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(["0", "1", "2"].into_iter().map(PossibleValue::new)))
+    }
 }
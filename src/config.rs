@@ -1,51 +1,509 @@
+use crate::backend::DdcOptions;
 use crate::error::DisplayError;
+use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday};
 use directories::ProjectDirs;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+/// Environment variable that points dispman at a different config file.
+pub const CONFIG_PATH_ENV: &str = "DISPMAN_CONFIG";
+
+/// Environment variable that overrides `default_display` from the config.
+pub const DEFAULT_DISPLAY_ENV: &str = "DISPMAN_DEFAULT_DISPLAY";
+
+static CONFIG_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Schema version written by this build of dispman. Bump it whenever the
+/// config layout changes and add the upgrade step to `Config::migrate`.
+pub const CURRENT_CONFIG_VERSION: u32 = 1;
+
+/// dispman's config file, `config.toml`.
+// Doc comments on these types are the descriptions in the JSON Schema
+// printed by `config schema`, so they are written for users.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
+    /// Schema version of the file; older versions are upgraded on load.
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// Saved profiles, keyed by name.
     pub profiles: HashMap<String, Profile>,
+    /// Named sequences of profiles, applied in order by `profile load`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub chains: HashMap<String, Vec<String>>,
+    /// Profiles `dispman daemon` loads on power events.
+    #[serde(default, skip_serializing_if = "DaemonConfig::is_empty")]
+    pub daemon: DaemonConfig,
+    /// Saved desktop arrangements, applied by `layout apply`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub layouts: HashMap<String, Layout>,
+    /// User-defined labels for displays, keyed by stable ID, Windows display
+    /// name, or a substring of either (e.g. `"DISPLAY1" = "left-monitor"`).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, String>,
+    /// Defaults for command-line options, changed with `config set`.
+    #[serde(default, skip_serializing_if = "GlobalSettings::is_empty")]
+    pub settings: GlobalSettings,
+    /// Profiles loaded at set times by `schedule run`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ScheduleEntry>,
+}
+
+/// The `[daemon]` section: profiles `dispman daemon` loads on power events.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DaemonConfig {
+    /// Loaded after the system resumes from sleep or hibernation.
+    pub wake_profile: Option<String>,
+    /// Loaded just before the system sleeps.
+    pub suspend_profile: Option<String>,
+}
+
+impl DaemonConfig {
+    pub fn is_empty(&self) -> bool {
+        self.wake_profile.is_none() && self.suspend_profile.is_none()
+    }
+}
+
+/// The `[settings]` section: defaults for command-line options, changed with
+/// `config set`. Unset fields fall back to the built-in defaults.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GlobalSettings {
+    /// Display used when a command is given no `--display`.
+    pub default_display: Option<usize>,
+    /// Retries for a failed DDC/CI command, unless `--ddc-retries` is given.
+    pub ddc_retry_count: Option<u8>,
+    /// Delay between DDC/CI retries, unless `--ddc-delay-ms` is given.
+    pub ddc_delay_ms: Option<u64>,
+    /// Pause between writes during `profile load` for profiles that do not
+    /// set their own `delay_between_sets_ms`.
+    pub default_delay_between_sets_ms: Option<u64>,
+    /// Retry finding monitors until one answers DDC/CI; see
+    /// `backend::enumerate_with_retry`.
+    pub retry_on_empty: Option<bool>,
+}
+
+impl GlobalSettings {
+    /// Every key `config get` and `config set` accept.
+    pub const KEYS: [&'static str; 5] = [
+        "default_display",
+        "ddc_retry_count",
+        "ddc_delay_ms",
+        "default_delay_between_sets_ms",
+        "retry_on_empty",
+    ];
+
+    pub fn is_empty(&self) -> bool {
+        self.default_display.is_none()
+            && self.ddc_retry_count.is_none()
+            && self.ddc_delay_ms.is_none()
+            && self.default_delay_between_sets_ms.is_none()
+            && self.retry_on_empty.is_none()
+    }
+
+    /// The current value of `key`, or its built-in default if unset.
+    pub fn get(&self, key: &str) -> Result<String, DisplayError> {
+        let defaults = DdcOptions::default();
+        Ok(match key {
+            "default_display" => self.default_display.unwrap_or(0).to_string(),
+            "ddc_retry_count" => self
+                .ddc_retry_count
+                .unwrap_or(defaults.retry_count)
+                .to_string(),
+            "ddc_delay_ms" => self
+                .ddc_delay_ms
+                .unwrap_or(defaults.retry_delay_ms)
+                .to_string(),
+            "default_delay_between_sets_ms" => {
+                self.default_delay_between_sets_ms.unwrap_or(0).to_string()
+            }
+            "retry_on_empty" => self
+                .retry_on_empty
+                .unwrap_or(defaults.retry_on_empty)
+                .to_string(),
+            _ => return Err(unknown_setting(key)),
+        })
+    }
+
+    /// Parses `value` for `key` and stores it.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), DisplayError> {
+        match key {
+            "default_display" => self.default_display = Some(parse_setting(key, value)?),
+            "ddc_retry_count" => self.ddc_retry_count = Some(parse_setting(key, value)?),
+            "ddc_delay_ms" => self.ddc_delay_ms = Some(parse_setting(key, value)?),
+            "default_delay_between_sets_ms" => {
+                self.default_delay_between_sets_ms = Some(parse_setting(key, value)?)
+            }
+            "retry_on_empty" => self.retry_on_empty = Some(parse_setting(key, value)?),
+            _ => return Err(unknown_setting(key)),
+        }
+        Ok(())
+    }
+
+    /// The display a command targets when given no `--display`, and where
+    /// that choice comes from: `DISPMAN_DEFAULT_DISPLAY` wins over
+    /// `default_display`. An unparsable variable is ignored.
+    pub fn implicit_display(&self) -> Option<(usize, &'static str)> {
+        self.implicit_display_from(std::env::var(DEFAULT_DISPLAY_ENV).ok().as_deref())
+    }
+
+    /// `implicit_display`, given the value of `DISPMAN_DEFAULT_DISPLAY`.
+    fn implicit_display_from(&self, env: Option<&str>) -> Option<(usize, &'static str)> {
+        if let Some(value) = env.map(str::trim).filter(|v| !v.is_empty()) {
+            match value.parse() {
+                Ok(id) => return Some((id, DEFAULT_DISPLAY_ENV)),
+                Err(_) => eprintln!(
+                    "Warning: ignoring {}='{}': not a display ID",
+                    DEFAULT_DISPLAY_ENV, value
+                ),
+            }
+        }
+        self.default_display
+            .map(|id| (id, "default_display in the config"))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+fn unknown_setting(key: &str) -> DisplayError {
+    DisplayError::config(format!(
+        "Unknown setting '{}'; expected one of: {}",
+        key,
+        GlobalSettings::KEYS.join(", ")
+    ))
+}
+
+fn parse_setting<T>(key: &str, value: &str) -> Result<T, DisplayError>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| DisplayError::config_error(format!("Invalid value for {}: {}", key, value), e))
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_CONFIG_VERSION,
+            profiles: HashMap::new(),
+            chains: HashMap::new(),
+            daemon: DaemonConfig::default(),
+            layouts: HashMap::new(),
+            aliases: HashMap::new(),
+            settings: GlobalSettings::default(),
+            schedules: Vec::new(),
+        }
+    }
+}
+
+/// VCP values to write to each display, saved by `profile save` and
+/// written back by `profile load`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Profile {
-    // Map of Display stable_id (EDID-derived on macOS, device path on Windows)
-    // to a list of (VCP Code, Value).
+    /// Map of Display stable_id (EDID-derived on macOS, device path on
+    /// Windows) to a list of (VCP Code, Value).
     pub settings: HashMap<String, Vec<(u8, u32)>>,
+    /// When the profile was first saved. Missing for profiles saved by
+    /// older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// VCP codes never written when the profile is loaded, even if a value
+    /// for them is stored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_codes: Vec<u8>,
+    /// Pause between writes to the same display when loading, for monitors
+    /// that misorder back-to-back commands. Falls back to
+    /// `default_delay_between_sets_ms` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_between_sets_ms: Option<u64>,
+    /// Machine the profile was saved on. Missing for profiles saved by
+    /// older versions, or when the hostname could not be determined.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+}
+
+/// Summary of a saved profile, as listed by `profile list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileMeta {
+    pub name: String,
+    pub display_count: usize,
+    /// Total number of saved values across all displays.
+    pub feature_count: usize,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// A desktop arrangement: the mode of each display, keyed by its Windows
+/// device name (e.g. `\\.\DISPLAY1`).
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+pub struct Layout {
+    /// Mode of each display, keyed by Windows device name.
+    pub displays: HashMap<String, DisplayMode>,
+}
+
+/// Position, resolution, refresh rate and rotation of one display.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub struct DisplayMode {
+    /// Left edge in the virtual desktop, in pixels.
+    pub x: i32,
+    /// Top edge in the virtual desktop, in pixels.
+    pub y: i32,
+    /// Horizontal resolution in pixels.
+    pub width: u32,
+    /// Vertical resolution in pixels.
+    pub height: u32,
+    /// Refresh rate in Hz.
+    pub refresh_hz: u32,
+    /// Rotation in 90-degree steps (0-3), as reported by Windows.
+    pub orientation: u32,
+}
+
+impl std::fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} @ {} Hz at ({}, {})",
+            self.width, self.height, self.refresh_hz, self.x, self.y
+        )?;
+        if self.orientation != 0 {
+            write!(f, ", rotated {}°", self.orientation * 90)?;
+        }
+        Ok(())
+    }
+}
+
+/// A profile to load at `time` (local) on `days`, or every day if `days`
+/// is empty.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct ScheduleEntry {
+    /// Number shown by `schedule list` and taken by `schedule remove`.
+    pub id: usize,
+    /// Profile or chain to load.
+    pub profile: String,
+    /// Local time of day, e.g. `08:30:00`.
+    pub time: NaiveTime,
+    /// Days to run on (`Mon` to `Sun`); every day if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<Weekday>,
+}
+
+impl ScheduleEntry {
+    /// Whether the entry falls in the minute of `now`.
+    pub fn is_due(&self, now: NaiveDateTime) -> bool {
+        self.time.hour() == now.hour()
+            && self.time.minute() == now.minute()
+            && (self.days.is_empty() || self.days.contains(&now.weekday()))
+    }
+
+    /// The days as a comma-separated list, or `every day`.
+    pub fn days_label(&self) -> String {
+        if self.days.is_empty() {
+            return "every day".to_string();
+        }
+        self.days
+            .iter()
+            .map(|day| day.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Parses a schedule time given as `HH:MM` (24-hour).
+pub fn parse_schedule_time(s: &str) -> Result<NaiveTime, DisplayError> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .map_err(|e| DisplayError::config_error(format!("Invalid time '{}'; expected HH:MM", s), e))
+}
+
+/// Parses a comma-separated list of days (`mon`, `Tuesday`, ...); `weekdays`
+/// and `weekends` stand for Monday-Friday and Saturday-Sunday.
+pub fn parse_schedule_days(s: &str) -> Result<Vec<Weekday>, DisplayError> {
+    let mut days = Vec::new();
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.to_ascii_lowercase().as_str() {
+            "weekdays" => days.extend([
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ]),
+            "weekends" => days.extend([Weekday::Sat, Weekday::Sun]),
+            _ => {
+                // chrono's parse error only prints as `ParseWeekdayError { .. }`.
+                let day = part.parse().map_err(|_| {
+                    DisplayError::config(format!(
+                        "Invalid day '{}'; expected e.g. mon, monday, weekdays",
+                        part
+                    ))
+                })?;
+                days.push(day);
+            }
+        }
+    }
+    days.sort_by_key(|day| day.num_days_from_monday());
+    days.dedup();
+    Ok(days)
 }
 
 impl Config {
+    /// JSON Schema describing the config file, as printed by `config schema`.
+    pub fn json_schema() -> schemars::Schema {
+        schemars::schema_for!(Config)
+    }
+
+    /// Loads the config from `Config::config_path`.
     pub fn load() -> Result<Self, DisplayError> {
-        let path = Self::config_path()?;
+        Self::load_from(&Self::config_path()?)
+    }
+
+    /// Loads the config file at `path`, or an empty config if it does not
+    /// exist. Older schema versions are migrated and written back.
+    pub fn load_from(path: &Path) -> Result<Self, DisplayError> {
         if !path.exists() {
             return Ok(Config::default());
         }
 
         let content = fs::read_to_string(path)?;
         let config: Config = toml::from_str(&content)?;
+        if config.version == CURRENT_CONFIG_VERSION {
+            return Ok(config);
+        }
+
+        let from = config.version;
+        let backup = backup_file(path)?;
+        let config = config.migrate()?;
+        config.save_to(path)?;
+        eprintln!(
+            "Warning: migrated config from version {} to {} (backup saved to {})",
+            from,
+            CURRENT_CONFIG_VERSION,
+            backup.display()
+        );
         Ok(config)
     }
 
+    /// Upgrades a config loaded from an older schema version to
+    /// `CURRENT_CONFIG_VERSION`, one version at a time.
+    pub fn migrate(mut self) -> Result<Config, DisplayError> {
+        if self.version > CURRENT_CONFIG_VERSION {
+            return Err(DisplayError::config(format!(
+                "Config version {} is newer than this dispman supports ({})",
+                self.version, CURRENT_CONFIG_VERSION
+            )));
+        }
+
+        while self.version < CURRENT_CONFIG_VERSION {
+            // Version 0 never existed on disk; files without a version field
+            // deserialize as 1. Future steps go here as `n => { ... }`.
+            self.version += 1;
+        }
+        Ok(self)
+    }
+
+    /// Copies the config file to `config.toml.bak` next to it and returns the
+    /// backup path.
+    pub fn backup() -> Result<PathBuf, DisplayError> {
+        backup_file(&Self::config_path()?)
+    }
+
+    /// Saves the config to `Config::config_path`; see `save_atomic`.
     pub fn save(&self) -> Result<(), DisplayError> {
-        let path = Self::config_path()?;
+        self.save_atomic()
+    }
+
+    /// Saves the config to `Config::config_path` without ever leaving a
+    /// half-written file behind.
+    pub fn save_atomic(&self) -> Result<(), DisplayError> {
+        self.save_to(&Self::config_path()?)
+    }
+
+    /// Writes the config to `config.toml.tmp` next to `path`, flushes it to
+    /// disk and renames it over `path`, so a crash mid-write leaves the old
+    /// file intact. If the temporary file cannot be written, `path` is left
+    /// alone and the error returned; if only the rename fails (e.g. across
+    /// devices), `path` is written directly instead.
+    pub fn save_to(&self, path: &Path) -> Result<(), DisplayError> {
+        self.save_to_with(path, |file, content| file.write_all(content))
+    }
+
+    /// `save_to`, with the temporary file written by `write` so tests can
+    /// interrupt it.
+    fn save_to_with(
+        &self,
+        path: &Path,
+        write: impl FnOnce(&mut File, &[u8]) -> std::io::Result<()>,
+    ) -> Result<(), DisplayError> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
 
         let content = toml::to_string_pretty(self)?;
-        fs::write(path, content)?;
+        let tmp = path.with_extension("toml.tmp");
+        let written = File::create(&tmp).and_then(|mut file| {
+            write(&mut file, content.as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        if let Err(e) = fs::rename(&tmp, path) {
+            let _ = fs::remove_file(&tmp);
+            eprintln!(
+                "Warning: could not replace {} atomically ({}); writing it directly",
+                path.display(),
+                e
+            );
+            fs::write(path, content)?;
+        }
         Ok(())
     }
 
-    fn config_path() -> Result<PathBuf, DisplayError> {
+    /// Makes `config_path` return `path` for the rest of the process, as
+    /// `--config-path` does. Only the first call has an effect.
+    pub fn set_path_override(path: PathBuf) {
+        let _ = CONFIG_PATH_OVERRIDE.set(path);
+    }
+
+    /// Location of the config file: the `set_path_override` path if set,
+    /// else `$DISPMAN_CONFIG`, else the user config directory. The file may
+    /// not exist yet.
+    pub fn config_path() -> Result<PathBuf, DisplayError> {
+        if let Some(path) = CONFIG_PATH_OVERRIDE.get() {
+            return Ok(path.clone());
+        }
+        if let Some(path) = std::env::var_os(CONFIG_PATH_ENV).filter(|p| !p.is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
         let proj_dirs = ProjectDirs::from("com", "dispman", "dispman")
-            .ok_or_else(|| DisplayError::ConfigError("Could not determine config directory".to_string()))?;
+            .ok_or_else(|| DisplayError::config("Could not determine config directory".to_string()))?;
         Ok(proj_dirs.config_dir().join("config.toml"))
     }
 
+    /// All profiles, sorted by name.
+    pub fn list_profiles(&self) -> Vec<ProfileMeta> {
+        let mut list: Vec<ProfileMeta> = self
+            .profiles
+            .iter()
+            .map(|(name, profile)| ProfileMeta {
+                name: name.clone(),
+                display_count: profile.settings.len(),
+                feature_count: profile.settings.values().map(Vec::len).sum(),
+                created_at: profile.created_at,
+            })
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
     pub fn get_profile(&self, name: &str) -> Option<&Profile> {
         self.profiles.get(name)
     }
@@ -53,4 +511,595 @@ impl Config {
     pub fn save_profile(&mut self, name: String, profile: Profile) {
         self.profiles.insert(name, profile);
     }
+
+    /// Looks up `name` as a profile, or failing that as a chain, which is
+    /// expanded into a single merged profile. Returns `None` if neither exists.
+    pub fn resolve_profile(&self, name: &str) -> Result<Option<Profile>, DisplayError> {
+        if let Some(profile) = self.profiles.get(name) {
+            return Ok(Some(profile.clone()));
+        }
+        match self.chains.get(name) {
+            Some(chain) => self.merge_chain(chain).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Merges the named profiles in order, later ones overriding earlier ones.
+    /// Every name must be a saved profile; chains do not nest.
+    pub fn merge_chain(&self, names: &[String]) -> Result<Profile, DisplayError> {
+        let profiles = names
+            .iter()
+            .map(|name| {
+                self.profiles
+                    .get(name)
+                    .ok_or_else(|| DisplayError::config(format!("Profile '{}' not found", name)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(crate::profile::merge_profiles(&profiles))
+    }
+
+    /// Stores a chain of profile names under `name`. Fails if a profile with
+    /// the same name exists, since profiles take precedence on load.
+    pub fn save_chain(&mut self, name: String, profiles: Vec<String>) -> Result<(), DisplayError> {
+        if self.profiles.contains_key(&name) {
+            return Err(DisplayError::config(format!(
+                "A profile named '{}' already exists",
+                name
+            )));
+        }
+        self.merge_chain(&profiles)?;
+        self.chains.insert(name, profiles);
+        Ok(())
+    }
+
+    /// Removes every profile, returning how many were removed.
+    pub fn remove_all_profiles(&mut self) -> usize {
+        let count = self.profiles.len();
+        self.profiles.clear();
+        count
+    }
+
+    /// Removes the named profile, returning whether it existed.
+    pub fn remove_profile(&mut self, name: &str) -> bool {
+        self.profiles.remove(name).is_some()
+    }
+
+    /// Sets or clears the per-profile write delay. Fails if the profile does
+    /// not exist.
+    pub fn set_profile_delay(
+        &mut self,
+        name: &str,
+        delay_ms: Option<u64>,
+    ) -> Result<(), DisplayError> {
+        let profile = self
+            .profiles
+            .get_mut(name)
+            .ok_or_else(|| DisplayError::config(format!("Profile '{}' not found", name)))?;
+        profile.delay_between_sets_ms = delay_ms;
+        Ok(())
+    }
+
+    /// Moves the profile stored under `old` to `new`. Fails if `old` does not
+    /// exist, or if `new` already exists and `force` is not set.
+    pub fn rename_profile(
+        &mut self,
+        old: &str,
+        new: &str,
+        force: bool,
+    ) -> Result<(), DisplayError> {
+        if !self.profiles.contains_key(old) {
+            return Err(DisplayError::config(format!("Profile '{}' not found", old)));
+        }
+        if old != new && !force && self.profiles.contains_key(new) {
+            return Err(DisplayError::config(format!(
+                "Profile '{}' already exists (use --force to overwrite)",
+                new
+            )));
+        }
+        if let Some(profile) = self.profiles.remove(old) {
+            self.profiles.insert(new.to_string(), profile);
+        }
+        Ok(())
+    }
+
+    /// Stores a copy of the profile `src` under `dst`, dated now. Fails if
+    /// `src` does not exist, or if `dst` already exists and `force` is not
+    /// set.
+    pub fn copy_profile(&mut self, src: &str, dst: &str, force: bool) -> Result<(), DisplayError> {
+        let Some(profile) = self.profiles.get(src) else {
+            return Err(DisplayError::config(format!("Profile '{}' not found", src)));
+        };
+        if !force && self.profiles.contains_key(dst) {
+            return Err(DisplayError::config(format!(
+                "Profile '{}' already exists (use --force to overwrite)",
+                dst
+            )));
+        }
+        let copy = Profile {
+            created_at: Some(Utc::now()),
+            ..profile.clone()
+        };
+        self.profiles.insert(dst.to_string(), copy);
+        Ok(())
+    }
+
+    /// Profiles saved with `profile save --timestamp` for `base_name`, i.e.
+    /// named `BASE-YYYYMMDDTHHMMSS`, newest first.
+    pub fn find_timestamped_profiles(&self, base_name: &str) -> Vec<(String, DateTime<Utc>)> {
+        let mut found: Vec<(String, DateTime<Utc>)> = self
+            .profiles
+            .keys()
+            .filter_map(|name| {
+                let suffix = name.strip_prefix(base_name)?.strip_prefix('-')?;
+                let at = NaiveDateTime::parse_from_str(suffix, PROFILE_TIMESTAMP_FORMAT).ok()?;
+                Some((name.clone(), at.and_utc()))
+            })
+            .collect();
+        found.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        found
+    }
+
+    /// Removes all but the `keep` newest timestamped versions of
+    /// `base_name`, returning the names removed.
+    pub fn prune_timestamped_profiles(&mut self, base_name: &str, keep: usize) -> Vec<String> {
+        let stale: Vec<String> = self
+            .find_timestamped_profiles(base_name)
+            .into_iter()
+            .skip(keep)
+            .map(|(name, _)| name)
+            .collect();
+        for name in &stale {
+            self.profiles.remove(name);
+        }
+        stale
+    }
+
+    /// Adds a schedule entry for the profile or chain `profile` and returns
+    /// its ID, one more than the highest ID in use.
+    pub fn add_schedule(
+        &mut self,
+        profile: String,
+        time: NaiveTime,
+        days: Vec<Weekday>,
+    ) -> Result<usize, DisplayError> {
+        if !self.profiles.contains_key(&profile) && !self.chains.contains_key(&profile) {
+            return Err(DisplayError::config(format!(
+                "Profile '{}' not found",
+                profile
+            )));
+        }
+        let id = self.schedules.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        self.schedules.push(ScheduleEntry {
+            id,
+            profile,
+            time,
+            days,
+        });
+        Ok(id)
+    }
+
+    /// Removes the schedule entry `id`, returning whether it existed.
+    pub fn remove_schedule(&mut self, id: usize) -> bool {
+        let before = self.schedules.len();
+        self.schedules.retain(|s| s.id != id);
+        self.schedules.len() != before
+    }
+
+    /// The schedule entries that fall in the minute of `now`.
+    pub fn due_schedules(&self, now: NaiveDateTime) -> Vec<&ScheduleEntry> {
+        self.schedules.iter().filter(|s| s.is_due(now)).collect()
+    }
+
+    pub fn get_layout(&self, name: &str) -> Option<&Layout> {
+        self.layouts.get(name)
+    }
+
+    pub fn save_layout(&mut self, name: String, layout: Layout) {
+        self.layouts.insert(name, layout);
+    }
+
+    /// Applies the `[aliases]` map to a display identifier. An exact key
+    /// wins; otherwise the longest key contained in `raw` is used. Returns
+    /// `raw` unchanged if no alias matches.
+    pub fn resolve_display_name<'a>(&'a self, raw: &'a str) -> &'a str {
+        if let Some(alias) = self.aliases.get(raw) {
+            return alias;
+        }
+        self.aliases
+            .iter()
+            .filter(|(key, _)| !key.is_empty() && raw.contains(key.as_str()))
+            .max_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+            .map_or(raw, |(_, alias)| alias.as_str())
+    }
+
+    /// The settings `profile` stores for a display, looked up by the
+    /// display's alias first and then by `raw` itself.
+    pub fn profile_settings<'p>(
+        &self,
+        profile: &'p Profile,
+        raw: &str,
+    ) -> Option<&'p Vec<(u8, u32)>> {
+        profile
+            .settings
+            .get(self.resolve_display_name(raw))
+            .or_else(|| profile.settings.get(raw))
+    }
+}
+
+/// Name of this machine: `COMPUTERNAME` on Windows, otherwise `HOSTNAME` or
+/// the output of the `hostname` command.
+pub fn hostname() -> Result<String, DisplayError> {
+    let name = match ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+    {
+        Some(name) => name,
+        None => {
+            let output = std::process::Command::new("hostname").output()?;
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DisplayError::config(
+            "Could not determine the hostname".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// The per-machine variant of profile `name`, e.g. `work-DESKTOP-ABC123`.
+pub fn host_profile_name(name: &str, host: &str) -> String {
+    format!("{}-{}", name, host)
+}
+
+/// Suffix format of `profile save --timestamp`: ISO 8601 basic, in UTC.
+pub const PROFILE_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Name of a timestamped version of `name`, e.g. `work-20240115T143022`.
+pub fn timestamped_profile_name(name: &str, at: DateTime<Utc>) -> String {
+    format!("{}-{}", name, at.format(PROFILE_TIMESTAMP_FORMAT))
+}
+
+/// Copies the config file at `path` to `config.toml.bak` next to it and
+/// returns the backup path.
+fn backup_file(path: &Path) -> Result<PathBuf, DisplayError> {
+    if !path.exists() {
+        return Err(DisplayError::config(format!(
+            "No config file at {} to back up",
+            path.display()
+        )));
+    }
+
+    let backup = path.with_extension("toml.bak");
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated_config() -> Config {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "work".to_string(),
+            Profile {
+                settings: HashMap::from([("dell".to_string(), vec![(0x10, 80), (0x60, 0x0F)])]),
+                created_at: DateTime::from_timestamp(1_705_329_022, 0),
+                excluded_codes: vec![0xD6],
+                delay_between_sets_ms: Some(100),
+                hostname: Some("DESKTOP-ABC123".to_string()),
+            },
+        );
+        config
+            .chains
+            .insert("morning".to_string(), vec!["work".to_string()]);
+        config.daemon.wake_profile = Some("work".to_string());
+        config.layouts.insert(
+            "desk".to_string(),
+            Layout {
+                displays: HashMap::from([(
+                    "\\\\.\\DISPLAY1".to_string(),
+                    DisplayMode {
+                        x: -1920,
+                        y: 0,
+                        width: 1920,
+                        height: 1080,
+                        refresh_hz: 60,
+                        orientation: 1,
+                    },
+                )]),
+            },
+        );
+        config
+            .aliases
+            .insert("DISPLAY1".to_string(), "left".to_string());
+        config.settings.default_display = Some(1);
+        config.settings.retry_on_empty = Some(true);
+        config.schedules.push(ScheduleEntry {
+            id: 1,
+            profile: "work".to_string(),
+            time: NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+            days: vec![Weekday::Mon, Weekday::Fri],
+        });
+        config
+    }
+
+    /// Writes `config` as TOML, checks the file against the schema, and
+    /// reads it back.
+    fn round_trip(config: &Config) -> Config {
+        let schema = serde_json::to_value(Config::json_schema()).unwrap();
+        let toml = toml::to_string_pretty(config).unwrap();
+        let instance: serde_json::Value = toml::from_str(&toml).unwrap();
+        if let Err(e) = jsonschema::validate(&schema, &instance) {
+            panic!("{} does not match the schema: {}", instance, e);
+        }
+        toml::from_str(&toml).unwrap()
+    }
+
+    #[test]
+    fn default_config_matches_schema() {
+        let config = round_trip(&Config::default());
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert!(config.profiles.is_empty());
+    }
+
+    #[test]
+    fn populated_config_matches_schema() {
+        let config = populated_config();
+        let read = round_trip(&config);
+        assert_eq!(
+            toml::to_string(&read).unwrap(),
+            toml::to_string(&config).unwrap()
+        );
+    }
+
+    #[test]
+    fn crash_while_saving_keeps_previous_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = populated_config();
+        config.save_to(&path).unwrap();
+        let before = fs::read_to_string(&path).unwrap();
+
+        config
+            .aliases
+            .insert("DISPLAY2".to_string(), "Bildschirm rechts ü".to_string());
+        // The writer dies partway through a multi-byte character.
+        let crashed = std::panic::catch_unwind(|| {
+            config.save_to_with(&path, |file, content| {
+                let cut = content.iter().position(|&b| b == 0xC3).unwrap() + 1;
+                file.write_all(&content[..cut]).unwrap();
+                panic!("killed mid-write");
+            })
+        });
+        assert!(crashed.is_err());
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+        let loaded = Config::load_from(&path).unwrap();
+        assert_eq!(loaded.aliases.get("DISPLAY2"), None);
+        assert!(loaded.profiles.contains_key("work"));
+
+        // The leftover temporary file does not get in the way of the next save.
+        config.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path).unwrap();
+        assert_eq!(loaded.aliases["DISPLAY2"], "Bildschirm rechts ü");
+        assert!(!path.with_extension("toml.tmp").exists());
+    }
+
+    #[test]
+    fn failed_temporary_write_leaves_config_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = populated_config();
+        config.save_to(&path).unwrap();
+        let before = fs::read_to_string(&path).unwrap();
+
+        config.profiles.clear();
+        let result = config.save_to_with(&path, |_, _| Err(std::io::Error::other("disk full")));
+        assert!(matches!(result, Err(DisplayError::IoError(_))));
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+        assert!(!path.with_extension("toml.tmp").exists());
+    }
+
+    fn settings_with_default(id: Option<usize>) -> GlobalSettings {
+        GlobalSettings {
+            default_display: id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn implicit_display_prefers_env_var() {
+        let settings = settings_with_default(Some(1));
+        assert_eq!(
+            settings.implicit_display_from(Some(" 2 ")),
+            Some((2, DEFAULT_DISPLAY_ENV))
+        );
+    }
+
+    #[test]
+    fn implicit_display_falls_back_to_config() {
+        let settings = settings_with_default(Some(1));
+        let expected = Some((1, "default_display in the config"));
+        assert_eq!(settings.implicit_display_from(None), expected);
+        assert_eq!(settings.implicit_display_from(Some("")), expected);
+        assert_eq!(settings.implicit_display_from(Some("left")), expected);
+    }
+
+    #[test]
+    fn implicit_display_unset() {
+        let settings = settings_with_default(None);
+        assert_eq!(settings.implicit_display_from(None), None);
+        assert_eq!(settings.implicit_display_from(Some("-1")), None);
+        assert_eq!(
+            settings.implicit_display_from(Some("0")),
+            Some((0, DEFAULT_DISPLAY_ENV))
+        );
+    }
+
+    fn empty_profile() -> Profile {
+        Profile {
+            settings: HashMap::new(),
+            created_at: None,
+            excluded_codes: Vec::new(),
+            delay_between_sets_ms: None,
+            hostname: None,
+        }
+    }
+
+    fn at(timestamp: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn timestamped_name_uses_basic_iso_format() {
+        assert_eq!(
+            timestamped_profile_name("work", at(1_705_329_022)),
+            "work-20240115T143022"
+        );
+    }
+
+    #[test]
+    fn finds_timestamped_versions_newest_first() {
+        let mut config = Config::default();
+        for name in [
+            "work",
+            "work-20240115T143022",
+            "work-20240301T080000",
+            "work-home",
+            "work-home-20240401T080000",
+            "workshop-20240401T080000",
+            "work-2024",
+        ] {
+            config.save_profile(name.to_string(), empty_profile());
+        }
+        let found = config.find_timestamped_profiles("work");
+        assert_eq!(
+            found,
+            [
+                ("work-20240301T080000".to_string(), at(1_709_280_000)),
+                ("work-20240115T143022".to_string(), at(1_705_329_022)),
+            ]
+        );
+    }
+
+    #[test]
+    fn prune_keeps_newest_versions() {
+        let mut config = Config::default();
+        config.save_profile("work".to_string(), empty_profile());
+        for day in 1..=4 {
+            let name = timestamped_profile_name("work", at(1_704_067_200 + day * 86_400));
+            config.save_profile(name, empty_profile());
+        }
+        let removed = config.prune_timestamped_profiles("work", 2);
+        assert_eq!(removed, ["work-20240103T000000", "work-20240102T000000"]);
+        let mut names: Vec<_> = config.profiles.keys().cloned().collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            ["work", "work-20240104T000000", "work-20240105T000000"]
+        );
+    }
+
+    #[test]
+    fn parses_schedule_times() {
+        assert_eq!(
+            parse_schedule_time(" 08:30 ").unwrap(),
+            NaiveTime::from_hms_opt(8, 30, 0).unwrap()
+        );
+        assert_eq!(
+            parse_schedule_time("23:59").unwrap(),
+            NaiveTime::from_hms_opt(23, 59, 0).unwrap()
+        );
+        for bad in ["24:00", "8.30", "08:30:00", "noon", ""] {
+            assert!(parse_schedule_time(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn parses_schedule_days() {
+        assert_eq!(
+            parse_schedule_days("fri, Mon,monday").unwrap(),
+            [Weekday::Mon, Weekday::Fri]
+        );
+        assert_eq!(
+            parse_schedule_days("weekends,weekdays").unwrap(),
+            [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun
+            ]
+        );
+        assert!(parse_schedule_days("").unwrap().is_empty());
+        let err = parse_schedule_days("mon,someday").unwrap_err();
+        assert!(err.to_string().contains("Invalid day 'someday'"));
+    }
+
+    fn schedule(time: (u32, u32), days: Vec<Weekday>) -> ScheduleEntry {
+        ScheduleEntry {
+            id: 1,
+            profile: "work".to_string(),
+            time: NaiveTime::from_hms_opt(time.0, time.1, 0).unwrap(),
+            days,
+        }
+    }
+
+    /// 2024-01-15 was a Monday.
+    fn monday(hour: u32, minute: u32, second: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 15)
+            .unwrap()
+            .and_hms_opt(hour, minute, second)
+            .unwrap()
+    }
+
+    #[test]
+    fn schedule_is_due_for_the_whole_minute() {
+        let entry = schedule((8, 30), Vec::new());
+        assert!(entry.is_due(monday(8, 30, 0)));
+        assert!(entry.is_due(monday(8, 30, 59)));
+        assert!(!entry.is_due(monday(8, 31, 0)));
+        assert!(!entry.is_due(monday(20, 30, 0)));
+    }
+
+    #[test]
+    fn schedule_is_due_only_on_its_days() {
+        let entry = schedule((8, 30), vec![Weekday::Mon, Weekday::Wed]);
+        assert!(entry.is_due(monday(8, 30, 0)));
+        let tuesday = monday(8, 30, 0) + chrono::Days::new(1);
+        assert!(!entry.is_due(tuesday));
+        assert_eq!(entry.days_label(), "Mon,Wed");
+        assert_eq!(schedule((8, 30), Vec::new()).days_label(), "every day");
+    }
+
+    #[test]
+    fn schedules_get_increasing_ids() {
+        let mut config = Config::default();
+        config.save_profile("work".to_string(), empty_profile());
+        let time = NaiveTime::from_hms_opt(8, 30, 0).unwrap();
+        let add = |config: &mut Config, profile: &str| {
+            config.add_schedule(profile.to_string(), time, Vec::new())
+        };
+        assert_eq!(add(&mut config, "work").unwrap(), 1);
+        assert_eq!(add(&mut config, "work").unwrap(), 2);
+        assert!(config.remove_schedule(1));
+        assert!(!config.remove_schedule(1));
+        assert_eq!(add(&mut config, "work").unwrap(), 3);
+        assert!(add(&mut config, "gaming").is_err());
+        assert_eq!(config.due_schedules(monday(8, 30, 10)).len(), 2);
+    }
+
+    #[test]
+    fn schema_rejects_wrong_types() {
+        let schema = serde_json::to_value(Config::json_schema()).unwrap();
+        let instance = serde_json::json!({
+            "profiles": { "work": { "settings": { "dell": [["bright", 80]] } } }
+        });
+        assert!(jsonschema::validate(&schema, &instance).is_err());
+    }
 }
@@ -0,0 +1,36 @@
+//! Helpers for the display names and descriptions the operating system
+//! reports.
+
+/// Converts a fixed-size UTF-16 buffer from a Windows struct to a `String`,
+/// stopping at the first NUL as C does. Some drivers leave stale characters
+/// after it, which trimming the ends would keep.
+pub fn normalize_wide_string(wide: &[u16]) -> String {
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+    String::from_utf16_lossy(&wide[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().collect()
+    }
+
+    #[test]
+    fn stops_at_embedded_nul() {
+        let mut buffer = wide("DELL U2722D\0Generic PnP");
+        buffer.extend([0; 8]);
+        assert_eq!(normalize_wide_string(&buffer), "DELL U2722D");
+    }
+
+    #[test]
+    fn keeps_buffer_without_nul() {
+        assert_eq!(normalize_wide_string(&wide("DISPLAY1")), "DISPLAY1");
+    }
+
+    #[test]
+    fn leading_nul_gives_empty_string() {
+        assert_eq!(normalize_wide_string(&wide("\0stale")), "");
+    }
+}
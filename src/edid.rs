@@ -0,0 +1,104 @@
+//! Parsing of the 128-byte EDID base block a monitor reports to the host.
+//!
+//! EDID carries identification data that DDC/CI does not expose, such as the
+//! manufacturer, product code, serial number, and native resolution.
+
+use crate::error::DisplayError;
+use serde::Serialize;
+use std::fmt;
+
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const BLOCK_LEN: usize = 128;
+const DESCRIPTOR_OFFSETS: [usize; 4] = [54, 72, 90, 108];
+const SERIAL_DESCRIPTOR_TAG: u8 = 0xFF;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EdidInfo {
+    /// Three-letter PNP manufacturer ID, e.g. `DEL`.
+    pub manufacturer_id: String,
+    pub product_code: u16,
+    /// The serial number descriptor if present, otherwise the numeric serial
+    /// from the header. `None` if neither is set.
+    pub serial_number: Option<String>,
+    /// Year of manufacture (or model year).
+    pub year: u16,
+    /// Resolution of the preferred (first) detailed timing.
+    pub native_width: u16,
+    pub native_height: u16,
+}
+
+impl EdidInfo {
+    /// Parses the base block of `edid`. Extension blocks are ignored.
+    pub fn parse(edid: &[u8]) -> Result<Self, DisplayError> {
+        if edid.len() < BLOCK_LEN {
+            return Err(DisplayError::InvalidEdid(format!(
+                "expected at least {} bytes, got {}",
+                BLOCK_LEN,
+                edid.len()
+            )));
+        }
+        if edid[..8] != HEADER {
+            return Err(DisplayError::InvalidEdid("missing EDID header".to_string()));
+        }
+
+        let id = u16::from_be_bytes([edid[8], edid[9]]);
+        let manufacturer_id = [10, 5, 0]
+            .iter()
+            .map(|shift| char::from(b'A' - 1 + ((id >> shift) & 0x1F) as u8))
+            .collect();
+        let product_code = u16::from_le_bytes([edid[10], edid[11]]);
+        let numeric_serial = u32::from_le_bytes([edid[12], edid[13], edid[14], edid[15]]);
+        let year = 1990 + u16::from(edid[17]);
+
+        let mut serial_number = (numeric_serial != 0).then(|| numeric_serial.to_string());
+        for offset in DESCRIPTOR_OFFSETS {
+            let d = &edid[offset..offset + 18];
+            if d[..3] == [0, 0, 0] && d[3] == SERIAL_DESCRIPTOR_TAG {
+                let text = String::from_utf8_lossy(&d[5..]);
+                let text = text.split('\n').next().unwrap_or_default().trim();
+                if !text.is_empty() {
+                    serial_number = Some(text.to_string());
+                }
+            }
+        }
+
+        // The first descriptor is the preferred timing when its pixel clock
+        // is non-zero.
+        let t = &edid[54..72];
+        let (native_width, native_height) = if t[0] != 0 || t[1] != 0 {
+            (
+                u16::from(t[2]) | (u16::from(t[4] & 0xF0) << 4),
+                u16::from(t[5]) | (u16::from(t[7] & 0xF0) << 4),
+            )
+        } else {
+            (0, 0)
+        };
+
+        Ok(EdidInfo {
+            manufacturer_id,
+            product_code,
+            serial_number,
+            year,
+            native_width,
+            native_height,
+        })
+    }
+}
+
+impl fmt::Display for EdidInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Manufacturer: {}", self.manufacturer_id)?;
+        writeln!(f, "Product Code: 0x{:04X}", self.product_code)?;
+        writeln!(
+            f,
+            "Serial Number: {}",
+            self.serial_number.as_deref().unwrap_or("(none)")
+        )?;
+        writeln!(f, "Year: {}", self.year)?;
+        write!(
+            f,
+            "Native Resolution: {}×{}",
+            self.native_width, self.native_height
+        )
+    }
+}
@@ -1,20 +1,43 @@
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum DisplayError {
     #[cfg(target_os = "windows")]
-    #[error("Windows API error: {0}")]
+    #[error("Windows API error: {} (0x{:08X})", .0.message(), .0.code().0)]
     WindowsError(#[from] windows::core::Error),
 
     #[error("DDC/CI communication failed: {0}")]
     DdcCommunicationFailed(String),
 
+    #[error("DDC/CI command still failing after {retries} retries: {source}")]
+    RetryExhausted {
+        retries: u8,
+        #[source]
+        source: Box<DisplayError>,
+    },
+
+    #[error("Verification failed: wrote {expected} but monitor reports {actual}")]
+    VerificationFailed { expected: u32, actual: u32 },
+
+    #[error("Invalid value {value} for VCP 0x{code:02X}: monitor maximum is {max}")]
+    InvalidValue { code: u8, value: u32, max: u32 },
+
+    #[error("Capabilities string is incomplete: {0}")]
+    CapabilitiesIncomplete(String),
+
     #[error("Feature not supported: {0}")]
     FeatureNotSupported(String),
 
+    #[error("Invalid EDID: {0}")]
+    InvalidEdid(String),
+
     #[error("Monitor not found: {0}")]
     MonitorNotFound(String),
 
+    #[error("Failed to change display mode of {device} (code {code})")]
+    DisplayModeChangeFailed { device: String, code: i32 },
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -27,9 +50,104 @@ pub enum DisplayError {
     #[error("TOML serialization error: {0}")]
     TomlSerError(#[from] toml::ser::Error),
 
-    #[error("Config error: {0}")]
-    ConfigError(String),
+    #[error("Config error: {message}")]
+    ConfigError {
+        message: String,
+        /// The error that caused this one, if any. Reported by `anyhow` as
+        /// "Caused by".
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
 
     #[error("Platform not supported")]
     UnsupportedPlatform,
 }
+
+impl DisplayError {
+    /// A `ConfigError` with no underlying cause.
+    pub fn config(message: impl Into<String>) -> Self {
+        DisplayError::ConfigError {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// A `ConfigError` that keeps `source` as its cause.
+    pub fn config_error(
+        message: impl fmt::Display,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        DisplayError::ConfigError {
+            message: message.to_string(),
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Wraps the calling thread's last Win32 error, for APIs that signal
+    /// failure with a zero return value instead of a `windows::core::Result`.
+    #[cfg(target_os = "windows")]
+    pub fn last_windows_error() -> Self {
+        DisplayError::WindowsError(windows::core::Error::from_thread())
+    }
+
+    /// `DdcCommunicationFailed` for a failed Monitor Configuration API call,
+    /// with the thread's last Win32 error appended, e.g.
+    /// `SetVCPFeature failed for code 0x10 (Win32 error 0x80070005: Access is denied.)`.
+    /// Call it right after the failing call, before anything else can
+    /// overwrite the error.
+    #[cfg(target_os = "windows")]
+    pub fn last_ddc_error(context: impl Into<String>) -> Self {
+        let error = windows::core::Error::from_thread();
+        DisplayError::DdcCommunicationFailed(format!(
+            "{} (Win32 error 0x{:08X}: {})",
+            context.into(),
+            error.code().0,
+            error.message()
+        ))
+    }
+
+    /// Process exit code for this error, so scripts can tell failures
+    /// apart. `RetryExhausted` reports the code of the error it wraps.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DisplayError::MonitorNotFound(_) => 2,
+            DisplayError::DdcCommunicationFailed(_) | DisplayError::CapabilitiesIncomplete(_) => 3,
+            DisplayError::FeatureNotSupported(_) => 4,
+            DisplayError::ConfigError { .. } => 5,
+            DisplayError::IoError(_) => 6,
+            DisplayError::SerializationError(_) => 7,
+            DisplayError::RetryExhausted { source, .. } => source.exit_code(),
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_follow_error_kind() {
+        let cases = [
+            (DisplayError::MonitorNotFound("x".to_string()), 2),
+            (DisplayError::DdcCommunicationFailed("x".to_string()), 3),
+            (DisplayError::CapabilitiesIncomplete("x".to_string()), 3),
+            (DisplayError::FeatureNotSupported("x".to_string()), 4),
+            (DisplayError::config("x"), 5),
+            (std::io::Error::other("x").into(), 6),
+            (DisplayError::UnsupportedPlatform, 1),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.exit_code(), code, "{}", error);
+        }
+    }
+
+    #[test]
+    fn retry_exhausted_reports_wrapped_code() {
+        let error = DisplayError::RetryExhausted {
+            retries: 3,
+            source: Box::new(DisplayError::DdcCommunicationFailed("x".to_string())),
+        };
+        assert_eq!(error.exit_code(), 3);
+    }
+}
@@ -0,0 +1,165 @@
+//! Reading and restoring the desktop arrangement (position, resolution,
+//! refresh rate and rotation of each display). Unlike the rest of dispman
+//! this goes through the OS display settings rather than DDC/CI.
+
+use crate::config::Layout;
+use crate::error::DisplayError;
+
+/// Captures the current mode of every display attached to the desktop.
+pub fn current_layout() -> Result<Layout, DisplayError> {
+    #[cfg(target_os = "windows")]
+    {
+        windows::current_layout()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(DisplayError::UnsupportedPlatform)
+    }
+}
+
+/// Applies `layout`. Every display it names must be connected; nothing is
+/// changed otherwise.
+pub fn apply_layout(layout: &Layout) -> Result<(), DisplayError> {
+    #[cfg(target_os = "windows")]
+    {
+        windows::apply_layout(layout)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = layout;
+        Err(DisplayError::UnsupportedPlatform)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use crate::config::{DisplayMode, Layout};
+    use crate::error::DisplayError;
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use windows::Win32::Foundation::POINTL;
+    use windows::Win32::Graphics::Gdi::{
+        CDS_NORESET, CDS_TYPE, CDS_UPDATEREGISTRY, ChangeDisplaySettingsExW,
+        DEVMODE_DISPLAY_ORIENTATION, DEVMODEW, DISP_CHANGE_SUCCESSFUL,
+        DISPLAY_DEVICE_ATTACHED_TO_DESKTOP, DISPLAY_DEVICEW, DM_DISPLAYFREQUENCY,
+        DM_DISPLAYORIENTATION, DM_PELSHEIGHT, DM_PELSWIDTH, DM_POSITION, ENUM_CURRENT_SETTINGS,
+        EnumDisplayDevicesW, EnumDisplaySettingsW,
+    };
+    use windows::core::{HSTRING, PCWSTR};
+
+    pub fn current_layout() -> Result<Layout, DisplayError> {
+        let mut displays = HashMap::new();
+        let mut index = 0;
+        loop {
+            let mut device = DISPLAY_DEVICEW {
+                cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+                ..Default::default()
+            };
+            if !unsafe { EnumDisplayDevicesW(PCWSTR::null(), index, &mut device, 0) }.as_bool() {
+                break;
+            }
+            index += 1;
+            if !device
+                .StateFlags
+                .contains(DISPLAY_DEVICE_ATTACHED_TO_DESKTOP)
+            {
+                continue;
+            }
+
+            let name = OsString::from_wide(&device.DeviceName)
+                .to_string_lossy()
+                .trim_matches(char::from(0))
+                .to_string();
+            let mode = current_mode(&name)?;
+            // SAFETY: for display devices the position/orientation variant
+            // of the union is the one Windows fills in.
+            let (position, orientation) = unsafe {
+                let display = mode.Anonymous1.Anonymous2;
+                (display.dmPosition, display.dmDisplayOrientation)
+            };
+            displays.insert(
+                name,
+                DisplayMode {
+                    x: position.x,
+                    y: position.y,
+                    width: mode.dmPelsWidth,
+                    height: mode.dmPelsHeight,
+                    refresh_hz: mode.dmDisplayFrequency,
+                    orientation: orientation.0,
+                },
+            );
+        }
+        Ok(Layout { displays })
+    }
+
+    pub fn apply_layout(layout: &Layout) -> Result<(), DisplayError> {
+        // Read every current mode first so a missing display aborts before
+        // anything has been staged.
+        let mut staged = Vec::new();
+        for (name, target) in &layout.displays {
+            let mut mode = current_mode(name)?;
+            mode.Anonymous1.Anonymous2.dmPosition = POINTL {
+                x: target.x,
+                y: target.y,
+            };
+            mode.Anonymous1.Anonymous2.dmDisplayOrientation =
+                DEVMODE_DISPLAY_ORIENTATION(target.orientation);
+            mode.dmPelsWidth = target.width;
+            mode.dmPelsHeight = target.height;
+            mode.dmDisplayFrequency = target.refresh_hz;
+            mode.dmFields = DM_POSITION
+                | DM_DISPLAYORIENTATION
+                | DM_PELSWIDTH
+                | DM_PELSHEIGHT
+                | DM_DISPLAYFREQUENCY;
+            staged.push((name, mode));
+        }
+
+        // CDS_NORESET stores each change without applying it; the final
+        // call with no device applies them all at once.
+        for (name, mode) in &staged {
+            let result = unsafe {
+                ChangeDisplaySettingsExW(
+                    &HSTRING::from(name.as_str()),
+                    Some(mode),
+                    None,
+                    CDS_UPDATEREGISTRY | CDS_NORESET,
+                    None,
+                )
+            };
+            if result != DISP_CHANGE_SUCCESSFUL {
+                return Err(DisplayError::DisplayModeChangeFailed {
+                    device: name.to_string(),
+                    code: result.0,
+                });
+            }
+        }
+        let result =
+            unsafe { ChangeDisplaySettingsExW(PCWSTR::null(), None, None, CDS_TYPE(0), None) };
+        if result != DISP_CHANGE_SUCCESSFUL {
+            return Err(DisplayError::DisplayModeChangeFailed {
+                device: "desktop".to_string(),
+                code: result.0,
+            });
+        }
+        Ok(())
+    }
+
+    fn current_mode(device: &str) -> Result<DEVMODEW, DisplayError> {
+        let mut mode = DEVMODEW {
+            dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+            ..Default::default()
+        };
+        let found = unsafe {
+            EnumDisplaySettingsW(&HSTRING::from(device), ENUM_CURRENT_SETTINGS, &mut mode)
+        };
+        if !found.as_bool() {
+            return Err(DisplayError::MonitorNotFound(format!(
+                "{} is not attached to the desktop",
+                device
+            )));
+        }
+        Ok(mode)
+    }
+}
@@ -1,5 +1,12 @@
 pub mod backend;
 pub mod capabilities;
 pub mod config;
+pub mod display;
+pub mod edid;
 pub mod error;
+pub mod layout;
+pub mod output;
+pub mod power;
+pub mod profile;
+pub mod schema;
 pub mod vcp;
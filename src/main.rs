@@ -1,170 +1,3158 @@
 mod cli;
+#[macro_use]
+mod reporter;
 
-use clap::Parser;
-use cli::{Cli, Commands, ProfileCommands};
+use chrono::{Local, Utc};
+use clap::{CommandFactory, Parser};
+use cli::{
+    BatchCommand, BatchLine, CapabilitiesArgs, ChainCommands, Cli, ColorTempAction, Commands,
+    ConfigCommands, DetectArgs, GeometryAction, GetArgs, InputAction, InspectArgs, LayoutCommands,
+    OnFailure, OutputFormat, PowerAction, PresetAction, ProfileCommands, ScheduleCommands, SetArgs,
+    VcpCommands, VcpSortKey,
+};
 use dispman::{
-    backend,
-    config::{Config, Profile},
+    backend::{self, DdcOptions, VcpSnapshot},
+    capabilities::{self, Capabilities},
+    config::{
+        Config, GlobalSettings, Profile, host_profile_name, hostname, parse_schedule_days,
+        parse_schedule_time, timestamped_profile_name,
+    },
+    edid::EdidInfo,
     error::DisplayError,
+    layout,
+    output::{CsvFormatter, JsonFormatter, OutputFormatter, TextFormatter},
+    power::{self, PowerEvent},
+    profile::{self, OnFailurePolicy, ProfileDetail},
+    schema::{DetectOptions, DisplayEntry, FeatureReading, MonitorInfo, VcpReading},
+    vcp::{ColorPreset, InputSource, PicturePreset, PowerMode, VcpCategory, VcpFeature, VcpValue},
 };
+use reporter::{ProgressBar, Reporter};
 use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {:?}", e);
+        let code = e
+            .downcast_ref::<DisplayError>()
+            .map_or(1, DisplayError::exit_code);
+        std::process::exit(code);
+    }
+}
 
-fn main() -> anyhow::Result<()> {
+fn run() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    if let Some(path) = &cli.config_path {
+        Config::set_path_override(path.clone());
+    }
+    let settings = match Config::load() {
+        Ok(config) => config.settings,
+        Err(e) => {
+            eprintln!("Warning: ignoring [settings] in the config file: {}", e);
+            GlobalSettings::default()
+        }
+    };
+    let defaults = DdcOptions::default();
+    let ddc_options = DdcOptions {
+        retry_count: cli
+            .ddc_retries
+            .or(settings.ddc_retry_count)
+            .unwrap_or(defaults.retry_count),
+        retry_delay_ms: cli
+            .ddc_delay_ms
+            .or(settings.ddc_delay_ms)
+            .unwrap_or(defaults.retry_delay_ms),
+        verbose: cli.verbose,
+        cache_capabilities: !cli.no_cache,
+        retry_on_empty: settings.retry_on_empty.unwrap_or(defaults.retry_on_empty),
+    };
+    let ctx = Context {
+        ddc_options,
+        reporter: Reporter::new(cli.quiet, cli.verbose),
+        formatter: formatter(cli.output_format, cli.all_displays),
+        targeting: Targeting {
+            display_name: cli.display_name.clone(),
+            first_match: cli.first_match,
+            default_display: settings.implicit_display(),
+        },
+        output_format: cli.output_format,
+        all_displays: cli.all_displays,
+        dry_run: cli.dry_run,
+        verbose: cli.verbose,
+    };
+
+    let command = match cli.command {
+        Commands::Brightness {
+            value,
+            display,
+            percent,
+        } => expand_shortcut("brightness", value, display, percent)?,
+        Commands::Contrast {
+            value,
+            display,
+            percent,
+        } => expand_shortcut("contrast", value, display, percent)?,
+        Commands::Volume {
+            value,
+            display,
+            percent,
+        } => expand_shortcut("volume", value, display, percent)?,
+        command => command,
+    };
+
+    match command {
+        Commands::Detect(args) => cmd_detect(&ctx, args)?,
+        Commands::Info { json } => cmd_info(&ctx, json)?,
+        Commands::Capabilities(CapabilitiesArgs {
+            diff: Some(ids),
+            json,
+            ..
+        }) => cmd_capabilities_diff(&ctx, ids, json)?,
+        Commands::Capabilities(args) => cmd_capabilities(&ctx, args)?,
+        Commands::Get(args) => cmd_get(&ctx, args)?,
+        Commands::Set(args) => cmd_set(&ctx, args)?,
+        Commands::Brightness { .. } | Commands::Contrast { .. } | Commands::Volume { .. } => {
+            unreachable!("shortcuts are expanded above")
+        }
+        Commands::Geometry { action, display } => cmd_geometry(&ctx, action, display)?,
+        Commands::ColorTemp { action, display } => cmd_color_temp(&ctx, action, display)?,
+        Commands::Preset { action, display } => cmd_preset(&ctx, action, display)?,
+        Commands::Watch {
+            feature,
+            display,
+            interval_ms,
+            count,
+        } => cmd_watch(&ctx, feature, display, interval_ms, count)?,
+        Commands::SetMulti {
+            settings,
+            display,
+            post_delay_ms,
+        } => cmd_set_multi(&ctx, settings, display, post_delay_ms)?,
+        Commands::Batch {
+            file,
+            stop_on_error,
+        } => cmd_batch(&ctx, file, stop_on_error)?,
+        Commands::Clone {
+            source,
+            target,
+            features,
+        } => cmd_clone(&ctx, source, target, features)?,
+        Commands::Reset {
+            factory,
+            color_only,
+            display,
+            confirm,
+        } => cmd_reset(&ctx, factory, color_only, display, confirm)?,
+        Commands::Edid { display, raw } => cmd_edid(&ctx, display, raw)?,
+        Commands::Power { action, display } => cmd_power(&ctx, action, display)?,
+        Commands::Input {
+            action: InputAction::List,
+            ..
+        } => {
+            for source in InputSource::ALL {
+                out!(ctx.reporter, "0x{:02X}  {}", source.value(), source);
+            }
+        }
+        Commands::Input { action, display } => cmd_input(&ctx, action, display)?,
+        Commands::Vcp { command } => cmd_vcp(&ctx, command)?,
+        Commands::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "dispman", &mut io::stdout());
+        }
+        Commands::Config { command } => cmd_config(&ctx, command)?,
+        Commands::Profile { command } => cmd_profile(&ctx, command)?,
+        Commands::Layout { command } => cmd_layout(&ctx, command)?,
+        Commands::Daemon => run_daemon(ctx.reporter, ctx.ddc_options)?,
+        Commands::Schedule { command } => cmd_schedule(&ctx, command)?,
+        Commands::Inspect(args) => cmd_inspect(&ctx, args)?,
+    }
+
+    Ok(())
+}
+
+// One handler per subcommand, in the order they are dispatched above.
+
+fn cmd_detect(ctx: &Context, args: DetectArgs) -> anyhow::Result<()> {
+    let DetectArgs {
+        json,
+        wait,
+        min_displays,
+        full,
+        ready,
+        min_ready,
+        filter,
+        filter_model,
+        invert,
+    } = args;
+    if invert && filter.is_none() && filter_model.is_none() {
+        anyhow::bail!("--invert needs --filter or --filter-model");
+    }
+    let mut displays = match (wait, ready) {
+        (Some(ms), _) => {
+            enumerate_with_wait(&ctx.ddc_options, Duration::from_millis(ms), min_displays)?
+        }
+        (_, Some(ms)) => {
+            enumerate_until_ready(&ctx.ddc_options, Duration::from_millis(ms), min_ready)?
+        }
+        (None, None) => enumerate(&ctx.ddc_options)?,
+    };
+    displays.retain_mut(|d| {
+        let matches = filter.as_deref().is_none_or(|p| d.name_matches(p))
+            && filter_model
+                .as_deref()
+                .is_none_or(|p| d.model_matches(p).unwrap_or(false));
+        matches != invert
+    });
+    let format = if json {
+        OutputFormat::Json
+    } else {
+        ctx.output_format
+    };
+    // Pinging is slow, so the plain listing skips it unless --full.
+    let options = DetectOptions {
+        ping: full || format != OutputFormat::Text,
+        model: full,
+    };
+    let entries: Vec<DisplayEntry> = displays
+        .iter_mut()
+        .map(|d| DisplayEntry::gather(d, options))
+        .collect();
+    print_output(
+        &ctx.reporter,
+        &formatter(format, ctx.all_displays).format_displays(&entries)?,
+    );
+    Ok(())
+}
+
+fn cmd_info(ctx: &Context, json: bool) -> anyhow::Result<()> {
+    if ctx.output_format == OutputFormat::Csv && !json {
+        anyhow::bail!("info does not support CSV output");
+    }
+    let config = Config::load()?;
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let monitors: Vec<MonitorInfo> = displays
+        .iter_mut()
+        .map(|d| {
+            let alias = display_alias(&config, d);
+            let mut profiles: Vec<String> = config
+                .profiles
+                .iter()
+                .filter(|(_, p)| {
+                    p.settings.contains_key(d.stable_id())
+                        || alias.is_some_and(|a| p.settings.contains_key(a))
+                })
+                .map(|(name, _)| name.clone())
+                .collect();
+            profiles.sort();
+            MonitorInfo::gather(d, profiles)
+        })
+        .collect();
+    if json || ctx.output_format == OutputFormat::Json {
+        out!(ctx.reporter, "{}", serde_json::to_string_pretty(&monitors)?);
+        return Ok(());
+    }
+    out!(
+        ctx.reporter,
+        "dispman {} ({} {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    out!(ctx.reporter, "Displays: {}", monitors.len());
+    let show = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    for m in &monitors {
+        out!(ctx.reporter);
+        out!(ctx.reporter, "=== Display {}: {} ===", m.id, m.name);
+        out!(ctx.reporter, "  Stable ID:    {}", m.stable_id);
+        out!(
+            ctx.reporter,
+            "  Connection:   {}",
+            show(m.connection.map(|c| c.to_string()))
+        );
+        out!(
+            ctx.reporter,
+            "  DDC/CI:       {}",
+            if m.ddc_capable { "yes" } else { "no" }
+        );
+        let level = |v: &Option<VcpValue>| v.map(|v| format!("{}/{}", v.current, v.max));
+        out!(
+            ctx.reporter,
+            "  Brightness:   {}",
+            show(level(&m.brightness))
+        );
+        out!(ctx.reporter, "  Contrast:     {}", show(level(&m.contrast)));
+        out!(
+            ctx.reporter,
+            "  Input source: {}",
+            show(m.input_source.clone())
+        );
+        out!(
+            ctx.reporter,
+            "  Power mode:   {}",
+            show(m.power_mode.clone())
+        );
+        let profiles = if m.profiles.is_empty() {
+            "(none)".to_string()
+        } else {
+            m.profiles.join(", ")
+        };
+        out!(ctx.reporter, "  Profiles:     {}", profiles);
+    }
+    Ok(())
+}
+
+fn cmd_capabilities_diff(ctx: &Context, ids: Vec<usize>, json: bool) -> anyhow::Result<()> {
+    if ctx.output_format == OutputFormat::Csv && !json {
+        anyhow::bail!("capabilities --diff does not support CSV output");
+    }
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let a =
+        select_display_mut(&mut displays, Some(ids[0]), &ctx.targeting)?.capabilities_parsed()?;
+    let b =
+        select_display_mut(&mut displays, Some(ids[1]), &ctx.targeting)?.capabilities_parsed()?;
+    let diff = Capabilities::diff(&a, &b);
+    if json || ctx.output_format == OutputFormat::Json {
+        out!(ctx.reporter, "{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        out!(
+            ctx.reporter,
+            "A = Display {}, B = Display {}\n",
+            ids[0],
+            ids[1]
+        );
+        ctx.reporter.print(format_args!("{}", diff));
+    }
+    Ok(())
+}
+
+fn cmd_capabilities(ctx: &Context, args: CapabilitiesArgs) -> anyhow::Result<()> {
+    let CapabilitiesArgs {
+        display,
+        json,
+        commands_only,
+        watch,
+        interval_secs,
+        filter_writable,
+        ..
+    } = args;
+    if watch {
+        // Every poll has to reach the monitor, not the cached string.
+        let options = DdcOptions {
+            cache_capabilities: false,
+            ..ctx.ddc_options
+        };
+        let mut displays = enumerate(&options)?;
+        let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+        watch_capabilities(&ctx.reporter, target, Duration::from_secs(interval_secs))?;
+        return Ok(());
+    }
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    let mut caps = target.capabilities_parsed()?;
+    if filter_writable {
+        caps.vcp_features
+            .retain(|&code, _| !VcpFeature::from_code(code).is_typically_read_only());
+    }
+    if commands_only {
+        let commands = caps.command_list();
+        if json || ctx.output_format == OutputFormat::Json {
+            out!(ctx.reporter, "{}", serde_json::to_string_pretty(&commands)?);
+        } else if ctx.output_format == OutputFormat::Csv {
+            anyhow::bail!("capabilities --commands-only does not support CSV output");
+        } else {
+            for command in &commands {
+                out!(ctx.reporter, "{}", command);
+            }
+        }
+        return Ok(());
+    }
+    let format = if json {
+        OutputFormat::Json
+    } else {
+        ctx.output_format
+    };
+    print_output(
+        &ctx.reporter,
+        &formatter(format, ctx.all_displays).format_capabilities(&caps)?,
+    );
+    Ok(())
+}
+
+fn cmd_get(ctx: &Context, args: GetArgs) -> anyhow::Result<()> {
+    let GetArgs {
+        feature,
+        display,
+        percent,
+        check_support,
+        expect,
+        expect_percent,
+        tolerance,
+    } = args;
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let code = feature.parse::<VcpFeature>()?.code();
+    let expectation = match (expect, expect_percent) {
+        (Some(value), _) => Some(Expectation::Value(parse_set_value(code, &value)?)),
+        (_, Some(pct)) => Some(Expectation::Percent(pct)),
+        (None, None) => None,
+    };
+    let percent = percent || matches!(expectation, Some(Expectation::Percent(_)));
+    if ctx.all_displays {
+        check_all_displays_conflict(display, &ctx.targeting)?;
+        let mut readings = Vec::new();
+        let mut failures = Vec::new();
+        for d in displays.iter_mut() {
+            if check_support {
+                warn_if_unsupported(d, code);
+            }
+            match d
+                .get_vcp_feature(code)
+                .and_then(|value| vcp_reading(d, &feature, value, percent))
+            {
+                Ok(reading) => readings.push(reading),
+                Err(e) => failures.push((d.id, e)),
+            }
+        }
+        match expectation {
+            Some(expectation) => {
+                let mut all_pass = true;
+                for reading in &readings {
+                    let pass = expectation.matches(reading, tolerance);
+                    all_pass &= pass;
+                    print_expectation(
+                        &ctx.reporter,
+                        &ctx.formatter
+                            .format_vcp_value(std::slice::from_ref(reading))?,
+                        pass,
+                    );
+                }
+                report_failures(&failures, displays.len())?;
+                if !all_pass {
+                    std::process::exit(EXPECT_MISMATCH_EXIT_CODE);
+                }
+            }
+            None => {
+                print_output(&ctx.reporter, &ctx.formatter.format_vcp_value(&readings)?);
+                report_failures(&failures, displays.len())?;
+            }
+        }
+    } else {
+        let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+        if check_support {
+            warn_if_unsupported(target, code);
+        }
+        let value = target.get_vcp_feature(code)?;
+        let reading = vcp_reading(target, &feature, value, percent)?;
+        let output = ctx
+            .formatter
+            .format_vcp_value(std::slice::from_ref(&reading))?;
+        match expectation {
+            Some(expectation) => {
+                let pass = expectation.matches(&reading, tolerance);
+                print_expectation(&ctx.reporter, &output, pass);
+                if !pass {
+                    std::process::exit(EXPECT_MISMATCH_EXIT_CODE);
+                }
+            }
+            None => print_output(&ctx.reporter, &output),
+        }
+    }
+    Ok(())
+}
+
+fn cmd_set(ctx: &Context, args: SetArgs) -> anyhow::Result<()> {
+    let SetArgs {
+        feature,
+        value,
+        display,
+        percent,
+        increment,
+        decrement,
+        verify,
+        verify_tolerance,
+        verify_delay_ms,
+        transition_ms,
+        steps,
+        validate,
+        check_support,
+    } = args;
+    let code = feature.parse::<VcpFeature>()?.code();
+    let request = match (value, percent, increment, decrement) {
+        (Some(value), _, _, _) => SetRequest::Absolute(parse_set_value(code, &value)?),
+        (_, Some(pct), _, _) => SetRequest::Percent(pct),
+        (_, _, Some(delta), _) => SetRequest::Relative(i64::from(delta)),
+        (_, _, _, Some(delta)) => SetRequest::Relative(-i64::from(delta)),
+        (None, None, None, None) => unreachable!("clap requires one of the target group"),
+    };
+    let options = SetOptions {
+        verify: verify.then_some(Verify {
+            tolerance: verify_tolerance,
+            delay: Duration::from_millis(verify_delay_ms),
+        }),
+        transition: transition_ms.map(|duration_ms| Transition {
+            duration_ms,
+            steps,
+            verbose: ctx.verbose,
+        }),
+        validate,
+    };
+    let suffix = if verify { " (verified)" } else { "" };
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    if ctx.dry_run {
+        if ctx.all_displays {
+            check_all_displays_conflict(display, &ctx.targeting)?;
+            for d in &displays {
+                print_dry_run_set(&ctx.reporter, d, code, request);
+            }
+        } else {
+            let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+            print_dry_run_set(&ctx.reporter, target, code, request);
+        }
+    } else if ctx.all_displays {
+        check_all_displays_conflict(display, &ctx.targeting)?;
+        let mut failures = Vec::new();
+        for d in displays.iter_mut() {
+            if check_support {
+                warn_if_unsupported(d, code);
+            }
+            match apply_set(d, code, &feature, request, options) {
+                Ok((v, max)) => out!(
+                    ctx.reporter,
+                    "Display {} ({}): set {} to {}{}",
+                    d.id,
+                    d.name(),
+                    feature,
+                    describe_set(request, v, max),
+                    suffix
+                ),
+                Err(e) => failures.push((d.id, e)),
+            }
+        }
+        report_failures(&failures, displays.len())?;
+    } else {
+        let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+        if check_support {
+            warn_if_unsupported(target, code);
+        }
+        let (value, max) = apply_set(target, code, &feature, request, options)?;
+        out!(
+            ctx.reporter,
+            "Set {} to {}{}",
+            feature,
+            describe_set(request, value, max),
+            suffix
+        );
+    }
+    Ok(())
+}
+
+fn cmd_geometry(
+    ctx: &Context,
+    action: Option<GeometryAction>,
+    display: Option<usize>,
+) -> anyhow::Result<()> {
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    match action.unwrap_or(GeometryAction::Show) {
+        GeometryAction::Show => {
+            out!(
+                ctx.reporter,
+                "Display {} geometry: {}",
+                target.id,
+                target.name()
+            );
+            out!(
+                ctx.reporter,
+                "{:<6} {:<28} {:>8} {:>8}",
+                "Code",
+                "Name",
+                "Current",
+                "Max"
+            );
+            for feature in VcpFeature::geometry_codes() {
+                let (current, max) = match target.get_vcp_feature(feature.code()) {
+                    Ok(value) => (value.current.to_string(), value.max.to_string()),
+                    Err(_) => ("-".to_string(), "-".to_string()),
+                };
+                out!(
+                    ctx.reporter,
+                    "0x{:02X}   {:<28} {:>8} {:>8}",
+                    feature.code(),
+                    feature.name(),
+                    current,
+                    max
+                );
+            }
+        }
+        GeometryAction::Reset => {
+            let code = VcpFeature::AutoSetup.code();
+            if ctx.dry_run {
+                print_dry_run_set(&ctx.reporter, target, code, SetRequest::Absolute(1));
+            } else {
+                target.set_vcp_feature(code, 1)?;
+                out!(ctx.reporter, "Display {}: auto setup started", target.id);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_color_temp(
+    ctx: &Context,
+    action: Option<ColorTempAction>,
+    display: Option<usize>,
+) -> anyhow::Result<()> {
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    match action.unwrap_or(ColorTempAction::Get) {
+        ColorTempAction::Get => {
+            let preset = target.get_color_preset()?;
+            out!(
+                ctx.reporter,
+                "Display {}: {} (0x{:02X})",
+                target.id,
+                preset,
+                preset.value()
+            );
+        }
+        ColorTempAction::Set { preset } => {
+            let preset = parse_color_preset(&preset)?;
+            if ctx.dry_run {
+                out!(
+                    ctx.reporter,
+                    "[DRY RUN] Would set color preset to {} (0x{:02X}) on Display {}: {}",
+                    preset,
+                    preset.value(),
+                    target.id,
+                    target.name()
+                );
+            } else {
+                target.set_color_preset(preset)?;
+                out!(
+                    ctx.reporter,
+                    "Display {}: set color preset to {} (0x{:02X})",
+                    target.id,
+                    preset,
+                    preset.value()
+                );
+            }
+        }
+        ColorTempAction::List => {
+            let supported: Vec<u16> = match target.capabilities_parsed() {
+                Ok(caps) => caps.vcp_features.get(&0x14).cloned().unwrap_or_default(),
+                Err(e) => {
+                    eprintln!(
+                        "Warning: could not read capabilities of display {}: {}",
+                        target.id, e
+                    );
+                    Vec::new()
+                }
+            };
+            let mut presets: Vec<ColorPreset> = ColorPreset::ALL.to_vec();
+            presets.extend(
+                supported
+                    .iter()
+                    .map(|&v| ColorPreset::from_value(v))
+                    .filter(|p| matches!(p, ColorPreset::Custom(_))),
+            );
+            presets.sort_by_key(ColorPreset::value);
+            out!(
+                ctx.reporter,
+                "Display {} color presets (* = supported):",
+                target.id
+            );
+            for preset in presets {
+                let mark = if supported.contains(&preset.value()) {
+                    "*"
+                } else {
+                    " "
+                };
+                out!(
+                    ctx.reporter,
+                    "{} 0x{:02X}  {}",
+                    mark,
+                    preset.value(),
+                    preset
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_preset(
+    ctx: &Context,
+    action: Option<PresetAction>,
+    display: Option<usize>,
+) -> anyhow::Result<()> {
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    match action.unwrap_or(PresetAction::Get) {
+        PresetAction::Get => {
+            let preset = target.get_picture_preset()?;
+            out!(
+                ctx.reporter,
+                "Display {}: {} (0x{:02X})",
+                target.id,
+                preset,
+                preset.value()
+            );
+        }
+        PresetAction::Set { preset } => {
+            let preset = parse_picture_preset(&preset)?;
+            if ctx.dry_run {
+                out!(
+                    ctx.reporter,
+                    "[DRY RUN] Would set picture preset to {} (0x{:02X}) on Display {}: {}",
+                    preset,
+                    preset.value(),
+                    target.id,
+                    target.name()
+                );
+            } else {
+                target.set_picture_preset(preset)?;
+                out!(
+                    ctx.reporter,
+                    "Display {}: set picture preset to {} (0x{:02X})",
+                    target.id,
+                    preset,
+                    preset.value()
+                );
+            }
+        }
+        PresetAction::List => {
+            let presets: Vec<PicturePreset> = match target.capabilities_parsed() {
+                Ok(caps) => caps
+                    .vcp_features
+                    .get(&0xDC)
+                    .map(|values| {
+                        values
+                            .iter()
+                            .map(|&v| PicturePreset::from_value(v))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                Err(e) => {
+                    eprintln!(
+                        "Warning: could not read capabilities of display {}: {}",
+                        target.id, e
+                    );
+                    PicturePreset::ALL.to_vec()
+                }
+            };
+            if presets.is_empty() {
+                out!(
+                    ctx.reporter,
+                    "Display {} does not list any picture presets (VCP 0xDC).",
+                    target.id
+                );
+            }
+            for preset in presets {
+                out!(ctx.reporter, "0x{:02X}  {}", preset.value(), preset);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_watch(
+    ctx: &Context,
+    feature: String,
+    display: Option<usize>,
+    interval_ms: u64,
+    count: Option<usize>,
+) -> anyhow::Result<()> {
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    let code = feature.parse::<VcpFeature>()?.code();
+
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = Arc::clone(&running);
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))?;
+
+    let mut previous = target.get_vcp_feature(code)?.current;
+    out!(
+        ctx.reporter,
+        "Watching {} (0x{:02X}) on Display {}: {}",
+        feature,
+        code,
+        target.id,
+        previous
+    );
+
+    let mut changes = 0;
+    let interval = Duration::from_millis(interval_ms);
+    while running.load(Ordering::SeqCst) && count.is_none_or(|n| changes < n) {
+        thread::sleep(interval);
+        match target.get_vcp_feature(code) {
+            Ok(value) if value.current != previous => {
+                out!(
+                    ctx.reporter,
+                    "[{}] {} 0x{:02X} changed: {} -> {}",
+                    Local::now().format("%H:%M:%S"),
+                    feature,
+                    code,
+                    previous,
+                    value.current
+                );
+                previous = value.current;
+                changes += 1;
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Read failed: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn cmd_set_multi(
+    ctx: &Context,
+    settings: Vec<String>,
+    display: Option<usize>,
+    post_delay_ms: u64,
+) -> anyhow::Result<()> {
+    let pairs = settings
+        .iter()
+        .map(|s| parse_setting(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    if ctx.dry_run {
+        for (code, value) in &pairs {
+            print_dry_run_set(&ctx.reporter, target, *code, SetRequest::Absolute(*value));
+        }
+        return Ok(());
+    }
+    for (code, value) in &pairs {
+        target.set_vcp_feature(*code, *value)?;
+    }
+    if post_delay_ms > 0 {
+        thread::sleep(Duration::from_millis(post_delay_ms));
+    }
+    for (code, value) in &pairs {
+        out!(
+            ctx.reporter,
+            "Set 0x{:02X} ({}) to {} on Display {}",
+            code,
+            VcpFeature::from_code(*code).name(),
+            value,
+            target.id
+        );
+    }
+    Ok(())
+}
+
+fn cmd_batch(ctx: &Context, file: PathBuf, stop_on_error: bool) -> anyhow::Result<()> {
+    let script = fs::read_to_string(&file)?;
+    let steps = parse_batch(&script)?;
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let (mut succeeded, mut failed) = (0, 0);
+    for step in &steps {
+        ctx.reporter.verbose(format_args!("> {}", step.source));
+        match run_batch_step(
+            &ctx.reporter,
+            &mut displays,
+            step,
+            &ctx.targeting,
+            ctx.dry_run,
+        ) {
+            Ok(()) => succeeded += 1,
+            Err(e) => {
+                failed += 1;
+                eprintln!("Line {}: {}", step.line, e);
+                if stop_on_error {
+                    break;
+                }
+            }
+        }
+    }
+    out!(
+        ctx.reporter,
+        "Batch finished: {} succeeded, {} failed",
+        succeeded,
+        failed
+    );
+    if failed > 0 {
+        anyhow::bail!("{} of {} batch commands failed", failed, steps.len());
+    }
+    Ok(())
+}
+
+fn cmd_clone(
+    ctx: &Context,
+    source: usize,
+    target: usize,
+    features: Option<String>,
+) -> anyhow::Result<()> {
+    if source == target {
+        anyhow::bail!("Source and target must be different displays");
+    }
+    let codes = parse_feature_list(features.as_deref().unwrap_or(DEFAULT_CLONE_FEATURES))?;
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let Some((from, to)) = displays_pair_mut(&mut displays, source, target) else {
+        anyhow::bail!("Display {} or {} not found", source, target);
+    };
+    if ctx.dry_run {
+        for code in &codes {
+            out!(
+                ctx.reporter,
+                "[DRY RUN] Would copy 0x{:02X} ({}) from Display {} to Display {}",
+                code,
+                VcpFeature::from_code(*code).name(),
+                from.id,
+                to.id
+            );
+        }
+        return Ok(());
+    }
+    out!(ctx.reporter, "Display {} -> Display {}:", from.id, to.id);
+    for change in from.clone_settings_to(to, &codes)? {
+        let name = VcpFeature::from_code(change.code).name();
+        if change.before == change.after {
+            out!(
+                ctx.reporter,
+                "  0x{:02X} {}: {} (unchanged)",
+                change.code,
+                name,
+                change.after
+            );
+        } else {
+            out!(
+                ctx.reporter,
+                "  0x{:02X} {}: {} -> {}",
+                change.code,
+                name,
+                change.before,
+                change.after
+            );
+        }
+    }
+    Ok(())
+}
+
+fn cmd_reset(
+    ctx: &Context,
+    factory: bool,
+    color_only: bool,
+    display: Option<usize>,
+    confirm: bool,
+) -> anyhow::Result<()> {
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    if confirm && !confirm_on_stdin("This will reset monitor settings. Type 'yes' to continue:")? {
+        anyhow::bail!("Reset aborted");
+    }
+    if ctx.dry_run {
+        let what = if factory {
+            "factory defaults"
+        } else {
+            "factory color defaults"
+        };
+        out!(
+            ctx.reporter,
+            "[DRY RUN] Would restore {} on Display {}: {}",
+            what,
+            target.id,
+            target.name()
+        );
+    } else if factory {
+        target.restore_factory_defaults()?;
+        out!(
+            ctx.reporter,
+            "Display {}: restored factory defaults",
+            target.id
+        );
+    } else if color_only {
+        target.restore_factory_color_defaults()?;
+        out!(
+            ctx.reporter,
+            "Display {}: restored factory color defaults",
+            target.id
+        );
+    }
+    Ok(())
+}
+
+fn cmd_edid(ctx: &Context, display: Option<usize>, raw: bool) -> anyhow::Result<()> {
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    let edid = target.edid()?;
+    if raw {
+        for (i, chunk) in edid.chunks(16).enumerate() {
+            let bytes: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+            out!(ctx.reporter, "{:04X}: {}", i * 16, bytes.join(" "));
+        }
+    } else {
+        out!(ctx.reporter, "Display {}: {}", target.id, target.name());
+        out!(ctx.reporter, "{}", EdidInfo::parse(&edid)?);
+    }
+    Ok(())
+}
+
+fn cmd_power(ctx: &Context, action: PowerAction, display: Option<usize>) -> anyhow::Result<()> {
+    let mode = match action {
+        PowerAction::Get => None,
+        PowerAction::Standby => Some(PowerMode::Standby),
+        PowerAction::Suspend => Some(PowerMode::Suspend),
+        PowerAction::Off => Some(PowerMode::Off),
+        PowerAction::On => Some(PowerMode::On),
+    };
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    if ctx.all_displays {
+        check_all_displays_conflict(display, &ctx.targeting)?;
+        let mut failures = Vec::new();
+        for d in displays.iter_mut() {
+            if let Err(e) = run_power(&ctx.reporter, d, mode, ctx.dry_run) {
+                failures.push((d.id, e));
+            }
+        }
+        report_failures(&failures, displays.len())?;
+    } else {
+        let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+        run_power(&ctx.reporter, target, mode, ctx.dry_run)?;
+    }
+    Ok(())
+}
+
+fn cmd_input(ctx: &Context, action: InputAction, display: Option<usize>) -> anyhow::Result<()> {
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+    match action {
+        InputAction::Get => {
+            let value = target.get_vcp_feature(0x60)?.current as u16;
+            let source = InputSource::from_value(value);
+            out!(
+                ctx.reporter,
+                "Display {}: {} (0x{:02X})",
+                target.id,
+                source,
+                value
+            );
+        }
+        InputAction::Switch { source } => {
+            let mut source = parse_input_source(&source)?;
+            // USB-C has no standard value; use the one this monitor
+            // advertises, if it can be told apart. A dry run must not
+            // talk to the monitor, so it shows the fallback value.
+            if let InputSource::UsbC(_) = source
+                && !ctx.dry_run
+                && let Some(resolved) = target
+                    .capabilities_parsed()
+                    .ok()
+                    .and_then(|caps| InputSource::resolve_usb_c(&caps))
+            {
+                source = resolved;
+            }
+            if ctx.dry_run {
+                out!(
+                    ctx.reporter,
+                    "[DRY RUN] Would switch input to {} (0x{:02X}) on Display {}: {}",
+                    source,
+                    source.value(),
+                    target.id,
+                    target.name()
+                );
+            } else {
+                target.set_vcp_feature(0x60, u32::from(source.value()))?;
+                out!(
+                    ctx.reporter,
+                    "Display {}: switched input to {} (0x{:02X})",
+                    target.id,
+                    source,
+                    source.value()
+                );
+            }
+        }
+        InputAction::Next | InputAction::Prev => {
+            let forward = matches!(action, InputAction::Next);
+            let (from, source) = target.adjacent_input(forward)?;
+            if ctx.dry_run {
+                out!(
+                    ctx.reporter,
+                    "[DRY RUN] Would switch input: {} -> {} on Display {}: {}",
+                    from,
+                    source,
+                    target.id,
+                    target.name()
+                );
+            } else {
+                target.set_vcp_feature(0x60, u32::from(source.value()))?;
+                out!(ctx.reporter, "Switched input: {} -> {}", from, source);
+            }
+        }
+        InputAction::List => unreachable!("handled above"),
+    }
+    Ok(())
+}
+
+fn cmd_vcp(ctx: &Context, command: VcpCommands) -> anyhow::Result<()> {
+    match command {
+        VcpCommands::List { category, sort_by } => {
+            let category =
+                match category {
+                    Some(name) => Some(VcpCategory::from_name(&name).ok_or_else(|| {
+                        DisplayError::config(format!("Unknown category: {}", name))
+                    })?),
+                    None => None,
+                };
+            let mut features: Vec<VcpFeature> = VcpFeature::ALL_KNOWN
+                .iter()
+                .copied()
+                .filter(|f| category.is_none_or(|c| f.category() == c))
+                .collect();
+            match sort_by {
+                VcpSortKey::Code => features.sort_by_key(|f| f.code()),
+                VcpSortKey::Name => features.sort_by_key(|f| f.name()),
+            }
+            out!(
+                ctx.reporter,
+                "{:<6} {:<28} {:<10} Type",
+                "Code",
+                "Name",
+                "Category"
+            );
+            for f in features {
+                out!(
+                    ctx.reporter,
+                    "0x{:02X}   {:<28} {:<10} {}",
+                    f.code(),
+                    f.name(),
+                    f.category().to_string(),
+                    format_continuity(&f)
+                );
+            }
+        }
+        VcpCommands::Describe { code } => {
+            let feature = code.parse::<VcpFeature>()?;
+            out!(ctx.reporter, "{}", feature);
+            out!(ctx.reporter, "  Category: {}", feature.category());
+            out!(ctx.reporter, "  Type:     {}", format_continuity(&feature));
+            out!(ctx.reporter);
+            out!(ctx.reporter, "  {}", feature.description());
+        }
+    }
+    Ok(())
+}
+
+fn cmd_config(ctx: &Context, command: ConfigCommands) -> anyhow::Result<()> {
+    match command {
+        ConfigCommands::Backup => {
+            let path = Config::backup()?;
+            out!(ctx.reporter, "Config backed up to {}", path.display());
+        }
+        ConfigCommands::Reset { force } => {
+            let mut config = Config::load()?;
+            let count = config.profiles.len();
+            if count == 0 {
+                out!(ctx.reporter, "No profiles to delete.");
+                return Ok(());
+            }
+            if !force {
+                let prompt = format!(
+                    "This will delete {} profile(s). Type 'yes' to continue:",
+                    count
+                );
+                if !confirm_on_stdin(&prompt)? {
+                    anyhow::bail!("Reset aborted");
+                }
+            }
+            let removed = config.remove_all_profiles();
+            config.save()?;
+            out!(ctx.reporter, "Deleted {} profile(s).", removed);
+        }
+        ConfigCommands::Get { key } => {
+            out!(ctx.reporter, "{}", Config::load()?.settings.get(&key)?);
+        }
+        ConfigCommands::Set { key, value } => {
+            let mut config = Config::load()?;
+            config.settings.set(&key, &value)?;
+            config.save()?;
+            out!(ctx.reporter, "{} = {}", key, config.settings.get(&key)?);
+        }
+        ConfigCommands::List => {
+            let settings = Config::load()?.settings;
+            for key in GlobalSettings::KEYS {
+                out!(ctx.reporter, "{} = {}", key, settings.get(key)?);
+            }
+        }
+        ConfigCommands::Path => {
+            out!(ctx.reporter, "{}", Config::config_path()?.display());
+        }
+        ConfigCommands::Show => {
+            let config = Config::load()?;
+            ctx.reporter
+                .print(format_args!("{}", toml::to_string_pretty(&config)?));
+        }
+        ConfigCommands::Schema { yaml } => {
+            let schema = Config::json_schema();
+            if yaml {
+                ctx.reporter
+                    .print(format_args!("{}", serde_yaml_ng::to_string(&schema)?));
+            } else {
+                out!(ctx.reporter, "{}", serde_json::to_string_pretty(&schema)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_profile(ctx: &Context, command: ProfileCommands) -> anyhow::Result<()> {
+    match command {
+        ProfileCommands::Save {
+            name,
+            include_all,
+            include,
+            continuous_only,
+            display,
+            append,
+            interactive,
+            yes_to_all,
+            use_alias,
+            exclude,
+            name_from_hostname,
+            timestamp,
+            keep,
+            from_snapshot,
+            batch,
+        } => {
+            let host = if name_from_hostname {
+                Some(hostname()?)
+            } else {
+                hostname().ok()
+            };
+            let base_name = match (&host, name_from_hostname) {
+                (Some(host), true) => host_profile_name(&name, host),
+                _ => name,
+            };
+            let name = if timestamp {
+                timestamped_profile_name(&base_name, Utc::now())
+            } else {
+                base_name.clone()
+            };
+            let include_all = include_all || interactive;
+            let extra = match include {
+                Some(list) => parse_feature_list(&list)?,
+                None => Vec::new(),
+            };
+            let mut config = Config::load()?;
+            let (mut settings, created_at, mut excluded_codes) = match config.get_profile(&name) {
+                Some(existing) if append => (
+                    existing.settings.clone(),
+                    existing.created_at,
+                    existing.excluded_codes.clone(),
+                ),
+                _ => (HashMap::new(), None, Vec::new()),
+            };
+            if let Some(list) = exclude {
+                excluded_codes.extend(parse_feature_list(&list)?);
+                excluded_codes.sort_unstable();
+                excluded_codes.dedup();
+            }
+
+            let mut displays = if let Some(path) = from_snapshot {
+                let snapshot: VcpSnapshot = serde_json::from_str(&fs::read_to_string(&path)?)?;
+                if snapshot.stable_id.is_empty() {
+                    anyhow::bail!(
+                        "Snapshot {} does not name its monitor; take it again with `inspect --snapshot`",
+                        path.display()
+                    );
+                }
+                let mut display_settings: Vec<(u8, u32)> = snapshot
+                    .values
+                    .iter()
+                    .filter(|(code, value)| {
+                        (value.is_continuous || !continuous_only) && !excluded_codes.contains(code)
+                    })
+                    .map(|(&code, value)| (code, value.current))
+                    .collect();
+                display_settings.sort_unstable();
+                settings.insert(snapshot.stable_id, display_settings);
+                Vec::new()
+            } else {
+                let mut displays = enumerate(&ctx.ddc_options)?;
+                if display.is_some() || ctx.targeting.display_name.is_some() {
+                    let id = select_display_mut(&mut displays, display, &ctx.targeting)?.id;
+                    displays.retain(|d| d.id == id);
+                }
+                displays
+            };
+
+            for d in displays.iter_mut() {
+                let mut codes = vec![0x10, 0x12, 0x60, 0x62];
+                if include_all {
+                    match d.capabilities_parsed() {
+                        Ok(caps) => codes.extend(caps.vcp_features.keys()),
+                        Err(e) => eprintln!(
+                            "Display {}: could not read capabilities ({}); saving defaults only",
+                            d.id, e
+                        ),
+                    }
+                }
+                codes.extend(&extra);
+                codes.sort_unstable();
+                codes.dedup();
+                codes.retain(|code| !excluded_codes.contains(code));
+
+                let batched = batch.then(|| d.get_vcp_feature_batch(&codes).unwrap_or_default());
+                let mut display_settings = Vec::new();
+                for code in codes {
+                    let value = match &batched {
+                        Some(values) => values.get(&code).copied(),
+                        None => d.get_vcp_feature(code).ok(),
+                    };
+                    match value {
+                        Some(val) if val.is_continuous || !continuous_only => {
+                            display_settings.push((code, val.current))
+                        }
+                        _ => {}
+                    }
+                    if ctx.reporter.is_verbose() {
+                        eprint!(".");
+                    }
+                }
+                if interactive && !yes_to_all {
+                    let mut kept = Vec::new();
+                    for (code, value) in display_settings {
+                        let prompt = format!(
+                            "Display {}: 0x{:02X} {} = {}. Include in profile?",
+                            d.id,
+                            code,
+                            VcpFeature::from_code(code).name(),
+                            value
+                        );
+                        if ask_yes_no(&prompt)? {
+                            kept.push((code, value));
+                        }
+                    }
+                    display_settings = kept;
+                }
+                ctx.reporter.verbose(format_args!(
+                    " display {}: {} features saved",
+                    d.id,
+                    display_settings.len()
+                ));
+                let key = match display_alias(&config, d) {
+                    Some(alias) if use_alias => alias,
+                    _ => d.stable_id(),
+                };
+                settings.insert(key.to_string(), display_settings);
+            }
+
+            let created_at = created_at.or_else(|| Some(Utc::now()));
+            let delay_between_sets_ms = config
+                .get_profile(&name)
+                .and_then(|existing| existing.delay_between_sets_ms);
+            config.save_profile(
+                name.clone(),
+                Profile {
+                    settings,
+                    created_at,
+                    excluded_codes,
+                    delay_between_sets_ms,
+                    hostname: host,
+                },
+            );
+            let pruned = match keep {
+                Some(keep) => config.prune_timestamped_profiles(&base_name, keep),
+                None => Vec::new(),
+            };
+            config.save()?;
+            out!(ctx.reporter, "Profile '{}' saved.", name);
+            for old in pruned {
+                out!(ctx.reporter, "Removed old version '{}'.", old);
+            }
+        }
+        ProfileCommands::Load {
+            name,
+            display,
+            concurrent,
+            skip_unresponsive,
+            chain,
+            on_failure,
+            name_from_hostname,
+            preview,
+            progress,
+            skip_unchanged,
+        } => {
+            let config = Config::load()?;
+            // Prefer this machine's variant of the profile when it exists.
+            let name = match name {
+                Some(name) if name_from_hostname => {
+                    let host_name = host_profile_name(&name, &hostname()?);
+                    if config.resolve_profile(&host_name)?.is_some() {
+                        Some(host_name)
+                    } else {
+                        Some(name)
+                    }
+                }
+                name => name,
+            };
+            let (name, profile) = match (name, chain) {
+                (_, Some(list)) => {
+                    let names = parse_name_list(&list);
+                    (names.join(","), Some(config.merge_chain(&names)?))
+                }
+                (Some(name), None) => {
+                    let profile = config.resolve_profile(&name)?;
+                    (name, profile)
+                }
+                (None, None) => unreachable!("clap requires NAME or --chain"),
+            };
+            if let Some(profile) = profile {
+                let mut displays = enumerate(&ctx.ddc_options)?;
+                let profile = resolve_aliases(&config, &profile, &displays);
+                if display.is_some() || ctx.targeting.display_name.is_some() {
+                    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+                    if !profile.settings.contains_key(target.stable_id()) {
+                        anyhow::bail!(
+                            "Profile '{}' has no settings for display {} ({})",
+                            name,
+                            target.id,
+                            target.stable_id()
+                        );
+                    }
+                    let id = target.id;
+                    displays.retain(|d| d.id == id);
+                }
+                displays.retain(|d| profile.settings.contains_key(d.stable_id()));
+                if !ctx.dry_run {
+                    let mut unresponsive = Vec::new();
+                    for d in displays.iter_mut() {
+                        if !d.is_ddc_capable() {
+                            unresponsive.push(d.id);
+                        }
+                    }
+                    if !unresponsive.is_empty() && !skip_unresponsive {
+                        anyhow::bail!(
+                            "Display(s) {:?} do not respond to DDC/CI; nothing was changed \
+                         (pass --skip-unresponsive to load the others)",
+                            unresponsive
+                        );
+                    }
+                    for id in &unresponsive {
+                        eprintln!("Warning: skipping display {}: no DDC/CI response", id);
+                    }
+                    displays.retain(|d| !unresponsive.contains(&d.id));
+                }
+                if preview {
+                    let mut total = 0;
+                    let mut changed = 0;
+                    for d in displays.iter_mut() {
+                        let entries = profile::diff_against_live(&profile, d);
+                        if entries.is_empty() {
+                            continue;
+                        }
+                        out!(ctx.reporter, "Display {} ({}):", d.id, d.name());
+                        for entry in &entries {
+                            let current = match entry.current {
+                                Some(value) => profile::format_value(entry.code, value),
+                                None => "?".to_string(),
+                            };
+                            let marker = if entry.matches() { " [unchanged]" } else { "" };
+                            out!(
+                                ctx.reporter,
+                                "  0x{:02X} {}: {} -> {}{}",
+                                entry.code,
+                                VcpFeature::from_code(entry.code).name(),
+                                current,
+                                profile::format_value(entry.code, entry.saved),
+                                marker
+                            );
+                        }
+                        total += entries.len();
+                        changed += entries.iter().filter(|e| !e.matches()).count();
+                    }
+                    out!(
+                        ctx.reporter,
+                        "Profile '{}': {} of {} setting(s) would change.",
+                        name,
+                        changed,
+                        total
+                    );
+                    if changed > 0 {
+                        std::process::exit(PREVIEW_CHANGES_EXIT_CODE);
+                    }
+                    return Ok(());
+                }
+                if ctx.dry_run {
+                    let mut count = 0;
+                    let mut touched = 0;
+                    for d in &displays {
+                        if let Some(settings) = profile.settings.get(d.stable_id()) {
+                            touched += 1;
+                            for (code, value) in settings {
+                                print_dry_run_set(
+                                    &ctx.reporter,
+                                    d,
+                                    *code,
+                                    SetRequest::Absolute(*value),
+                                );
+                                count += 1;
+                            }
+                        }
+                    }
+                    out!(
+                        ctx.reporter,
+                        "[DRY RUN] Profile '{}': {} value(s) would be set on {} display(s).",
+                        name,
+                        count,
+                        touched
+                    );
+                    return Ok(());
+                }
+                if concurrent {
+                    load_profile_concurrent(&name, displays, &profile)?;
+                    out!(ctx.reporter, "Profile '{}' loaded.", name);
+                    return Ok(());
+                }
+                let policy = match on_failure {
+                    OnFailure::Continue => OnFailurePolicy::Continue,
+                    OnFailure::Abort => OnFailurePolicy::Abort,
+                    OnFailure::Warn => OnFailurePolicy::Warn,
+                };
+                let mut bar = None;
+                if progress && !ctx.reporter.is_quiet() {
+                    let total = profile::count_writes(&profile, &displays);
+                    let touched = displays
+                        .iter()
+                        .filter(|d| profile.settings.contains_key(d.stable_id()))
+                        .count();
+                    eprintln!(
+                        "Loading profile '{}' ({} settings across {} displays)",
+                        name, total, touched
+                    );
+                    // The per-write lines of --verbose already count progress.
+                    if !ctx.reporter.is_verbose() {
+                        bar = Some(ProgressBar::new(total));
+                    }
+                }
+                let mut result = profile::apply_with_progress(
+                    &profile,
+                    &mut displays,
+                    policy,
+                    skip_unchanged,
+                    |step| {
+                        ctx.reporter.verbose(format_args!(
+                            "[{}/{}] Display {} ({}): setting {} to {}...",
+                            step.index,
+                            step.total,
+                            step.display.id,
+                            step.display.name(),
+                            VcpFeature::from_code(step.code).name(),
+                            step.value
+                        ));
+                        if let Some(bar) = bar.as_mut() {
+                            bar.inc();
+                        }
+                    },
+                );
+                if let Some(bar) = bar {
+                    bar.finish();
+                }
+                for failure in &result.failed {
+                    if policy == OnFailurePolicy::Warn {
+                        eprintln!("Warning: {}", failure);
+                    } else {
+                        eprintln!("{}", failure);
+                    }
+                }
+                out!(ctx.reporter, "{}", result);
+                if policy != OnFailurePolicy::Warn && !result.failed.is_empty() {
+                    let first = result.failed.swap_remove(0);
+                    return Err(anyhow::Error::new(first.error)
+                        .context(format!("Profile '{}' was not fully applied", name)));
+                }
+                out!(ctx.reporter, "Profile '{}' loaded.", name);
+            } else {
+                return Err(DisplayError::config(format!("Profile '{}' not found", name)).into());
+            }
+        }
+        ProfileCommands::Chain { command } => match command {
+            ChainCommands::Save { name, profiles } => {
+                let mut config = Config::load()?;
+                config.save_chain(name.clone(), parse_name_list(&profiles))?;
+                config.save()?;
+                out!(ctx.reporter, "Chain '{}' saved.", name);
+            }
+            ChainCommands::Delete { name } => {
+                let mut config = Config::load()?;
+                if config.chains.remove(&name).is_none() {
+                    return Err(DisplayError::config(format!("Chain '{}' not found", name)).into());
+                }
+                config.save()?;
+                out!(ctx.reporter, "Chain '{}' deleted.", name);
+            }
+            ChainCommands::List => {
+                let config = Config::load()?;
+                for (name, profiles) in &config.chains {
+                    out!(ctx.reporter, "{} = {}", name, profiles.join(","));
+                }
+            }
+        },
+        ProfileCommands::List { json } if ctx.reporter.is_verbose() => {
+            let config = Config::load()?;
+            let profiles = config.list_profiles();
+            if json {
+                let details: Vec<ProfileDetail> = profiles
+                    .iter()
+                    .filter_map(|meta| {
+                        let profile = config.get_profile(&meta.name)?;
+                        Some(ProfileDetail::new(&meta.name, profile))
+                    })
+                    .collect();
+                out!(ctx.reporter, "{}", serde_json::to_string_pretty(&details)?);
+            } else {
+                for meta in &profiles {
+                    let Some(profile) = config.get_profile(&meta.name) else {
+                        continue;
+                    };
+                    match &profile.hostname {
+                        Some(host) => out!(ctx.reporter, "{} (saved on {})", meta.name, host),
+                        None => out!(ctx.reporter, "{}", meta.name),
+                    }
+                    for line in profile.describe().lines() {
+                        out!(ctx.reporter, "  {}", line);
+                    }
+                }
+            }
+        }
+        ProfileCommands::List { json } => {
+            let profiles = Config::load()?.list_profiles();
+            let output = if json {
+                formatter(OutputFormat::Json, ctx.all_displays).format_profile_list(&profiles)?
+            } else {
+                ctx.formatter.format_profile_list(&profiles)?
+            };
+            print_output(&ctx.reporter, &output);
+        }
+        ProfileCommands::Delete { name } => {
+            let mut config = Config::load()?;
+            if !config.remove_profile(&name) {
+                return Err(DisplayError::config(format!("Profile '{}' not found", name)).into());
+            }
+            config.save()?;
+            out!(ctx.reporter, "Profile '{}' deleted.", name);
+        }
+        ProfileCommands::Diff { a, b, json } => {
+            let config = Config::load()?;
+            let profile_a = config
+                .get_profile(&a)
+                .ok_or_else(|| DisplayError::config(format!("Profile '{}' not found", a)))?;
+            let profile_b = config
+                .get_profile(&b)
+                .ok_or_else(|| DisplayError::config(format!("Profile '{}' not found", b)))?;
+            let entries = profile::diff(profile_a, profile_b);
 
-    match cli.command {
-        Commands::Detect { json } => {
-            let displays = backend::enumerate()?;
             if json {
-                println!("{}", serde_json::to_string_pretty(&displays)?);
+                out!(ctx.reporter, "{}", serde_json::to_string_pretty(&entries)?);
+            } else if entries.is_empty() {
+                out!(ctx.reporter, "Profiles '{}' and '{}' are identical.", a, b);
             } else {
-                for d in displays {
-                    println!(
-                        "Display {}: {} (stable id: {})",
-                        d.id,
-                        d.name(),
-                        d.stable_id()
+                let show = |v: Option<u32>| v.map_or("—".to_string(), |v| v.to_string());
+                let mut current_display = None;
+                for entry in &entries {
+                    if current_display != Some(&entry.display) {
+                        out!(ctx.reporter, "{}:", entry.display);
+                        out!(ctx.reporter, "  {:<6} {:>10} {:>10}", "Code", a, b);
+                        current_display = Some(&entry.display);
+                    }
+                    out!(
+                        ctx.reporter,
+                        "  0x{:02X}   {:>10} {:>10}",
+                        entry.code,
+                        show(entry.a),
+                        show(entry.b)
+                    );
+                }
+            }
+        }
+        ProfileCommands::Validate { name } => {
+            let config = Config::load()?;
+            let Some(profile) = config.resolve_profile(&name)? else {
+                return Err(DisplayError::config(format!("Profile '{}' not found", name)).into());
+            };
+            let mut displays = enumerate(&ctx.ddc_options)?;
+            let profile = resolve_aliases(&config, &profile, &displays);
+            let mut problems = 0;
+            for d in displays.iter_mut() {
+                if !profile.settings.contains_key(d.stable_id()) {
+                    continue;
+                }
+                let validation = profile.validate(d)?;
+                out!(
+                    ctx.reporter,
+                    "Display {} ({}): {} valid, {} unsupported, {} out of range",
+                    d.id,
+                    d.name(),
+                    validation.valid.len(),
+                    validation.unsupported_codes.len(),
+                    validation.out_of_range.len()
+                );
+                for code in &validation.unsupported_codes {
+                    out!(
+                        ctx.reporter,
+                        "  0x{:02X} {}: not supported",
+                        code,
+                        VcpFeature::from_code(*code).name()
+                    );
+                }
+                for (code, value) in &validation.out_of_range {
+                    out!(
+                        ctx.reporter,
+                        "  0x{:02X} {}: value {} out of range",
+                        code,
+                        VcpFeature::from_code(*code).name(),
+                        profile::format_value(*code, *value)
+                    );
+                }
+                problems += validation.unsupported_codes.len() + validation.out_of_range.len();
+            }
+            let mut missing: Vec<&String> = profile
+                .settings
+                .keys()
+                .filter(|id| !displays.iter().any(|d| d.stable_id() == id.as_str()))
+                .collect();
+            missing.sort();
+            for id in missing {
+                out!(ctx.reporter, "Skipped {}: not connected", id);
+            }
+            if problems > 0 {
+                anyhow::bail!("Profile '{}' has {} invalid setting(s)", name, problems);
+            }
+            out!(ctx.reporter, "Profile '{}' is valid.", name);
+        }
+        ProfileCommands::SetDelay { name, ms } => {
+            let mut config = Config::load()?;
+            config.set_profile_delay(&name, ms)?;
+            config.save()?;
+            match ms {
+                Some(ms) => out!(
+                    ctx.reporter,
+                    "Profile '{}' will pause {} ms between writes.",
+                    name,
+                    ms
+                ),
+                None => out!(
+                    ctx.reporter,
+                    "Profile '{}' uses the default write delay.",
+                    name
+                ),
+            }
+        }
+        ProfileCommands::Copy { src, dst, force } => {
+            let mut config = Config::load()?;
+            config.copy_profile(&src, &dst, force)?;
+            config.save()?;
+            out!(ctx.reporter, "Profile '{}' copied to '{}'.", src, dst);
+        }
+        ProfileCommands::Rename { old, new, force } => {
+            let mut config = Config::load()?;
+            config.rename_profile(&old, &new, force)?;
+            config.save()?;
+            out!(ctx.reporter, "Profile '{}' renamed to '{}'.", old, new);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_layout(ctx: &Context, command: LayoutCommands) -> anyhow::Result<()> {
+    match command {
+        LayoutCommands::Save { name } => {
+            let layout = layout::current_layout()?;
+            let count = layout.displays.len();
+            let mut config = Config::load()?;
+            config.save_layout(name.clone(), layout);
+            config.save()?;
+            out!(
+                ctx.reporter,
+                "Layout '{}' saved ({} display(s)).",
+                name,
+                count
+            );
+        }
+        LayoutCommands::Apply { name, also_profile } => {
+            let config = Config::load()?;
+            let Some(saved) = config.get_layout(&name) else {
+                anyhow::bail!("Layout '{}' not found", name);
+            };
+            if ctx.dry_run {
+                let mut devices: Vec<_> = saved.displays.iter().collect();
+                devices.sort_by(|a, b| a.0.cmp(b.0));
+                for (device, mode) in devices {
+                    out!(ctx.reporter, "[DRY RUN] Would set {} to {}", device, mode);
+                }
+                if let Some(profile) = also_profile {
+                    out!(
+                        ctx.reporter,
+                        "[DRY RUN] Would then load profile '{}'",
+                        profile
+                    );
+                }
+                return Ok(());
+            }
+            layout::apply_layout(saved)?;
+            out!(ctx.reporter, "Layout '{}' applied.", name);
+            if let Some(profile) = also_profile {
+                load_named_profile(&profile, &ctx.ddc_options)?;
+                out!(ctx.reporter, "Profile '{}' loaded.", profile);
+            }
+        }
+        LayoutCommands::List => {
+            let config = Config::load()?;
+            for name in config.layouts.keys() {
+                out!(ctx.reporter, "{}", name);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_schedule(ctx: &Context, command: ScheduleCommands) -> anyhow::Result<()> {
+    match command {
+        ScheduleCommands::Add {
+            profile,
+            time,
+            days,
+        } => {
+            let time = parse_schedule_time(&time)?;
+            let days = match days {
+                Some(list) => parse_schedule_days(&list)?,
+                None => Vec::new(),
+            };
+            let mut config = Config::load()?;
+            let id = config.add_schedule(profile.clone(), time, days)?;
+            config.save()?;
+            out!(
+                ctx.reporter,
+                "Schedule {} added: profile '{}' at {}.",
+                id,
+                profile,
+                time.format("%H:%M")
+            );
+        }
+        ScheduleCommands::List => {
+            let config = Config::load()?;
+            if config.schedules.is_empty() {
+                out!(ctx.reporter, "No schedules.");
+            }
+            for entry in &config.schedules {
+                out!(
+                    ctx.reporter,
+                    "{:>3}  {}  {:<27}  {}",
+                    entry.id,
+                    entry.time.format("%H:%M"),
+                    entry.days_label(),
+                    entry.profile
+                );
+            }
+        }
+        ScheduleCommands::Remove { id } => {
+            let mut config = Config::load()?;
+            if !config.remove_schedule(id) {
+                anyhow::bail!("Schedule {} not found", id);
+            }
+            config.save()?;
+            out!(ctx.reporter, "Schedule {} removed.", id);
+        }
+        ScheduleCommands::Run => {
+            let now = Local::now().naive_local();
+            let config = Config::load()?;
+            let due = config.due_schedules(now);
+            if due.is_empty() {
+                ctx.reporter
+                    .verbose(format_args!("No schedules due at {}", now.format("%H:%M")));
+            }
+            let mut failed = 0;
+            for entry in due {
+                if ctx.dry_run {
+                    out!(
+                        ctx.reporter,
+                        "[DRY RUN] Would load profile '{}' (schedule {})",
+                        entry.profile,
+                        entry.id
                     );
+                    continue;
+                }
+                match load_named_profile(&entry.profile, &ctx.ddc_options) {
+                    Ok(()) => out!(
+                        ctx.reporter,
+                        "Schedule {}: profile '{}' loaded.",
+                        entry.id,
+                        entry.profile
+                    ),
+                    Err(e) => {
+                        failed += 1;
+                        eprintln!(
+                            "Schedule {}: failed to load profile '{}': {}",
+                            entry.id, entry.profile, e
+                        );
+                    }
                 }
             }
+            if failed > 0 {
+                anyhow::bail!("{} scheduled profile(s) failed to load", failed);
+            }
         }
-        Commands::Capabilities { display } => {
-            let mut displays = backend::enumerate()?;
-            let target = select_display_mut(&mut displays, display)?;
-            let caps_str = target.capabilities()?;
-            let caps = dispman::capabilities::Capabilities::parse(&caps_str);
-            println!("{}", caps);
+    }
+    Ok(())
+}
+
+fn cmd_inspect(ctx: &Context, args: InspectArgs) -> anyhow::Result<()> {
+    let InspectArgs {
+        display,
+        all_codes,
+        feature,
+        timeout_ms,
+        json,
+        output,
+        timing,
+        changes_since,
+        snapshot,
+        batch,
+    } = args;
+    if let Some(path) = snapshot {
+        let mut displays = enumerate(&ctx.ddc_options)?;
+        let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+        let snapshot = target.get_all_vcp_features()?;
+        fs::write(&path, serde_json::to_string_pretty(&snapshot)?)?;
+        out!(
+            ctx.reporter,
+            "Saved {} feature(s) of display {} to {} ({} could not be read)",
+            snapshot.values.len(),
+            target.id,
+            path.display(),
+            snapshot.failed_codes.len()
+        );
+        return Ok(());
+    }
+    if let Some(name) = changes_since {
+        let config = Config::load()?;
+        let Some(profile) = config.resolve_profile(&name)? else {
+            return Err(DisplayError::config(format!("Profile '{}' not found", name)).into());
+        };
+        let mut displays = enumerate(&ctx.ddc_options)?;
+        let profile = resolve_aliases(&config, &profile, &displays);
+        let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+        let entries = profile::diff_against_live(&profile, target);
+        if entries.is_empty() {
+            anyhow::bail!(
+                "Profile '{}' has no settings for display {} ({})",
+                name,
+                target.id,
+                target.stable_id()
+            );
         }
-        Commands::Get { feature, display } => {
-            let mut displays = backend::enumerate()?;
-            let target = select_display_mut(&mut displays, display)?;
-            let code = parse_feature(&feature)?;
-            let value = target.get_vcp_feature(code)?;
-            println!(
-                "Display {}: {} = {} (0x{:X})",
-                target.id, feature, value, value
+        out!(
+            ctx.reporter,
+            "Display {} compared with profile '{}':",
+            target.id,
+            name
+        );
+        for entry in &entries {
+            let current = match entry.current {
+                Some(value) => profile::format_value(entry.code, value),
+                None => "unreadable".to_string(),
+            };
+            out!(
+                ctx.reporter,
+                "  0x{:02X} {}: profile={}, current={} [{}]",
+                entry.code,
+                VcpFeature::from_code(entry.code).name(),
+                profile::format_value(entry.code, entry.saved),
+                current,
+                if entry.matches() { "OK" } else { "CHANGED" }
             );
         }
-        Commands::Set {
+        let matching = entries.iter().filter(|e| e.matches()).count();
+        out!(
+            ctx.reporter,
+            "Score: {}% ({} of {} settings match)",
+            matching * 100 / entries.len(),
+            matching,
+            entries.len()
+        );
+        return Ok(());
+    }
+    let format = if json {
+        OutputFormat::Json
+    } else {
+        ctx.output_format
+    };
+    if output.is_some() && format == OutputFormat::Text {
+        anyhow::bail!("--output needs --json or a machine-readable --output-format");
+    }
+    let codes = match feature {
+        Some(list) => parse_feature_list(&list)?,
+        None => DEFAULT_INSPECT_CODES.to_vec(),
+    };
+    let mut displays = enumerate(&ctx.ddc_options)?;
+    if ctx.all_displays && !all_codes {
+        check_all_displays_conflict(display, &ctx.targeting)?;
+        let out = compare_features(&mut displays, &codes, format)?;
+        match output {
+            Some(path) => fs::write(path, out + "\n")?,
+            None => out!(ctx.reporter, "{}", out),
+        }
+        return Ok(());
+    }
+    let target = select_display_mut(&mut displays, display, &ctx.targeting)?;
+
+    let mut found = Vec::new();
+    if all_codes {
+        let timeout = timeout_ms.map(Duration::from_millis);
+        for code in 0x00..=0xFFu8 {
+            let result = match timeout {
+                Some(timeout) => target.get_vcp_feature_timeout(code, timeout),
+                None => target.get_vcp_feature(code),
+            };
+            if let Ok(val) = result {
+                found.push((code, val));
+            }
+        }
+    } else if format != OutputFormat::Text {
+        let batched = batch.then(|| target.get_vcp_feature_batch(&codes).unwrap_or_default());
+        for &code in &codes {
+            let value = match &batched {
+                Some(values) => values.get(&code).copied(),
+                None => target.get_vcp_feature(code).ok(),
+            };
+            if let Some(val) = value {
+                found.push((code, val));
+            }
+        }
+    }
+
+    let readings: Vec<FeatureReading> = found
+        .iter()
+        .map(|(code, val)| FeatureReading::new(*code, val))
+        .collect();
+    if format != OutputFormat::Text {
+        let out = formatter(format, ctx.all_displays).format_vcp_values(&readings)?;
+        match output {
+            Some(path) => fs::write(path, out + "\n")?,
+            None => out!(ctx.reporter, "{}", out),
+        }
+        return Ok(());
+    }
+
+    out!(
+        ctx.reporter,
+        "Inspecting Display {}: {}",
+        target.id,
+        target.name()
+    );
+    if let Some(version) = target
+        .capabilities_parsed()
+        .ok()
+        .and_then(|caps| caps.mccs_version_parsed())
+    {
+        out!(
+            ctx.reporter,
+            "MCCS {} (supports: {})",
+            version,
+            version.supported_features()
+        );
+    }
+    if all_codes {
+        print_output(
+            &ctx.reporter,
+            &formatter(format, ctx.all_displays).format_vcp_values(&readings)?,
+        );
+    } else {
+        let batched = batch.then(|| target.get_vcp_feature_batch(&codes).unwrap_or_default());
+        for code in codes {
+            let name = VcpFeature::from_code(code).name();
+            let value = match &batched {
+                Some(values) => values.get(&code).copied(),
+                None => target.get_vcp_feature(code).ok(),
+            };
+            match value {
+                Some(val) if code == 0xD6 => out!(
+                    ctx.reporter,
+                    "{}: {} [{}]",
+                    name,
+                    format_inspect_value(&val),
+                    PowerMode::from_value(val.current as u16)
+                ),
+                Some(val) => out!(ctx.reporter, "{}: {}", name, format_inspect_value(&val)),
+                None => out!(ctx.reporter, "{}: Not supported", name),
+            }
+        }
+    }
+    if timing {
+        match target.timing_report() {
+            Ok(report) => out!(ctx.reporter, "Timing: {}", report),
+            Err(e) => out!(ctx.reporter, "Timing: Not available ({})", e),
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites a `dispman brightness [VALUE]`-style shortcut as the equivalent
+/// `get` or `set` command.
+fn expand_shortcut(
+    feature: &str,
+    value: Option<u32>,
+    display: Option<usize>,
+    percent: bool,
+) -> anyhow::Result<Commands> {
+    let feature = feature.to_string();
+    let Some(value) = value else {
+        return Ok(Commands::Get(GetArgs {
             feature,
-            value,
             display,
-        } => {
-            let mut displays = backend::enumerate()?;
-            let target = select_display_mut(&mut displays, display)?;
-            let code = parse_feature(&feature)?;
-            target.set_vcp_feature(code, value)?;
-            println!("Set {} to {}", feature, value);
-        }
-        Commands::Profile { command } => match command {
-            ProfileCommands::Save { name } => {
-                let mut displays = backend::enumerate()?;
-                let mut config = Config::load()?;
-                let mut settings = HashMap::new();
+            percent,
+            check_support: false,
+            expect: None,
+            expect_percent: None,
+            tolerance: 0,
+        }));
+    };
+    if percent && value > 100 {
+        anyhow::bail!("Percentage must be between 0 and 100, got {}", value);
+    }
+    Ok(Commands::Set(SetArgs {
+        feature,
+        value: (!percent).then(|| value.to_string()),
+        display,
+        percent: percent.then_some(value),
+        increment: None,
+        decrement: None,
+        verify: false,
+        verify_tolerance: 0,
+        verify_delay_ms: backend::DEFAULT_VERIFY_DELAY_MS,
+        transition_ms: None,
+        steps: None,
+        validate: false,
+        check_support: false,
+    }))
+}
 
-                for d in displays.iter_mut() {
-                    let mut display_settings = Vec::new();
-                    for code in [0x10, 0x12, 0x60, 0x62] {
-                        if let Ok(val) = d.get_vcp_feature(code) {
-                            display_settings.push((code, val));
-                        }
-                    }
-                    settings.insert(d.stable_id().to_string(), display_settings);
-                }
+/// Prints a warning if `display` does not advertise `code` in its
+/// capabilities string, or if the capabilities cannot be read.
+fn warn_if_unsupported(display: &mut backend::Display, code: u8) {
+    match display.supports_feature(VcpFeature::from_code(code)) {
+        Ok(true) => {}
+        Ok(false) => eprintln!(
+            "Warning: display {} does not list VCP 0x{:02X} in its capabilities",
+            display.id, code
+        ),
+        Err(e) => eprintln!(
+            "Warning: could not check support for VCP 0x{:02X} on display {}: {}",
+            code, display.id, e
+        ),
+    }
+}
 
-                config.save_profile(name.clone(), Profile { settings });
-                config.save()?;
-                println!("Profile '{}' saved.", name);
-            }
-            ProfileCommands::Load { name } => {
-                let config = Config::load()?;
-                if let Some(profile) = config.get_profile(&name).cloned() {
-                    let mut displays = backend::enumerate()?;
-                    for d in displays.iter_mut() {
-                        if let Some(settings) = profile.settings.get(d.stable_id()) {
-                            for (code, value) in settings {
-                                if let Err(e) = d.set_vcp_feature(*code, *value) {
-                                    eprintln!(
-                                        "Failed to set feature 0x{:X} on display {}: {}",
-                                        code, d.id, e
-                                    );
-                                }
-                            }
-                        }
+/// How long `daemon` waits after a resume before talking to the monitors,
+/// which often ignore DDC/CI until they have finished waking up.
+const RESUME_SETTLE_DELAY: Duration = Duration::from_secs(3);
+
+/// Loads the `[daemon]` profile for each power event until the process is
+/// stopped.
+fn run_daemon(reporter: Reporter, ddc_options: DdcOptions) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    if config.daemon.is_empty() {
+        anyhow::bail!(
+            "No daemon profiles configured; set wake_profile and/or suspend_profile under \
+             [daemon] in {}",
+            Config::config_path()?.display()
+        );
+    }
+    let daemon = config.daemon;
+    out!(reporter, "Waiting for power events (Ctrl+C to stop)...");
+    power::watch_power_events(move |event| {
+        let name = match event {
+            PowerEvent::Resume => daemon.wake_profile.as_deref(),
+            PowerEvent::Suspend => daemon.suspend_profile.as_deref(),
+        };
+        let Some(name) = name else { return };
+        if event == PowerEvent::Resume {
+            thread::sleep(RESUME_SETTLE_DELAY);
+        }
+        match load_named_profile(name, &ddc_options) {
+            Ok(()) => out!(reporter, "{:?}: profile '{}' loaded.", event, name),
+            Err(e) => eprintln!("{:?}: failed to load profile '{}': {}", event, name, e),
+        }
+    })?;
+    Ok(())
+}
+
+/// Re-reads the config and applies profile or chain `name` to every
+/// connected display it covers. Every value is attempted; failures are
+/// reported on stderr and the first one is returned.
+fn load_named_profile(name: &str, ddc_options: &DdcOptions) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let Some(profile) = config.resolve_profile(name)? else {
+        return Err(DisplayError::config(format!("Profile '{}' not found", name)).into());
+    };
+    let mut displays = enumerate(ddc_options)?;
+    let profile = resolve_aliases(&config, &profile, &displays);
+    let mut result = profile::apply(&profile, &mut displays, OnFailurePolicy::Continue);
+    for failure in &result.failed {
+        eprintln!("{}", failure);
+    }
+    if result.failed.is_empty() {
+        return Ok(());
+    }
+    let first = result.failed.swap_remove(0);
+    Err(anyhow::Error::new(first.error)
+        .context(format!("Profile '{}' was not fully applied", name)))
+}
+
+/// The settings of `profile` that should be written to `displays`: keyed by
+/// stable ID, so settings saved under an `[aliases]` label reach the monitor
+/// the alias points at, and without the profile's excluded codes. The write
+/// delay falls back to `default_delay_between_sets_ms`.
+fn resolve_aliases(config: &Config, profile: &Profile, displays: &[backend::Display]) -> Profile {
+    let mut settings = HashMap::new();
+    for d in displays {
+        let values = config
+            .profile_settings(profile, d.stable_id())
+            .or_else(|| config.profile_settings(profile, d.name()));
+        if let Some(values) = values {
+            let values = values
+                .iter()
+                .copied()
+                .filter(|(code, _)| !profile.excluded_codes.contains(code))
+                .collect();
+            settings.insert(d.stable_id().to_string(), values);
+        }
+    }
+    Profile {
+        settings,
+        created_at: profile.created_at,
+        excluded_codes: profile.excluded_codes.clone(),
+        delay_between_sets_ms: profile
+            .delay_between_sets_ms
+            .or(config.settings.default_delay_between_sets_ms),
+        hostname: profile.hostname.clone(),
+    }
+}
+
+/// The `[aliases]` label for a display, matched against its stable ID and
+/// then its name.
+fn display_alias<'a>(config: &'a Config, display: &'a backend::Display) -> Option<&'a str> {
+    [display.stable_id(), display.name()]
+        .into_iter()
+        .map(|raw| (raw, config.resolve_display_name(raw)))
+        .find(|(raw, resolved)| raw != resolved)
+        .map(|(_, alias)| alias)
+}
+
+/// Writes `profile` to all `displays` at once. Each display's failure is
+/// reported on stderr; the first one is returned once every display is done.
+#[cfg(feature = "async")]
+fn load_profile_concurrent(
+    name: &str,
+    displays: Vec<backend::Display>,
+    profile: &Profile,
+) -> anyhow::Result<()> {
+    let ids: Vec<usize> = displays.iter().map(|d| d.id).collect();
+    let runtime = tokio::runtime::Runtime::new()?;
+    let delay = profile.delay_between_sets_ms.map(Duration::from_millis);
+    let results = runtime.block_on(backend::set_profile_concurrent(
+        displays,
+        &profile.settings,
+        delay,
+    ));
+    let mut first_error = None;
+    for (id, result) in ids.into_iter().zip(results) {
+        if let Err(e) = result {
+            eprintln!("Failed to apply profile to display {}: {}", id, e);
+            first_error.get_or_insert(e);
+        }
+    }
+    match first_error {
+        Some(e) => {
+            Err(anyhow::Error::new(e).context(format!("Profile '{}' was not fully applied", name)))
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "async"))]
+fn load_profile_concurrent(
+    _name: &str,
+    _displays: Vec<backend::Display>,
+    _profile: &Profile,
+) -> anyhow::Result<()> {
+    anyhow::bail!("--concurrent requires dispman to be built with the `async` feature")
+}
+
+/// Enumerates displays and applies the command-line DDC/CI options to each.
+fn enumerate(options: &DdcOptions) -> Result<Vec<backend::Display>, DisplayError> {
+    if options.retry_on_empty {
+        return backend::enumerate_with_retry(
+            backend::ENUMERATE_RETRY_ATTEMPTS,
+            backend::ENUMERATE_RETRY_DELAY_MS,
+            options,
+        );
+    }
+    let mut displays = backend::enumerate()?;
+    for d in displays.iter_mut() {
+        d.set_ddc_options(*options);
+    }
+    Ok(displays)
+}
+
+/// Pause between enumeration attempts in `detect --wait`.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Retries enumeration until at least `min_displays` displays are found or
+/// `timeout` expires. Errors other than "no monitors" are returned at once.
+fn enumerate_with_wait(
+    options: &DdcOptions,
+    timeout: Duration,
+    min_displays: usize,
+) -> Result<Vec<backend::Display>, DisplayError> {
+    let start = Instant::now();
+    loop {
+        match enumerate(options) {
+            Ok(displays) if displays.len() >= min_displays => return Ok(displays),
+            Ok(_) | Err(DisplayError::MonitorNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        if start.elapsed() >= timeout {
+            return Err(DisplayError::MonitorNotFound(format!(
+                "fewer than {} display(s) found after waiting {}ms",
+                min_displays,
+                timeout.as_millis()
+            )));
+        }
+        thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
+
+/// Retries enumeration until every display answers a DDC/CI ping, or at
+/// least `min_ready` of them do, or `timeout` expires. Each display that is
+/// not ready yet is announced once on stderr.
+fn enumerate_until_ready(
+    options: &DdcOptions,
+    timeout: Duration,
+    min_ready: Option<usize>,
+) -> Result<Vec<backend::Display>, DisplayError> {
+    let start = Instant::now();
+    let mut announced = Vec::new();
+    loop {
+        match enumerate(options) {
+            Ok(mut displays) if !displays.is_empty() => {
+                let mut ready = 0;
+                for d in displays.iter_mut() {
+                    if d.is_ddc_capable() {
+                        ready += 1;
+                    } else if !announced.contains(&d.id) {
+                        eprintln!("Waiting for DDC on Display {} ({})…", d.id, d.name());
+                        announced.push(d.id);
                     }
-                    println!("Profile '{}' loaded.", name);
-                } else {
-                    eprintln!("Profile '{}' not found.", name);
                 }
-            }
-            ProfileCommands::List => {
-                let config = Config::load()?;
-                for name in config.profiles.keys() {
-                    println!("{}", name);
+                if ready >= min_ready.unwrap_or(displays.len()) {
+                    return Ok(displays);
                 }
             }
-        },
-        Commands::Inspect { display } => {
-            let mut displays = backend::enumerate()?;
-            let target = select_display_mut(&mut displays, display)?;
-            println!("Inspecting Display {}: {}", target.id, target.name());
+            Ok(_) | Err(DisplayError::MonitorNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        if start.elapsed() >= timeout {
+            return Err(DisplayError::MonitorNotFound(format!(
+                "displays not ready for DDC/CI after waiting {}ms",
+                timeout.as_millis()
+            )));
+        }
+        thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
 
-            let codes = [
-                (0x10u8, "Brightness"),
-                (0x12, "Contrast"),
-                (0x60, "Input Source"),
-                (0x62, "Volume"),
-                (0xD6, "Power Mode"),
-            ];
+/// Continuous features read as `current/max`; non-continuous ones are
+/// enumerated values and read best in hex.
+fn format_inspect_value(value: &VcpValue) -> String {
+    if value.is_continuous {
+        format!("{}/{}", value.current, value.max)
+    } else {
+        format!("0x{:02X}", value.current)
+    }
+}
 
-            for (code, name) in codes {
-                match target.get_vcp_feature(code) {
-                    Ok(val) => println!("{}: {} (0x{:X})", name, val, val),
-                    Err(_) => println!("{}: Not supported", name),
+/// Features `inspect` reads when neither --all-codes nor --feature is given.
+const DEFAULT_INSPECT_CODES: [u8; 5] = [0x10, 0x12, 0x60, 0x62, 0xD6];
+
+/// Reads `codes` from every display for `inspect --all-displays`. Text output
+/// is a table with a row per feature and a column per display; JSON and CSV
+/// list the readings that succeeded, as `get --all-displays` does.
+fn compare_features(
+    displays: &mut [backend::Display],
+    codes: &[u8],
+    format: OutputFormat,
+) -> Result<String, DisplayError> {
+    if format != OutputFormat::Text {
+        let mut readings = Vec::new();
+        for &code in codes {
+            let name = VcpFeature::from_code(code).name();
+            for d in displays.iter_mut() {
+                if let Ok(value) = d.get_vcp_feature(code) {
+                    readings.push(vcp_reading(d, name, value, false)?);
                 }
             }
         }
+        return formatter(format, true).format_vcp_value(&readings);
     }
 
-    Ok(())
+    let headers: Vec<String> = displays
+        .iter()
+        .map(|d| format!("Display {}", d.id))
+        .collect();
+    let rows: Vec<(u8, &str, Vec<String>)> = codes
+        .iter()
+        .map(|&code| {
+            let values = displays
+                .iter_mut()
+                .map(|d| match d.get_vcp_feature(code) {
+                    Ok(value) => format_inspect_value(&value),
+                    Err(_) => "-".to_string(),
+                })
+                .collect();
+            (code, VcpFeature::from_code(code).name(), values)
+        })
+        .collect();
+    let name_width = rows
+        .iter()
+        .map(|(_, name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            rows.iter()
+                .map(|(_, _, v)| v[i].len())
+                .max()
+                .unwrap_or(0)
+                .max(h.len())
+        })
+        .collect();
+
+    let mut out = format!("Code  {:<name_width$}", "Name");
+    for (header, width) in headers.iter().zip(&widths) {
+        out.push_str(&format!("  {:>width$}", header));
+    }
+    for (code, name, values) in &rows {
+        out.push_str(&format!("\n0x{:02X}  {:<name_width$}", code, name));
+        for (value, width) in values.iter().zip(&widths) {
+            out.push_str(&format!("  {:>width$}", value));
+        }
+    }
+    Ok(out)
 }
 
-fn select_display_mut(
-    displays: &mut [backend::Display],
+/// State derived from the global flags and the `[settings]` table, shared by
+/// the `cmd_*` handlers.
+struct Context {
+    ddc_options: DdcOptions,
+    reporter: Reporter,
+    formatter: Box<dyn OutputFormatter>,
+    targeting: Targeting,
+    output_format: OutputFormat,
+    all_displays: bool,
+    dry_run: bool,
+    verbose: bool,
+}
+
+/// Global options that pick a display by something other than its index.
+struct Targeting {
+    display_name: Option<String>,
+    first_match: bool,
+    /// Display used when none is given, with where the setting came from.
+    default_display: Option<(usize, &'static str)>,
+}
+
+fn select_display_mut<'a>(
+    displays: &'a mut [backend::Display],
     id: Option<usize>,
-) -> Result<&mut backend::Display, DisplayError> {
+    targeting: &Targeting,
+) -> Result<&'a mut backend::Display, DisplayError> {
     if displays.is_empty() {
         return Err(DisplayError::MonitorNotFound(
             "No displays found".to_string(),
         ));
     }
 
-    match id {
-        Some(id) => displays
+    if let Some(pattern) = &targeting.display_name {
+        if id.is_some() {
+            return Err(DisplayError::config(
+                "--display-name cannot be combined with --display".to_string(),
+            ));
+        }
+        // Names like "Generic PnP Monitor" say little, so fall back to the
+        // model from the capabilities string when no name matches.
+        let by_name = displays.iter().filter(|d| d.name_matches(pattern)).count();
+        let count = if by_name > 0 {
+            by_name
+        } else {
+            displays
+                .iter_mut()
+                .filter_map(|d| d.model_matches(pattern).ok())
+                .filter(|&matched| matched)
+                .count()
+        };
+        if count > 1 && !targeting.first_match {
+            return Err(DisplayError::config(format!(
+                "{} displays match '{}'; narrow the pattern or pass --first-match",
+                count, pattern
+            )));
+        }
+        let found = if by_name > 0 {
+            backend::find_by_name(displays, pattern)
+        } else {
+            backend::find_by_model(displays, pattern)
+        };
+        return found.ok_or_else(|| {
+            DisplayError::MonitorNotFound(format!(
+                "No display name or model contains '{}'",
+                pattern
+            ))
+        });
+    }
+
+    match (id, targeting.default_display) {
+        (Some(id), _) => displays
             .iter_mut()
             .find(|d| d.id == id)
             .ok_or_else(|| DisplayError::MonitorNotFound(format!("Display {} not found", id))),
-        None => Ok(&mut displays[0]),
+        (None, Some((id, source))) => displays.iter_mut().find(|d| d.id == id).ok_or_else(|| {
+            DisplayError::MonitorNotFound(format!("Display {} ({}) not found", id, source))
+        }),
+        (None, None) => Ok(&mut displays[0]),
     }
 }
 
-fn parse_feature(feature: &str) -> Result<u8, DisplayError> {
-    match feature.to_lowercase().as_str() {
-        "brightness" => Ok(0x10),
-        "contrast" => Ok(0x12),
-        "volume" => Ok(0x62),
-        "input" => Ok(0x60),
-        "power" => Ok(0xD6),
-        s => {
-            if let Some(hex) = s.strip_prefix("0x") {
-                u8::from_str_radix(hex, 16).map_err(|_| {
-                    DisplayError::FeatureNotSupported(format!("Invalid hex code: {}", s))
-                })
-            } else {
-                s.parse::<u8>().map_err(|_| {
-                    DisplayError::FeatureNotSupported(format!("Unknown feature: {}", s))
-                })
+/// Prints `prompt` with a `[Y/n]` hint to stderr and reads the answer.
+/// An empty answer (or end of input) counts as yes; anything other than
+/// y/yes/n/no asks again.
+fn ask_yes_no(prompt: &str) -> std::io::Result<bool> {
+    loop {
+        eprint!("{} [Y/n] ", prompt);
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer)? == 0 {
+            return Ok(true);
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "" | "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => {}
+        }
+    }
+}
+
+/// Prints `prompt` to stderr and returns whether the user typed `yes`.
+fn confirm_on_stdin(prompt: &str) -> std::io::Result<bool> {
+    eprint!("{} ", prompt);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("yes"))
+}
+
+fn check_all_displays_conflict(
+    display: Option<usize>,
+    targeting: &Targeting,
+) -> Result<(), DisplayError> {
+    if display.is_some() || targeting.display_name.is_some() {
+        return Err(DisplayError::config(
+            "--all-displays cannot be combined with --display or --display-name".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Prints a summary of per-display failures collected by an `--all-displays`
+/// run and returns an error if any display failed.
+fn report_failures(failures: &[(usize, DisplayError)], total: usize) -> anyhow::Result<()> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    for (id, e) in failures {
+        eprintln!("Display {}: {}", id, e);
+    }
+    anyhow::bail!("{} of {} displays failed", failures.len(), total)
+}
+
+/// Flags that change how `set` writes a value.
+#[derive(Clone, Copy)]
+struct SetOptions {
+    /// Read the value back and check it.
+    verify: Option<Verify>,
+    /// Fade to the value instead of writing it at once.
+    transition: Option<Transition>,
+    /// Refuse values above the maximum the monitor reports.
+    validate: bool,
+}
+
+/// Options for `set --verify`.
+#[derive(Clone, Copy)]
+struct Verify {
+    /// Largest accepted difference between the written and read-back value.
+    tolerance: u32,
+    /// Pause between the write and the read-back.
+    delay: Duration,
+}
+
+/// Resolves and writes a `set` request according to `options`, returning the
+/// value now set and the maximum, if one was read. With `verify`, the value
+/// is the one read back.
+fn apply_set(
+    display: &mut backend::Display,
+    code: u8,
+    feature: &str,
+    request: SetRequest,
+    options: SetOptions,
+) -> Result<(u32, Option<u32>), DisplayError> {
+    let (value, max) = resolve_set_value(display, code, feature, request)?;
+    if options.validate {
+        display.validate_vcp_value(code, value)?;
+    }
+    let value = match (options.transition, options.verify) {
+        (None, Some(verify)) => {
+            display.set_vcp_feature_checked(code, value, verify.tolerance, verify.delay)?
+        }
+        (None, None) => {
+            display.set_vcp_feature(code, value)?;
+            value
+        }
+        (Some(transition), verify) => {
+            transition_to(display, code, feature, value, transition)?;
+            match verify {
+                Some(verify) => {
+                    thread::sleep(verify.delay);
+                    display.verify_vcp_value(code, value, verify.tolerance)?
+                }
+                None => value,
+            }
+        }
+    };
+    Ok((value, max))
+}
+
+/// Shortest interval between writes during a transition, so slow monitors
+/// are not flooded with DDC/CI commands.
+const MIN_TRANSITION_STEP_MS: u64 = 50;
+
+/// Options for `set --transition-ms`.
+#[derive(Clone, Copy)]
+struct Transition {
+    duration_ms: u64,
+    steps: Option<u32>,
+    verbose: bool,
+}
+
+/// Moves `code` linearly from its current value to `target` over the
+/// transition's duration. The last write is always exactly `target`.
+fn transition_to(
+    display: &mut backend::Display,
+    code: u8,
+    feature: &str,
+    target: u32,
+    transition: Transition,
+) -> Result<(), DisplayError> {
+    let start = display.get_vcp_feature(code)?;
+    if !start.is_continuous {
+        return Err(DisplayError::FeatureNotSupported(format!(
+            "{} is not a continuous feature and cannot be transitioned",
+            feature
+        )));
+    }
+
+    let from = start.current;
+    let distance = from.abs_diff(target).max(1);
+    let steps = transition
+        .steps
+        .unwrap_or_else(|| {
+            u32::try_from(transition.duration_ms / MIN_TRANSITION_STEP_MS).unwrap_or(u32::MAX)
+        })
+        .clamp(1, distance);
+    let delay = Duration::from_millis(transition.duration_ms / u64::from(steps));
+    if transition.verbose {
+        eprintln!(
+            "Transitioning {} {} -> {} ({} steps, {}ms)",
+            feature, from, target, steps, transition.duration_ms
+        );
+    }
+
+    for step in 1..=steps {
+        let delta = (i64::from(target) - i64::from(from)) * i64::from(step) / i64::from(steps);
+        display.set_vcp_feature(code, (i64::from(from) + delta) as u32)?;
+        if step < steps {
+            thread::sleep(delay);
+        }
+    }
+    Ok(())
+}
+
+/// How the `set` command derives the value it writes.
+#[derive(Clone, Copy)]
+enum SetRequest {
+    Absolute(u32),
+    Percent(u32),
+    Relative(i64),
+}
+
+impl std::fmt::Display for SetRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetRequest::Absolute(value) => write!(f, "{}", value),
+            SetRequest::Percent(pct) => write!(f, "{}%", pct),
+            SetRequest::Relative(delta) => write!(f, "current {:+}", delta),
+        }
+    }
+}
+
+/// Polls the capabilities string of `display` until Ctrl+C, printing each
+/// change. Truncated replies are skipped rather than reported as changes.
+fn watch_capabilities(
+    reporter: &Reporter,
+    display: &mut backend::Display,
+    interval: Duration,
+) -> anyhow::Result<()> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = Arc::clone(&running);
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))?;
+
+    let mut previous = display.capabilities()?;
+    if !capabilities::is_balanced(&previous) {
+        return Err(DisplayError::CapabilitiesIncomplete(previous).into());
+    }
+    out!(
+        reporter,
+        "Watching capabilities of Display {}: {}",
+        display.id,
+        display.name()
+    );
+    while running.load(Ordering::SeqCst) {
+        thread::sleep(interval);
+        match display.capabilities() {
+            Ok(raw) if !capabilities::is_balanced(&raw) => {
+                reporter.verbose(format_args!("Ignoring truncated capabilities reply"));
+            }
+            Ok(raw) => {
+                if let Some(change) = capabilities::capability_diff(&previous, &raw) {
+                    let time = Local::now().format("%H:%M:%S");
+                    for line in change.to_string().lines() {
+                        out!(reporter, "[{}] Capabilities changed: {}", time, line);
+                    }
+                }
+                previous = raw;
+            }
+            Err(e) => eprintln!("Read failed: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Prints the power mode of `display`, or changes it to `mode` and reports
+/// the mode it replaced.
+fn run_power(
+    reporter: &Reporter,
+    display: &mut backend::Display,
+    mode: Option<PowerMode>,
+    dry_run: bool,
+) -> Result<(), DisplayError> {
+    let Some(mode) = mode else {
+        let current = display.get_power_mode()?;
+        out!(reporter, "Display {}: {}", display.id, current);
+        return Ok(());
+    };
+    if dry_run {
+        out!(
+            reporter,
+            "[DRY RUN] Would set power mode to {} on Display {}: {}",
+            mode,
+            display.id,
+            display.name()
+        );
+        return Ok(());
+    }
+    // A monitor in standby may not answer the read; that must not stop
+    // `power on` from waking it.
+    let previous = match display.get_power_mode() {
+        Ok(previous) => previous.to_string(),
+        Err(_) => "unknown".to_string(),
+    };
+    display.set_power_mode(mode)?;
+    out!(
+        reporter,
+        "Display {}: power mode set to {} (was {})",
+        display.id,
+        mode,
+        previous
+    );
+    Ok(())
+}
+
+/// Prints the write `set` would perform, without talking to the display.
+fn print_dry_run_set(
+    reporter: &Reporter,
+    display: &backend::Display,
+    code: u8,
+    request: SetRequest,
+) {
+    out!(
+        reporter,
+        "[DRY RUN] Would set 0x{:02X} ({}) to {} on Display {}: {}",
+        code,
+        VcpFeature::from_code(code).name(),
+        request,
+        display.id,
+        display.name()
+    );
+}
+
+/// Converts a `set` request into the raw value to write.
+///
+/// Returns the value together with the maximum the display reported, if
+/// the request had to read it. Percentages are scaled against that maximum
+/// and rounded to the nearest step; a feature reporting a maximum of zero
+/// cannot be set by percentage. Relative changes are applied to the current
+/// value and clamped to `0..=max` using the maximum from the same read (only
+/// the lower bound applies when the display reports a maximum of zero).
+fn resolve_set_value(
+    display: &mut backend::Display,
+    code: u8,
+    feature: &str,
+    request: SetRequest,
+) -> Result<(u32, Option<u32>), DisplayError> {
+    match request {
+        SetRequest::Absolute(value) => Ok((value, None)),
+        SetRequest::Percent(pct) => {
+            let max = display.get_vcp_feature(code)?.max;
+            if max == 0 {
+                return Err(DisplayError::FeatureNotSupported(format!(
+                    "{} reports a maximum of 0 and cannot be set by percentage",
+                    feature
+                )));
             }
+            let target = (u64::from(pct) * u64::from(max) + 50) / 100;
+            Ok((target as u32, Some(max)))
+        }
+        SetRequest::Relative(delta) => {
+            let current = display.get_vcp_feature(code)?;
+            let max = match current.max {
+                0 => u32::MAX,
+                max => max,
+            };
+            let target = (i64::from(current.current) + delta).clamp(0, i64::from(max));
+            Ok((target as u32, Some(current.max)))
+        }
+    }
+}
+
+/// Formats the outcome of `set` for display, e.g. `80% (64/80)` for a
+/// percentage request.
+fn describe_set(request: SetRequest, value: u32, max: Option<u32>) -> String {
+    match (request, max) {
+        (SetRequest::Percent(pct), Some(max)) => format!("{}% ({}/{})", pct, value, max),
+        _ => value.to_string(),
+    }
+}
+
+/// Builds the `get` result for one display. With `percent`, features that
+/// report a maximum of zero are an error.
+fn vcp_reading(
+    display: &backend::Display,
+    feature: &str,
+    value: VcpValue,
+    percent: bool,
+) -> Result<VcpReading, DisplayError> {
+    let percent = if percent {
+        if value.max == 0 {
+            return Err(DisplayError::FeatureNotSupported(format!(
+                "{} reports a maximum of 0 and has no percentage",
+                feature
+            )));
+        }
+        let pct =
+            (u64::from(value.current) * 100 + u64::from(value.max) / 2) / u64::from(value.max);
+        Some(pct as u32)
+    } else {
+        None
+    };
+    Ok(VcpReading {
+        display: display.id,
+        display_name: display.name().to_string(),
+        feature: feature.to_string(),
+        current: value.current,
+        max: value.max,
+        is_continuous: value.is_continuous,
+        percent,
+    })
+}
+
+/// The formatter for `format`. Text output names each display when
+/// `--all-displays` is given.
+fn formatter(format: OutputFormat, all_displays: bool) -> Box<dyn OutputFormatter> {
+    match format {
+        OutputFormat::Text => Box::new(TextFormatter {
+            show_display_names: all_displays,
+        }),
+        OutputFormat::Json => Box::new(JsonFormatter),
+        OutputFormat::Csv => Box::new(CsvFormatter),
+    }
+}
+
+/// Exit code of `profile load --preview` when loading would change something.
+const PREVIEW_CHANGES_EXIT_CODE: i32 = 1;
+
+/// Exit code of `get --expect` when the value does not match.
+const EXPECT_MISMATCH_EXIT_CODE: i32 = 10;
+
+/// The value `get --expect` or `get --expect-percent` compares against.
+#[derive(Clone, Copy)]
+enum Expectation {
+    Value(u32),
+    Percent(u32),
+}
+
+impl Expectation {
+    /// Whether `reading` is within `tolerance` of the expected value. For
+    /// percentages the tolerance is in percentage points.
+    fn matches(self, reading: &VcpReading, tolerance: u32) -> bool {
+        let (actual, expected) = match self {
+            Expectation::Value(value) => (reading.current, value),
+            Expectation::Percent(pct) => match reading.percent {
+                Some(actual) => (actual, pct),
+                None => return false,
+            },
+        };
+        actual.abs_diff(expected) <= tolerance
+    }
+}
+
+/// Prints formatter output with a `[PASS]` or `[FAIL]` prefix.
+fn print_expectation(reporter: &Reporter, out: &str, pass: bool) {
+    let prefix = if pass { "[PASS]" } else { "[FAIL]" };
+    for line in out.lines() {
+        out!(reporter, "{} {}", prefix, line);
+    }
+}
+
+/// Prints formatter output, skipping empty results so an empty list prints
+/// nothing rather than a blank line.
+fn print_output(reporter: &Reporter, out: &str) {
+    if !out.is_empty() {
+        out!(reporter, "{}", out);
+    }
+}
+
+fn format_continuity(feature: &VcpFeature) -> &'static str {
+    match feature.is_continuous() {
+        Some(true) => "continuous",
+        Some(false) => "non-continuous",
+        None => "unknown",
+    }
+}
+
+/// Parses an input source name, or a raw value in hex (`0x11`) or decimal.
+fn parse_input_source(s: &str) -> Result<InputSource, DisplayError> {
+    if let Some(source) = InputSource::from_name(s) {
+        return Ok(source);
+    }
+    parse_number(s)
+        .ok()
+        .and_then(|v| u16::try_from(v).ok())
+        .map(InputSource::from_value)
+        .ok_or_else(|| DisplayError::config(format!("Unknown input source: {}", s)))
+}
+
+/// Parses a picture preset name, or a raw value in hex (`0x03`) or decimal.
+fn parse_picture_preset(s: &str) -> Result<PicturePreset, DisplayError> {
+    if let Some(preset) = PicturePreset::from_name(s) {
+        return Ok(preset);
+    }
+    parse_number(s)
+        .ok()
+        .and_then(|v| u16::try_from(v).ok())
+        .map(PicturePreset::from_value)
+        .ok_or_else(|| DisplayError::config(format!("Unknown picture preset: {}", s)))
+}
+
+/// Parses a color preset name, or a raw value in hex (`0x05`) or decimal.
+fn parse_color_preset(s: &str) -> Result<ColorPreset, DisplayError> {
+    if let Some(preset) = ColorPreset::from_name(s) {
+        return Ok(preset);
+    }
+    parse_number(s)
+        .ok()
+        .and_then(|v| u16::try_from(v).ok())
+        .map(ColorPreset::from_value)
+        .ok_or_else(|| DisplayError::config(format!("Unknown color preset: {}", s)))
+}
+
+/// Parses the VALUE argument of `set`. For the input source (0x60), source
+/// names such as `hdmi1` or `dp-2` are tried before numbers.
+fn parse_set_value(code: u8, value: &str) -> Result<u32, DisplayError> {
+    if code == VcpFeature::InputSource.code() {
+        return parse_input_source(value).map(|source| u32::from(source.value()));
+    }
+    parse_number(value)
+        .map_err(|e| DisplayError::config_error(format!("Invalid value: {}", value), e))
+}
+
+/// Parses a non-negative integer in hex (`0x11`) or decimal.
+fn parse_number(s: &str) -> Result<u32, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+/// A parsed line of a `batch` file.
+struct BatchStep {
+    /// 1-based line number in the file.
+    line: usize,
+    source: String,
+    feature: String,
+    code: u8,
+    /// The value to write, or `None` to read the feature.
+    value: Option<u32>,
+    display: Option<usize>,
+}
+
+/// Parses every command in a `batch` file, skipping blank lines and `#`
+/// comments. Fails on the first line that does not parse.
+fn parse_batch(script: &str) -> Result<Vec<BatchStep>, DisplayError> {
+    let mut steps = Vec::new();
+    for (index, line) in script.lines().enumerate() {
+        let source = line.trim();
+        if source.is_empty() || source.starts_with('#') {
+            continue;
+        }
+        let invalid =
+            |message: String| DisplayError::config(format!("line {}: {}", index + 1, message));
+        let parsed = BatchLine::try_parse_from(source.split_whitespace()).map_err(|e| {
+            // Keep clap's message but drop the usage and help hints.
+            let message = e.to_string();
+            let summary: Vec<&str> = message
+                .lines()
+                .take_while(|l| !l.trim().is_empty())
+                .map(str::trim)
+                .collect();
+            invalid(summary.join(" ").trim_start_matches("error: ").to_string())
+        })?;
+        let (feature, value, display) = match parsed.command {
+            BatchCommand::Get { feature, display } => (feature, None, display),
+            BatchCommand::Set {
+                feature,
+                value,
+                display,
+            } => (feature, Some(value), display),
+        };
+        let code = feature
+            .parse::<VcpFeature>()
+            .map_err(|e| invalid(e.to_string()))?
+            .code();
+        let value = value
+            .map(|v| parse_set_value(code, &v))
+            .transpose()
+            .map_err(|e| invalid(e.to_string()))?;
+        steps.push(BatchStep {
+            line: index + 1,
+            source: source.to_string(),
+            feature,
+            code,
+            value,
+            display,
+        });
+    }
+    Ok(steps)
+}
+
+/// Runs one `batch` command against the displays enumerated at the start.
+fn run_batch_step(
+    reporter: &Reporter,
+    displays: &mut [backend::Display],
+    step: &BatchStep,
+    targeting: &Targeting,
+    dry_run: bool,
+) -> Result<(), DisplayError> {
+    let target = select_display_mut(displays, step.display, targeting)?;
+    match step.value {
+        None => {
+            let value = target.get_vcp_feature(step.code)?;
+            out!(
+                reporter,
+                "Display {}: {} = {} (max {})",
+                target.id,
+                step.feature,
+                value.current,
+                value.max
+            );
+        }
+        Some(value) if dry_run => {
+            print_dry_run_set(reporter, target, step.code, SetRequest::Absolute(value));
+        }
+        Some(value) => {
+            target.set_vcp_feature(step.code, value)?;
+            out!(
+                reporter,
+                "Display {}: set {} to {}",
+                target.id,
+                step.feature,
+                value
+            );
         }
     }
+    Ok(())
+}
+
+/// Parses a `FEATURE=VALUE` argument of `set-multi`.
+fn parse_setting(s: &str) -> Result<(u8, u32), DisplayError> {
+    let (feature, value) = s
+        .split_once('=')
+        .ok_or_else(|| DisplayError::config(format!("Expected FEATURE=VALUE, got: {}", s)))?;
+    let code = feature.trim().parse::<VcpFeature>()?.code();
+    Ok((code, parse_set_value(code, value.trim())?))
+}
+
+/// Splits a comma-separated list of profile names.
+fn parse_name_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Features copied by `clone` when --features is not given.
+const DEFAULT_CLONE_FEATURES: &str = "brightness,contrast,sharpness,color-temp";
+
+/// Mutable references to two different displays, looked up by ID.
+fn displays_pair_mut(
+    displays: &mut [backend::Display],
+    a: usize,
+    b: usize,
+) -> Option<(&mut backend::Display, &mut backend::Display)> {
+    let i = displays.iter().position(|d| d.id == a)?;
+    let j = displays.iter().position(|d| d.id == b)?;
+    if i < j {
+        let (left, right) = displays.split_at_mut(j);
+        Some((&mut left[i], &mut right[0]))
+    } else if j < i {
+        let (left, right) = displays.split_at_mut(i);
+        Some((&mut right[0], &mut left[j]))
+    } else {
+        None
+    }
+}
+
+/// Parses a comma-separated list of feature names or codes.
+fn parse_feature_list(s: &str) -> Result<Vec<u8>, DisplayError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(|token| token.parse::<VcpFeature>().map(|f| f.code()))
+        .collect()
 }
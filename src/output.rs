@@ -0,0 +1,310 @@
+//! Renders command results as text, JSON, or CSV, selected with the global
+//! `--output-format` flag.
+
+use crate::backend::ConnectionType;
+use crate::capabilities::Capabilities;
+use crate::config::ProfileMeta;
+use crate::error::DisplayError;
+use crate::schema::{CapabilitiesReport, DisplayEntry, FeatureReading, VcpReading};
+use crate::vcp::VcpFeature;
+use chrono::Local;
+use std::fmt::Write;
+
+/// Formats the results of the commands that support `--output-format`.
+/// Every method returns the complete output, without a trailing newline.
+pub trait OutputFormatter {
+    /// `detect`: one entry per display.
+    fn format_displays(&self, displays: &[DisplayEntry]) -> Result<String, DisplayError>;
+
+    /// `get`: one reading per display asked for.
+    fn format_vcp_value(&self, readings: &[VcpReading]) -> Result<String, DisplayError>;
+
+    /// `inspect`: every feature that answered on one display.
+    fn format_vcp_values(&self, readings: &[FeatureReading]) -> Result<String, DisplayError>;
+
+    fn format_capabilities(&self, caps: &Capabilities) -> Result<String, DisplayError>;
+
+    fn format_profile_list(&self, profiles: &[ProfileMeta]) -> Result<String, DisplayError>;
+}
+
+/// The human-readable output dispman has always printed.
+pub struct TextFormatter {
+    /// Name each display in `get` output, as `--all-displays` does.
+    pub show_display_names: bool,
+}
+
+impl OutputFormatter for TextFormatter {
+    fn format_displays(&self, displays: &[DisplayEntry]) -> Result<String, DisplayError> {
+        let lines: Vec<String> = displays
+            .iter()
+            .map(|d| {
+                let name = match d.connection {
+                    Some(connection) if connection != ConnectionType::Unknown => {
+                        format!("{} via {}", d.name, connection)
+                    }
+                    _ => d.name.clone(),
+                };
+                let mut line = match d.geometry {
+                    Some(geometry) => format!(
+                        "Display {}: {} {} (stable id: {})",
+                        d.id, name, geometry, d.stable_id
+                    ),
+                    None => format!("Display {}: {} (stable id: {})", d.id, name, d.stable_id),
+                };
+                if let Some(model) = &d.model {
+                    line.push_str(&format!(" model: {}", model));
+                }
+                if let Some(capable) = d.ddc_capable {
+                    line.push_str(if capable { " [DDC/CI]" } else { " [no DDC/CI]" });
+                }
+                line
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    fn format_vcp_value(&self, readings: &[VcpReading]) -> Result<String, DisplayError> {
+        let lines: Vec<String> = readings
+            .iter()
+            .map(|r| {
+                let value = match r.percent {
+                    Some(pct) => format!("{}/{} ({}%)", r.current, r.max, pct),
+                    None => format!(
+                        "{} / {} (0x{:X} / 0x{:X})",
+                        r.current, r.max, r.current, r.max
+                    ),
+                };
+                if self.show_display_names {
+                    format!(
+                        "Display {} ({}): {} = {}",
+                        r.display, r.display_name, r.feature, value
+                    )
+                } else {
+                    format!("Display {}: {} = {}", r.display, r.feature, value)
+                }
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    fn format_vcp_values(&self, readings: &[FeatureReading]) -> Result<String, DisplayError> {
+        let mut out = format!("{:<6} {:<40} {:>8} {:>8}", "Code", "Name", "Current", "Max");
+        for r in readings {
+            write!(
+                out,
+                "\n0x{:02X}   {:<40} {:>8} {:>8}",
+                r.code, r.name, r.current, r.max
+            )
+            .expect("writing to a String cannot fail");
+        }
+        Ok(out)
+    }
+
+    fn format_capabilities(&self, caps: &Capabilities) -> Result<String, DisplayError> {
+        Ok(caps.to_string())
+    }
+
+    fn format_profile_list(&self, profiles: &[ProfileMeta]) -> Result<String, DisplayError> {
+        if profiles.is_empty() {
+            return Ok(String::new());
+        }
+        let width = profiles
+            .iter()
+            .map(|p| p.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let mut out = format!(
+            "{:<width$}  {:>8}  {:>8}  Created",
+            "Name", "Displays", "Features"
+        );
+        for p in profiles {
+            let created = p.created_at.map_or_else(
+                || "-".to_string(),
+                |t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+            );
+            write!(
+                out,
+                "\n{:<width$}  {:>8}  {:>8}  {}",
+                p.name, p.display_count, p.feature_count, created
+            )
+            .expect("writing to a String cannot fail");
+        }
+        Ok(out)
+    }
+}
+
+/// Pretty-printed JSON, using the types in `crate::schema`.
+pub struct JsonFormatter;
+
+impl OutputFormatter for JsonFormatter {
+    fn format_displays(&self, displays: &[DisplayEntry]) -> Result<String, DisplayError> {
+        Ok(serde_json::to_string_pretty(displays)?)
+    }
+
+    fn format_vcp_value(&self, readings: &[VcpReading]) -> Result<String, DisplayError> {
+        Ok(serde_json::to_string_pretty(readings)?)
+    }
+
+    fn format_vcp_values(&self, readings: &[FeatureReading]) -> Result<String, DisplayError> {
+        Ok(serde_json::to_string_pretty(readings)?)
+    }
+
+    fn format_capabilities(&self, caps: &Capabilities) -> Result<String, DisplayError> {
+        let report = CapabilitiesReport::new(caps);
+        Ok(serde_json::to_string_pretty(&report)?)
+    }
+
+    fn format_profile_list(&self, profiles: &[ProfileMeta]) -> Result<String, DisplayError> {
+        Ok(serde_json::to_string_pretty(profiles)?)
+    }
+}
+
+/// Comma-separated values with a header row. Optional columns are left
+/// empty rather than omitted, so every row has the same shape.
+pub struct CsvFormatter;
+
+impl OutputFormatter for CsvFormatter {
+    fn format_displays(&self, displays: &[DisplayEntry]) -> Result<String, DisplayError> {
+        let mut rows = vec![csv_row([
+            "id",
+            "name",
+            "stable_id",
+            "x",
+            "y",
+            "width",
+            "height",
+            "is_primary",
+            "ddc_capable",
+            "model",
+            "connection",
+        ])];
+        for d in displays {
+            let geometry = d.geometry.map(|g| {
+                [
+                    g.x.to_string(),
+                    g.y.to_string(),
+                    g.width.to_string(),
+                    g.height.to_string(),
+                    g.is_primary.to_string(),
+                ]
+            });
+            let [x, y, width, height, is_primary] = geometry.unwrap_or_default();
+            rows.push(csv_row([
+                d.id.to_string(),
+                d.name.clone(),
+                d.stable_id.clone(),
+                x,
+                y,
+                width,
+                height,
+                is_primary,
+                optional(d.ddc_capable),
+                optional(d.model.as_deref()),
+                optional(d.connection),
+            ]));
+        }
+        Ok(rows.join("\n"))
+    }
+
+    fn format_vcp_value(&self, readings: &[VcpReading]) -> Result<String, DisplayError> {
+        let mut rows = vec![csv_row([
+            "display",
+            "display_name",
+            "feature",
+            "current",
+            "max",
+            "is_continuous",
+            "percent",
+        ])];
+        for r in readings {
+            rows.push(csv_row([
+                r.display.to_string(),
+                r.display_name.clone(),
+                r.feature.clone(),
+                r.current.to_string(),
+                r.max.to_string(),
+                r.is_continuous.to_string(),
+                optional(r.percent),
+            ]));
+        }
+        Ok(rows.join("\n"))
+    }
+
+    fn format_vcp_values(&self, readings: &[FeatureReading]) -> Result<String, DisplayError> {
+        let mut rows = vec![csv_row(["code", "name", "current", "max", "is_continuous"])];
+        for r in readings {
+            rows.push(csv_row([
+                format!("0x{:02X}", r.code),
+                r.name.clone(),
+                r.current.to_string(),
+                r.max.to_string(),
+                r.is_continuous.to_string(),
+            ]));
+        }
+        Ok(rows.join("\n"))
+    }
+
+    fn format_capabilities(&self, caps: &Capabilities) -> Result<String, DisplayError> {
+        let mut codes: Vec<_> = caps.vcp_features.keys().copied().collect();
+        codes.sort();
+        let mut rows = vec![csv_row(["code", "name", "values", "writable"])];
+        for code in codes {
+            let values: Vec<String> = caps.vcp_features[&code]
+                .iter()
+                .map(|v| format!("0x{:02X}", v))
+                .collect();
+            let feature = VcpFeature::from_code(code);
+            rows.push(csv_row([
+                format!("0x{:02X}", code),
+                feature.name().to_string(),
+                values.join(" "),
+                (!feature.is_typically_read_only()).to_string(),
+            ]));
+        }
+        Ok(rows.join("\n"))
+    }
+
+    fn format_profile_list(&self, profiles: &[ProfileMeta]) -> Result<String, DisplayError> {
+        let mut rows = vec![csv_row([
+            "name",
+            "display_count",
+            "feature_count",
+            "created_at",
+        ])];
+        for p in profiles {
+            rows.push(csv_row([
+                p.name.clone(),
+                p.display_count.to_string(),
+                p.feature_count.to_string(),
+                optional(p.created_at.map(|t| t.to_rfc3339())),
+            ]));
+        }
+        Ok(rows.join("\n"))
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn csv_row<I, S>(fields: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    fields
+        .into_iter()
+        .map(|field| csv_field(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Quotes a field if it contains a delimiter, quote, or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,134 @@
+//! System suspend/resume notifications, used by `dispman daemon`.
+
+use crate::error::DisplayError;
+
+/// A power transition reported by the operating system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// The system is about to sleep or hibernate.
+    Suspend,
+    /// The system has resumed from sleep or hibernation.
+    Resume,
+}
+
+/// Blocks the current thread, calling `on_event` for every suspend and
+/// resume until the process exits.
+///
+/// On suspend, Windows only waits a couple of seconds for the handler to
+/// return before the machine goes to sleep.
+pub fn watch_power_events<F>(on_event: F) -> Result<(), DisplayError>
+where
+    F: FnMut(PowerEvent) + 'static,
+{
+    #[cfg(target_os = "windows")]
+    {
+        windows::watch_power_events(Box::new(on_event))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = on_event;
+        Err(DisplayError::UnsupportedPlatform)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use super::PowerEvent;
+    use crate::error::DisplayError;
+    use std::cell::RefCell;
+    use windows::Win32::Foundation::{HANDLE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::System::Power::{
+        RegisterSuspendResumeNotification, UnregisterSuspendResumeNotification,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DEVICE_NOTIFY_WINDOW_HANDLE, DefWindowProcW, DestroyWindow,
+        DispatchMessageW, GetMessageW, HWND_MESSAGE, MSG, PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND,
+        RegisterClassW, WINDOW_EX_STYLE, WINDOW_STYLE, WM_POWERBROADCAST, WNDCLASSW,
+    };
+    use windows::core::w;
+
+    type Handler = Box<dyn FnMut(PowerEvent)>;
+
+    thread_local! {
+        // The window procedure has no way to carry a closure, so the handler
+        // lives on the thread that owns the window and pumps its messages.
+        static HANDLER: RefCell<Option<Handler>> = const { RefCell::new(None) };
+    }
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if msg == WM_POWERBROADCAST {
+            let event = match wparam.0 as u32 {
+                PBT_APMSUSPEND => Some(PowerEvent::Suspend),
+                PBT_APMRESUMEAUTOMATIC => Some(PowerEvent::Resume),
+                _ => None,
+            };
+            if let Some(event) = event {
+                HANDLER.with(|handler| {
+                    if let Some(handler) = handler.borrow_mut().as_mut() {
+                        handler(event);
+                    }
+                });
+            }
+            return LRESULT(1);
+        }
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+    }
+
+    pub fn watch_power_events(on_event: Handler) -> Result<(), DisplayError> {
+        HANDLER.with(|handler| *handler.borrow_mut() = Some(on_event));
+
+        let class_name = w!("dispman-power-events");
+        unsafe {
+            let instance = GetModuleHandleW(None)?;
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance.into(),
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            if RegisterClassW(&class) == 0 {
+                return Err(DisplayError::last_windows_error());
+            }
+
+            // Message-only windows never see broadcasts, so WM_POWERBROADCAST
+            // has to be requested explicitly for this window.
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class_name,
+                w!("dispman"),
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                0,
+                0,
+                Some(HWND_MESSAGE),
+                None,
+                Some(instance.into()),
+                None,
+            )?;
+            let notify =
+                RegisterSuspendResumeNotification(HANDLE(hwnd.0), DEVICE_NOTIFY_WINDOW_HANDLE)?;
+
+            let mut msg = MSG::default();
+            let result = loop {
+                match GetMessageW(&mut msg, None, 0, 0).0 {
+                    0 => break Ok(()),
+                    -1 => break Err(DisplayError::last_windows_error()),
+                    _ => {
+                        DispatchMessageW(&msg);
+                    }
+                }
+            };
+
+            let _ = UnregisterSuspendResumeNotification(notify);
+            let _ = DestroyWindow(hwnd);
+            result
+        }
+    }
+}
@@ -0,0 +1,539 @@
+use crate::backend::Display;
+use crate::config::Profile;
+use crate::error::DisplayError;
+use crate::vcp::{ColorPreset, InputSource, PicturePreset, PowerMode, VcpFeature};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+/// A saved profile with its values decoded, as printed by
+/// `profile list --verbose --json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileDetail {
+    pub name: String,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Machine the profile was saved on, if recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Stored values keyed by display stable ID.
+    pub displays: BTreeMap<String, Vec<StoredSetting>>,
+}
+
+/// One stored VCP value.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredSetting {
+    /// VCP code as a `0xNN` hex string.
+    #[serde(serialize_with = "hex_code")]
+    pub code: u8,
+    /// Command-line name of the feature, e.g. `brightness`.
+    pub feature: String,
+    pub value: u32,
+    /// The value as `describe` shows it, e.g. `Hdmi1` for an input source.
+    pub display_value: String,
+}
+
+fn hex_code<S: Serializer>(code: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{:02X}", code))
+}
+
+impl ProfileDetail {
+    pub fn new(name: &str, profile: &Profile) -> Self {
+        let displays = profile
+            .settings
+            .iter()
+            .map(|(display, values)| {
+                let settings = values
+                    .iter()
+                    .map(|&(code, value)| StoredSetting {
+                        code,
+                        feature: VcpFeature::from_code(code).cli_name(),
+                        value,
+                        display_value: format_value(code, value),
+                    })
+                    .collect();
+                (display.clone(), settings)
+            })
+            .collect();
+        Self {
+            name: name.to_string(),
+            created_at: profile.created_at,
+            hostname: profile.hostname.clone(),
+            displays,
+        }
+    }
+}
+
+impl Profile {
+    /// Summarizes the stored values, one line per display sorted by stable
+    /// ID, e.g. `mymonitor: brightness=80, contrast=50, input=Hdmi1`.
+    pub fn describe(&self) -> String {
+        let displays: BTreeMap<&String, &Vec<(u8, u32)>> = self.settings.iter().collect();
+        displays
+            .into_iter()
+            .map(|(display, values)| format!("{}: {}", display, describe_settings(values)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Formats `(code, value)` pairs as `feature=value`, comma-separated.
+pub fn describe_settings(values: &[(u8, u32)]) -> String {
+    values
+        .iter()
+        .map(|&(code, value)| {
+            format!(
+                "{}={}",
+                VcpFeature::from_code(code).cli_name(),
+                format_value(code, value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats a stored value for display: input sources, power modes and
+/// color presets by name, everything else as a plain number.
+pub fn format_value(code: u8, value: u32) -> String {
+    let Ok(raw) = u16::try_from(value) else {
+        return value.to_string();
+    };
+    match VcpFeature::from_code(code) {
+        VcpFeature::InputSource => InputSource::from_value(raw).to_string(),
+        VcpFeature::PowerMode => PowerMode::from_value(raw).to_string(),
+        VcpFeature::ColorPreset => ColorPreset::from_value(raw).to_string(),
+        VcpFeature::Custom(0xDC) => PicturePreset::from_value(raw).to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// A single VCP code whose stored value differs between two profiles.
+/// `None` means the profile has no value for that display/code pair.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileDiffEntry {
+    pub display: String,
+    pub code: u8,
+    pub a: Option<u32>,
+    pub b: Option<u32>,
+}
+
+/// Compares two profiles and returns every (display, code) pair whose value
+/// differs, including pairs present in only one of them. Entries are sorted
+/// by display identifier and then by VCP code.
+pub fn diff(a: &Profile, b: &Profile) -> Vec<ProfileDiffEntry> {
+    let displays: BTreeSet<&String> = a.settings.keys().chain(b.settings.keys()).collect();
+
+    let mut entries = Vec::new();
+    for display in displays {
+        let values_a = settings_by_code(a, display);
+        let values_b = settings_by_code(b, display);
+        let codes: BTreeSet<u8> = values_a.keys().chain(values_b.keys()).copied().collect();
+
+        for code in codes {
+            let va = values_a.get(&code).copied();
+            let vb = values_b.get(&code).copied();
+            if va != vb {
+                entries.push(ProfileDiffEntry {
+                    display: display.clone(),
+                    code,
+                    a: va,
+                    b: vb,
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// A stored profile value next to what the monitor reports now. `current`
+/// is `None` if the value could not be read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LiveDiffEntry {
+    pub code: u8,
+    pub saved: u32,
+    pub current: Option<u32>,
+}
+
+impl LiveDiffEntry {
+    pub fn matches(&self) -> bool {
+        self.current == Some(self.saved)
+    }
+}
+
+/// Reads every code `profile` stores for `display` and pairs it with the
+/// saved value, in the order the profile lists them. Returns an empty list
+/// if the profile has no entry for the display's stable ID.
+pub fn diff_against_live(profile: &Profile, display: &mut Display) -> Vec<LiveDiffEntry> {
+    let Some(settings) = profile.settings.get(display.stable_id()) else {
+        return Vec::new();
+    };
+    settings
+        .iter()
+        .map(|&(code, saved)| LiveDiffEntry {
+            code,
+            saved,
+            current: display.get_vcp_feature(code).ok().map(|v| v.current),
+        })
+        .collect()
+}
+
+/// Result of `Profile::validate` for one display.
+#[derive(Debug, Default)]
+pub struct ProfileValidation {
+    pub valid: Vec<u8>,
+    /// Codes missing from the capabilities string, or that the monitor did
+    /// not answer when asked for their maximum.
+    pub unsupported_codes: Vec<u8>,
+    /// Values outside the enumerated list or above the maximum.
+    pub out_of_range: Vec<(u8, u32)>,
+}
+
+impl ProfileValidation {
+    pub fn is_valid(&self) -> bool {
+        self.unsupported_codes.is_empty() && self.out_of_range.is_empty()
+    }
+}
+
+impl Profile {
+    /// Checks the values stored for `display` against its capabilities: each
+    /// code must be listed, a value for a feature with enumerated values must
+    /// be one of them, and any other value must not exceed the maximum the
+    /// monitor reports. Excluded codes are skipped.
+    pub fn validate(&self, display: &mut Display) -> Result<ProfileValidation, DisplayError> {
+        let caps = display.capabilities_parsed()?;
+        let mut validation = ProfileValidation::default();
+        let settings = self
+            .settings
+            .get(display.stable_id())
+            .cloned()
+            .unwrap_or_default();
+        for (code, value) in settings {
+            if self.excluded_codes.contains(&code) {
+                continue;
+            }
+            let Some(allowed) = caps.vcp_features.get(&code) else {
+                validation.unsupported_codes.push(code);
+                continue;
+            };
+            let in_range = if allowed.is_empty() {
+                match display.get_vcp_feature(code) {
+                    Ok(reading) => reading.max == 0 || value <= reading.max,
+                    Err(_) => {
+                        validation.unsupported_codes.push(code);
+                        continue;
+                    }
+                }
+            } else {
+                u16::try_from(value).is_ok_and(|v| allowed.contains(&v))
+            };
+            if in_range {
+                validation.valid.push(code);
+            } else {
+                validation.out_of_range.push((code, value));
+            }
+        }
+        Ok(validation)
+    }
+}
+
+/// What `apply` does when a write fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnFailurePolicy {
+    /// Record the failure and write the remaining values.
+    #[default]
+    Continue,
+    /// Stop at the first failure; the remaining values are skipped.
+    Abort,
+    /// Like `Continue`, but the caller treats failures as warnings.
+    Warn,
+}
+
+/// A value `apply` could not write.
+#[derive(Debug)]
+pub struct ProfileError {
+    pub display: usize,
+    pub code: u8,
+    pub error: DisplayError,
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to set feature 0x{:X} on display {}: {}",
+            self.code, self.display, self.error
+        )
+    }
+}
+
+/// Outcome of `apply`, counted in values.
+#[derive(Debug, Default)]
+pub struct ProfileLoadResult {
+    pub applied: usize,
+    /// Values not attempted because an earlier write failed with
+    /// `OnFailurePolicy::Abort`.
+    pub skipped: usize,
+    /// Values not written because the monitor already reported them.
+    pub unchanged: usize,
+    pub failed: Vec<ProfileError>,
+}
+
+impl ProfileLoadResult {
+    pub fn total(&self) -> usize {
+        self.applied + self.skipped + self.unchanged + self.failed.len()
+    }
+}
+
+impl fmt::Display for ProfileLoadResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Applied {}/{} settings", self.applied, self.total())?;
+        let mut notes = Vec::new();
+        if !self.failed.is_empty() || self.skipped > 0 {
+            notes.push(format!("{} failed", self.failed.len()));
+        }
+        if self.skipped > 0 {
+            notes.push(format!("{} skipped", self.skipped));
+        }
+        if self.unchanged > 0 {
+            notes.push(format!("{} already set", self.unchanged));
+        }
+        if notes.is_empty() {
+            return Ok(());
+        }
+        write!(f, " ({})", notes.join(", "))
+    }
+}
+
+/// Writes the values `profile` stores for each of `displays`, in order,
+/// waiting `delay_between_sets_ms` between writes to the same display.
+/// Displays the profile has no entry for are left alone.
+pub fn apply(
+    profile: &Profile,
+    displays: &mut [Display],
+    policy: OnFailurePolicy,
+) -> ProfileLoadResult {
+    apply_with_progress(profile, displays, policy, false, |_| {})
+}
+
+/// A write `apply_with_progress` is about to make. `index` counts from 1.
+pub struct ApplyStep<'a> {
+    pub index: usize,
+    pub total: usize,
+    pub display: &'a Display,
+    pub code: u8,
+    pub value: u32,
+}
+
+/// Number of values `apply` would write to `displays`.
+pub fn count_writes(profile: &Profile, displays: &[Display]) -> usize {
+    displays
+        .iter()
+        .filter_map(|d| profile.settings.get(d.stable_id()))
+        .map(Vec::len)
+        .sum()
+}
+
+/// Like `apply`, calling `on_step` before each write. Values skipped after a
+/// failure under `OnFailurePolicy::Abort` are not reported. With
+/// `skip_unchanged`, each value is read first and only written if it
+/// differs; see `Display::set_vcp_feature_if_changed`.
+pub fn apply_with_progress(
+    profile: &Profile,
+    displays: &mut [Display],
+    policy: OnFailurePolicy,
+    skip_unchanged: bool,
+    mut on_step: impl FnMut(&ApplyStep),
+) -> ProfileLoadResult {
+    let total = count_writes(profile, displays);
+    let mut index = 0;
+    let delay = profile.delay_between_sets_ms.map(Duration::from_millis);
+    let mut result = ProfileLoadResult::default();
+    for d in displays.iter_mut() {
+        let Some(settings) = profile.settings.get(d.stable_id()) else {
+            continue;
+        };
+        for (i, &(code, value)) in settings.iter().enumerate() {
+            if policy == OnFailurePolicy::Abort && !result.failed.is_empty() {
+                result.skipped += 1;
+                continue;
+            }
+            if let Some(delay) = delay.filter(|_| i > 0) {
+                thread::sleep(delay);
+            }
+            index += 1;
+            on_step(&ApplyStep {
+                index,
+                total,
+                display: d,
+                code,
+                value,
+            });
+            let written = if skip_unchanged {
+                d.set_vcp_feature_if_changed(code, value)
+            } else {
+                d.set_vcp_feature(code, value).map(|()| true)
+            };
+            match written {
+                Ok(true) => result.applied += 1,
+                Ok(false) => result.unchanged += 1,
+                Err(error) => result.failed.push(ProfileError {
+                    display: d.id,
+                    code,
+                    error,
+                }),
+            }
+        }
+    }
+    result
+}
+
+fn settings_by_code(profile: &Profile, display: &str) -> BTreeMap<u8, u32> {
+    profile
+        .settings
+        .get(display)
+        .map(|settings| settings.iter().copied().collect())
+        .unwrap_or_default()
+}
+
+/// Combines `profiles` into one, applying them in order: a later profile's
+/// value for a (display, code) pair replaces an earlier one. Codes keep the
+/// position where they first appeared so writes stay in a stable order.
+/// Codes excluded by any of the profiles stay excluded, and the longest
+/// write delay of any of them is used.
+pub fn merge_profiles(profiles: &[&Profile]) -> Profile {
+    let mut settings: HashMap<String, Vec<(u8, u32)>> = HashMap::new();
+    let mut excluded_codes: Vec<u8> = Vec::new();
+    let mut delay_between_sets_ms = None;
+    for profile in profiles {
+        excluded_codes.extend(&profile.excluded_codes);
+        delay_between_sets_ms = delay_between_sets_ms.max(profile.delay_between_sets_ms);
+        for (display, values) in &profile.settings {
+            let merged = settings.entry(display.clone()).or_default();
+            for &(code, value) in values {
+                match merged.iter_mut().find(|(c, _)| *c == code) {
+                    Some(existing) => existing.1 = value,
+                    None => merged.push((code, value)),
+                }
+            }
+        }
+    }
+    excluded_codes.sort_unstable();
+    excluded_codes.dedup();
+    Profile {
+        settings,
+        created_at: None,
+        excluded_codes,
+        delay_between_sets_ms,
+        hostname: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::MockDdcBackend;
+
+    fn profile(settings: &[(&str, &[(u8, u32)])]) -> Profile {
+        Profile {
+            settings: settings
+                .iter()
+                .map(|(display, values)| (display.to_string(), values.to_vec()))
+                .collect(),
+            created_at: None,
+            excluded_codes: Vec::new(),
+            delay_between_sets_ms: None,
+            hostname: None,
+        }
+    }
+
+    fn monitor() -> MockDdcBackend {
+        MockDdcBackend::new()
+            .with_value(0x10, 40, 100)
+            .with_value(0x12, 50, 100)
+    }
+
+    #[test]
+    fn apply_writes_values_in_order() {
+        let mock = monitor();
+        let mut displays = vec![mock.display(0, "Dell", "dell")];
+        let profile = profile(&[("dell", &[(0x10, 80), (0x12, 60)])]);
+        let result = apply(&profile, &mut displays, OnFailurePolicy::Continue);
+        assert_eq!(result.applied, 2);
+        assert_eq!(mock.state().writes, [(0x10, 80), (0x12, 60)]);
+    }
+
+    #[test]
+    fn apply_leaves_displays_without_entry_alone() {
+        let mock = monitor();
+        let mut displays = vec![mock.display(0, "Dell", "dell")];
+        let profile = profile(&[("other", &[(0x10, 80)])]);
+        let result = apply(&profile, &mut displays, OnFailurePolicy::Continue);
+        assert_eq!(result.total(), 0);
+        assert!(mock.state().writes.is_empty());
+    }
+
+    #[test]
+    fn apply_continues_after_failure() {
+        let mock = monitor();
+        let mut displays = vec![mock.display(0, "Dell", "dell")];
+        let profile = profile(&[("dell", &[(0x87, 5), (0x10, 80)])]);
+        let result = apply(&profile, &mut displays, OnFailurePolicy::Continue);
+        assert_eq!(result.applied, 1);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].code, 0x87);
+        assert_eq!(result.to_string(), "Applied 1/2 settings (1 failed)");
+    }
+
+    #[test]
+    fn apply_abort_skips_remaining_values() {
+        let mock = monitor();
+        let mut displays = vec![mock.display(0, "Dell", "dell")];
+        let profile = profile(&[("dell", &[(0x87, 5), (0x10, 80), (0x12, 60)])]);
+        let result = apply(&profile, &mut displays, OnFailurePolicy::Abort);
+        assert_eq!((result.applied, result.skipped), (0, 2));
+        assert!(mock.state().writes.is_empty());
+    }
+
+    #[test]
+    fn apply_skip_unchanged_counts_values_already_set() {
+        let mock = monitor();
+        let mut displays = vec![mock.display(0, "Dell", "dell")];
+        let profile = profile(&[("dell", &[(0x10, 40), (0x12, 60)])]);
+        let result = apply_with_progress(
+            &profile,
+            &mut displays,
+            OnFailurePolicy::Continue,
+            true,
+            |_| {},
+        );
+        assert_eq!((result.applied, result.unchanged), (1, 1));
+        assert_eq!(mock.state().writes, [(0x12, 60)]);
+        assert_eq!(result.to_string(), "Applied 1/2 settings (1 already set)");
+    }
+
+    #[test]
+    fn apply_reports_each_step() {
+        let mock = monitor();
+        let mut displays = vec![mock.display(0, "Dell", "dell")];
+        let profile = profile(&[("dell", &[(0x10, 80), (0x12, 60)])]);
+        let mut steps = Vec::new();
+        apply_with_progress(
+            &profile,
+            &mut displays,
+            OnFailurePolicy::Continue,
+            false,
+            |step| steps.push((step.index, step.total, step.code)),
+        );
+        assert_eq!(steps, [(1, 2, 0x10), (2, 2, 0x12)]);
+    }
+
+    #[test]
+    fn merge_profiles_prefers_later_values() {
+        let base = profile(&[("dell", &[(0x10, 40), (0x12, 50)])]);
+        let night = profile(&[("dell", &[(0x10, 10)])]);
+        let merged = merge_profiles(&[&base, &night]);
+        assert_eq!(merged.settings["dell"], [(0x10, 10), (0x12, 50)]);
+    }
+}
@@ -0,0 +1,100 @@
+//! Console output for command handlers, honoring `--quiet` and `--verbose`.
+
+use std::fmt;
+use std::io::{self, Write};
+
+/// Decides which messages reach the terminal. Regular output goes to stdout
+/// and is dropped with `--quiet`; errors always reach stderr, and progress
+/// details only with `--verbose`.
+#[derive(Debug, Clone, Copy)]
+pub struct Reporter {
+    quiet: bool,
+    verbose: bool,
+}
+
+impl Reporter {
+    pub fn new(quiet: bool, verbose: bool) -> Self {
+        Self { quiet, verbose }
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.verbose
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// Prints a line to stdout unless `--quiet` is set.
+    pub fn println(&self, args: fmt::Arguments<'_>) {
+        if !self.quiet {
+            println!("{}", args);
+        }
+    }
+
+    /// Like `println`, without the trailing newline.
+    pub fn print(&self, args: fmt::Arguments<'_>) {
+        if !self.quiet {
+            print!("{}", args);
+        }
+    }
+
+    /// Prints a line to stderr, but only with `--verbose`.
+    pub fn verbose(&self, args: fmt::Arguments<'_>) {
+        if self.verbose {
+            eprintln!("{}", args);
+        }
+    }
+}
+
+/// A one-line progress bar on stderr, redrawn in place as steps complete.
+pub struct ProgressBar {
+    done: usize,
+    total: usize,
+}
+
+impl ProgressBar {
+    const WIDTH: usize = 30;
+
+    pub fn new(total: usize) -> Self {
+        let bar = Self { done: 0, total };
+        bar.draw();
+        bar
+    }
+
+    pub fn inc(&mut self) {
+        self.done = (self.done + 1).min(self.total);
+        self.draw();
+    }
+
+    /// Ends the bar's line so later output starts on a fresh one.
+    pub fn finish(self) {
+        eprintln!();
+    }
+
+    fn draw(&self) {
+        let filled = (self.done * Self::WIDTH)
+            .checked_div(self.total)
+            .unwrap_or(Self::WIDTH);
+        let mut stderr = io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r[{}{}] {}/{}",
+            "#".repeat(filled),
+            "-".repeat(Self::WIDTH - filled),
+            self.done,
+            self.total
+        );
+        let _ = stderr.flush();
+    }
+}
+
+/// `println!` through a `Reporter`: `out!(reporter, "Set {}", value)`.
+macro_rules! out {
+    ($reporter:expr) => {
+        $reporter.println(format_args!(""))
+    };
+    ($reporter:expr, $($arg:tt)*) => {
+        $reporter.println(format_args!($($arg)*))
+    };
+}
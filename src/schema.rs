@@ -0,0 +1,194 @@
+//! Types serialized by `--json` output. Their field names and meanings are
+//! stable across releases: fields may be added, but not renamed or removed.
+
+use crate::backend::{ConnectionType, Display, MonitorGeometry};
+use crate::capabilities::Capabilities;
+use crate::vcp::{InputSource, PowerMode, VcpFeature, VcpValue};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// One VCP feature read from a monitor, as printed by `inspect --json`.
+///
+/// ```json
+/// { "code": "0x10", "name": "Brightness", "current": 80, "max": 100, "is_continuous": true }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureReading {
+    /// VCP code as a `0xNN` hex string.
+    #[serde(serialize_with = "hex_code")]
+    pub code: u8,
+    /// Human-readable feature name; `Unknown` for codes dispman cannot name.
+    pub name: String,
+    pub current: u32,
+    pub max: u32,
+    /// Whether the monitor replied with a continuous (set parameter) value.
+    pub is_continuous: bool,
+}
+
+impl FeatureReading {
+    pub fn new(code: u8, value: &VcpValue) -> Self {
+        Self {
+            code,
+            name: VcpFeature::from_code(code).name().to_string(),
+            current: value.current,
+            max: value.max,
+            is_continuous: value.is_continuous,
+        }
+    }
+}
+
+/// Parsed capabilities, as printed by `capabilities --json`: the fields of
+/// `Capabilities` plus `writable`, keyed like `vcp_features`, which is
+/// `false` for features MCCS defines as read-only.
+#[derive(Debug, Serialize)]
+pub struct CapabilitiesReport<'a> {
+    #[serde(flatten)]
+    pub capabilities: &'a Capabilities,
+    pub writable: BTreeMap<String, bool>,
+}
+
+impl<'a> CapabilitiesReport<'a> {
+    pub fn new(capabilities: &'a Capabilities) -> Self {
+        let writable = capabilities
+            .vcp_features
+            .keys()
+            .map(|&code| {
+                let read_only = VcpFeature::from_code(code).is_typically_read_only();
+                (format!("0x{:02X}", code), !read_only)
+            })
+            .collect();
+        Self {
+            capabilities,
+            writable,
+        }
+    }
+}
+
+fn hex_code<S: Serializer>(code: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{:02X}", code))
+}
+
+/// Which of the slower DDC/CI queries `DisplayEntry::gather` runs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DetectOptions {
+    /// Ping the monitor to fill in `ddc_capable`.
+    pub ping: bool,
+    /// Fetch the capabilities string to fill in `model`. This takes a
+    /// second or two per monitor.
+    pub model: bool,
+}
+
+/// One monitor, as printed by `detect`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DisplayEntry {
+    pub id: usize,
+    pub name: String,
+    pub stable_id: String,
+    pub geometry: Option<MonitorGeometry>,
+    /// How the monitor is connected, where the platform reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection: Option<ConnectionType>,
+    /// Whether the monitor answered a DDC/CI ping. Only checked for
+    /// machine-readable output or `detect --full`, since pinging every
+    /// monitor is slow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ddc_capable: Option<bool>,
+    /// Model from the capabilities string, with `detect --full`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Pixel density, from the physical size the driver reports and the
+    /// native resolution in the EDID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppi: Option<f64>,
+}
+
+impl DisplayEntry {
+    /// Describes `display`, running the DDC/CI queries `options` asks for.
+    /// A monitor that does not return a capabilities string has no model.
+    pub fn gather(display: &mut Display, options: DetectOptions) -> Self {
+        let ddc_capable = options.ping.then(|| display.is_ddc_capable());
+        let model = if options.model {
+            display
+                .capabilities_parsed()
+                .ok()
+                .and_then(|caps| caps.model)
+        } else {
+            None
+        };
+        Self {
+            id: display.id,
+            name: display.name().to_string(),
+            stable_id: display.stable_id().to_string(),
+            geometry: display.geometry(),
+            connection: display.connection(),
+            ddc_capable,
+            model,
+            ppi: display.ppi(),
+        }
+    }
+}
+
+/// One monitor's summary, as printed by `info`. Features are `null` when
+/// the monitor does not answer for them.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorInfo {
+    pub id: usize,
+    pub name: String,
+    pub stable_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection: Option<ConnectionType>,
+    pub ddc_capable: bool,
+    pub brightness: Option<VcpValue>,
+    pub contrast: Option<VcpValue>,
+    /// Decoded name, e.g. `Hdmi1`.
+    pub input_source: Option<String>,
+    /// Decoded name, e.g. `On`.
+    pub power_mode: Option<String>,
+    /// Saved profiles with values for this monitor, by name.
+    pub profiles: Vec<String>,
+}
+
+impl MonitorInfo {
+    /// Describes `display`. Its features are only read if it answers a
+    /// DDC/CI ping, so a silent monitor does not stall on retries.
+    pub fn gather(display: &mut Display, profiles: Vec<String>) -> Self {
+        let ddc_capable = display.is_ddc_capable();
+        let mut read = |code: u8| {
+            ddc_capable
+                .then(|| display.get_vcp_feature(code).ok())
+                .flatten()
+        };
+        let brightness = read(0x10);
+        let contrast = read(0x12);
+        let input_source =
+            read(0x60).map(|v| InputSource::from_value(v.current as u16).to_string());
+        let power_mode = read(0xD6).map(|v| PowerMode::from_value(v.current as u16).to_string());
+        Self {
+            id: display.id,
+            name: display.name().to_string(),
+            stable_id: display.stable_id().to_string(),
+            connection: display.connection(),
+            ddc_capable,
+            brightness,
+            contrast,
+            input_source,
+            power_mode,
+            profiles,
+        }
+    }
+}
+
+/// A feature read from one display, as printed by `get`.
+#[derive(Debug, Clone, Serialize)]
+pub struct VcpReading {
+    pub display: usize,
+    pub display_name: String,
+    /// The feature as given on the command line (a name or a code).
+    pub feature: String,
+    pub current: u32,
+    pub max: u32,
+    pub is_continuous: bool,
+    /// `current` as a percentage of `max`, when `--percent` was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u32>,
+}
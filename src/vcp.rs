@@ -1,5 +1,32 @@
+use crate::capabilities::Capabilities;
+use crate::error::DisplayError;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
+
+/// A VCP feature reading as reported by the monitor: the current value, the
+/// maximum value the monitor accepts for that code, and the reply type.
+///
+/// `is_continuous` is derived from the reply type: momentary codes are
+/// non-continuous, "set parameter" codes are treated as continuous. MCCS
+/// also classifies some set-parameter codes (such as the input source) as
+/// non-continuous, so this is a hint rather than a guarantee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VcpValue {
+    pub current: u32,
+    pub max: u32,
+    pub is_continuous: bool,
+}
+
+impl fmt::Display for VcpValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} / {} (0x{:X} / 0x{:X})",
+            self.current, self.max, self.current, self.max
+        )
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum VcpFeature {
@@ -8,10 +35,129 @@ pub enum VcpFeature {
     Contrast,
     Volume,
     PowerMode,
+    ColorPreset,
+    RedGain,
+    GreenGain,
+    BlueGain,
+    Hue,
+    Saturation,
+    Sharpness,
+    HorizontalPosition,
+    HorizontalSize,
+    VerticalPosition,
+    VerticalSize,
+    HorizontalFrequency,
+    VerticalFrequency,
+    AutoSetup,
     Custom(u8),
 }
 
+/// Broad grouping of VCP features, loosely following the sections of the
+/// MCCS specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VcpCategory {
+    Image,
+    Color,
+    Geometry,
+    Audio,
+    Misc,
+}
+
+const INPUT_SOURCE_DESCRIPTION: &str = "Selects the active video input. Non-continuous: \
+each value names one input (0x01 analog 1, 0x03 DVI 1, 0x0F DisplayPort 1, 0x11 HDMI 1, ...). \
+Vendors frequently use values outside the MCCS table, so check the monitor's capabilities \
+string for the values it accepts.";
+const BRIGHTNESS_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the luminance of the image. Continuous: 0 is the minimum luminance and the \
+maximum is reported by the display.";
+const CONTRAST_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the contrast of the image. Continuous: 0 is the minimum contrast and the \
+maximum is reported by the display.";
+const VOLUME_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the audio volume. Continuous; writing 0 is not the same as muting, which is \
+controlled by VCP 0x8D.";
+const POWER_MODE_DESCRIPTION: &str = "Controls the display's power state. Non-continuous: \
+0x01 on, 0x02 standby, 0x03 suspend, 0x04 off (DPM), 0x05 off (hard power switch). A \
+display that is fully powered off may stop answering DDC/CI.";
+const COLOR_PRESET_DESCRIPTION: &str = "Selects one of the display's color temperature \
+presets. Non-continuous: values include 0x01 sRGB, 0x02 native, 0x04-0x0A fixed color \
+temperatures from 4000 K to 11000 K, and 0x0B-0x0D user presets.";
+const RED_GAIN_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the luminance of red pixels. Continuous.";
+const GREEN_GAIN_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the luminance of green pixels. Continuous.";
+const BLUE_GAIN_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the luminance of blue pixels. Continuous.";
+const HUE_DESCRIPTION: &str = "Increasing (decreasing) this value shifts the hue of all \
+colors around the color wheel. Continuous; the midpoint of the range is the default.";
+const SATURATION_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the saturation of all colors. Continuous; 0 produces a grayscale image.";
+const SHARPNESS_DESCRIPTION: &str = "Increasing (decreasing) this value sharpens (softens) \
+the image. Continuous; the exact algorithm is defined by the display.";
+const HORIZONTAL_POSITION_DESCRIPTION: &str = "Increasing (decreasing) this value moves \
+the image toward the right (left) of the screen. Continuous; mostly implemented by CRTs \
+and analog inputs.";
+const HORIZONTAL_SIZE_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the width of the image. Continuous; mostly implemented by CRTs and analog \
+inputs.";
+const VERTICAL_POSITION_DESCRIPTION: &str = "Increasing (decreasing) this value moves the \
+image toward the top (bottom) of the screen. Continuous; mostly implemented by CRTs and \
+analog inputs.";
+const VERTICAL_SIZE_DESCRIPTION: &str = "Increasing (decreasing) this value increases \
+(decreases) the height of the image. Continuous; mostly implemented by CRTs and analog \
+inputs.";
+const HORIZONTAL_FREQUENCY_DESCRIPTION: &str = "Horizontal sync frequency of the input \
+signal in Hz. Read-only; all bits set means the display cannot measure it.";
+const VERTICAL_FREQUENCY_DESCRIPTION: &str = "Vertical sync frequency of the input signal \
+in units of 0.01 Hz. Read-only; 0xFFFF means the display cannot measure it.";
+const AUTO_SETUP_DESCRIPTION: &str = "Writing 0x01 makes the display adjust its geometry \
+and clock to the current analog signal. Non-continuous; has no effect on digital inputs \
+for most displays.";
+
+/// Codes without a dedicated variant that MCCS defines as read-only: color
+/// temperature increment, active control, sub-pixel layout, display
+/// technology, usage time, application key, firmware level and VCP version.
+const READ_ONLY_CODES: [u8; 8] = [0x0B, 0x52, 0xB2, 0xB6, 0xC0, 0xC6, 0xC9, 0xDF];
+
 impl VcpFeature {
+    /// Every feature with a dedicated variant (everything but `Custom`), in
+    /// declaration order.
+    pub const ALL_KNOWN: &'static [VcpFeature] = &[
+        VcpFeature::InputSource,
+        VcpFeature::Brightness,
+        VcpFeature::Contrast,
+        VcpFeature::Volume,
+        VcpFeature::PowerMode,
+        VcpFeature::ColorPreset,
+        VcpFeature::RedGain,
+        VcpFeature::GreenGain,
+        VcpFeature::BlueGain,
+        VcpFeature::Hue,
+        VcpFeature::Saturation,
+        VcpFeature::Sharpness,
+        VcpFeature::HorizontalPosition,
+        VcpFeature::HorizontalSize,
+        VcpFeature::VerticalPosition,
+        VcpFeature::VerticalSize,
+        VcpFeature::HorizontalFrequency,
+        VcpFeature::VerticalFrequency,
+        VcpFeature::AutoSetup,
+    ];
+
+    /// The MCCS geometry features shown by `dispman geometry`, in table
+    /// order.
+    pub fn geometry_codes() -> &'static [VcpFeature] {
+        &[
+            VcpFeature::HorizontalPosition,
+            VcpFeature::HorizontalSize,
+            VcpFeature::VerticalPosition,
+            VcpFeature::VerticalSize,
+            VcpFeature::HorizontalFrequency,
+            VcpFeature::VerticalFrequency,
+            VcpFeature::AutoSetup,
+        ]
+    }
+
     pub fn code(&self) -> u8 {
         match self {
             VcpFeature::InputSource => 0x60,
@@ -19,6 +165,20 @@ impl VcpFeature {
             VcpFeature::Contrast => 0x12,
             VcpFeature::Volume => 0x62,
             VcpFeature::PowerMode => 0xD6,
+            VcpFeature::ColorPreset => 0x14,
+            VcpFeature::RedGain => 0x16,
+            VcpFeature::GreenGain => 0x18,
+            VcpFeature::BlueGain => 0x1A,
+            VcpFeature::Hue => 0x90,
+            VcpFeature::Saturation => 0x8A,
+            VcpFeature::Sharpness => 0x87,
+            VcpFeature::HorizontalPosition => 0x20,
+            VcpFeature::HorizontalSize => 0x22,
+            VcpFeature::VerticalPosition => 0x30,
+            VcpFeature::VerticalSize => 0x32,
+            VcpFeature::HorizontalFrequency => 0xAC,
+            VcpFeature::VerticalFrequency => 0xAE,
+            VcpFeature::AutoSetup => 0x1E,
             VcpFeature::Custom(c) => *c,
         }
     }
@@ -30,6 +190,20 @@ impl VcpFeature {
             0x12 => VcpFeature::Contrast,
             0x62 => VcpFeature::Volume,
             0xD6 => VcpFeature::PowerMode,
+            0x14 => VcpFeature::ColorPreset,
+            0x16 => VcpFeature::RedGain,
+            0x18 => VcpFeature::GreenGain,
+            0x1A => VcpFeature::BlueGain,
+            0x20 => VcpFeature::HorizontalPosition,
+            0x22 => VcpFeature::HorizontalSize,
+            0x30 => VcpFeature::VerticalPosition,
+            0x32 => VcpFeature::VerticalSize,
+            0xAC => VcpFeature::HorizontalFrequency,
+            0xAE => VcpFeature::VerticalFrequency,
+            0x1E => VcpFeature::AutoSetup,
+            0x90 => VcpFeature::Hue,
+            0x8A => VcpFeature::Saturation,
+            0x87 => VcpFeature::Sharpness,
             c => VcpFeature::Custom(c),
         }
     }
@@ -41,9 +215,158 @@ impl VcpFeature {
             VcpFeature::Contrast => "Contrast",
             VcpFeature::Volume => "Volume",
             VcpFeature::PowerMode => "Power Mode",
+            VcpFeature::ColorPreset => "Select Color Preset",
+            VcpFeature::RedGain => "Video Gain: Red",
+            VcpFeature::GreenGain => "Video Gain: Green",
+            VcpFeature::BlueGain => "Video Gain: Blue",
+            VcpFeature::Hue => "Hue",
+            VcpFeature::Saturation => "Color Saturation",
+            VcpFeature::Sharpness => "Sharpness",
+            VcpFeature::HorizontalPosition => "Horizontal Position (Phase)",
+            VcpFeature::HorizontalSize => "Horizontal Size",
+            VcpFeature::VerticalPosition => "Vertical Position (Phase)",
+            VcpFeature::VerticalSize => "Vertical Size",
+            VcpFeature::HorizontalFrequency => "Horizontal Frequency",
+            VcpFeature::VerticalFrequency => "Vertical Frequency",
+            VcpFeature::AutoSetup => "Auto Setup",
             VcpFeature::Custom(c) => mccs_name(*c).unwrap_or("Unknown"),
         }
     }
+
+    /// The name accepted on the command line (e.g. `brightness`,
+    /// `color-temp`), or the `0xNN` code for features without one.
+    pub fn cli_name(&self) -> String {
+        let name = match self {
+            VcpFeature::InputSource => "input",
+            VcpFeature::Brightness => "brightness",
+            VcpFeature::Contrast => "contrast",
+            VcpFeature::Volume => "volume",
+            VcpFeature::PowerMode => "power",
+            VcpFeature::ColorPreset => "color-temp",
+            VcpFeature::RedGain => "red-gain",
+            VcpFeature::GreenGain => "green-gain",
+            VcpFeature::BlueGain => "blue-gain",
+            VcpFeature::Hue => "hue",
+            VcpFeature::Saturation => "saturation",
+            VcpFeature::Sharpness => "sharpness",
+            VcpFeature::HorizontalPosition => "h-position",
+            VcpFeature::HorizontalSize => "h-size",
+            VcpFeature::VerticalPosition => "v-position",
+            VcpFeature::VerticalSize => "v-size",
+            VcpFeature::HorizontalFrequency => "h-frequency",
+            VcpFeature::VerticalFrequency => "v-frequency",
+            VcpFeature::AutoSetup => "auto-setup",
+            VcpFeature::Custom(c) => return format!("0x{:02X}", c),
+        };
+        name.to_string()
+    }
+
+    pub fn category(&self) -> VcpCategory {
+        match self {
+            VcpFeature::Brightness | VcpFeature::Contrast | VcpFeature::Sharpness => {
+                VcpCategory::Image
+            }
+            VcpFeature::ColorPreset
+            | VcpFeature::RedGain
+            | VcpFeature::GreenGain
+            | VcpFeature::BlueGain
+            | VcpFeature::Hue
+            | VcpFeature::Saturation => VcpCategory::Color,
+            VcpFeature::Volume => VcpCategory::Audio,
+            VcpFeature::HorizontalPosition
+            | VcpFeature::HorizontalSize
+            | VcpFeature::VerticalPosition
+            | VcpFeature::VerticalSize
+            | VcpFeature::HorizontalFrequency
+            | VcpFeature::VerticalFrequency
+            | VcpFeature::AutoSetup => VcpCategory::Geometry,
+            VcpFeature::InputSource | VcpFeature::PowerMode => VcpCategory::Misc,
+            VcpFeature::Custom(c) => custom_category(*c),
+        }
+    }
+
+    /// Whether MCCS defines the feature as continuous (a range from 0 to
+    /// max) rather than a set of enumerated values. `None` for custom codes.
+    pub fn is_continuous(&self) -> Option<bool> {
+        match self {
+            VcpFeature::InputSource
+            | VcpFeature::PowerMode
+            | VcpFeature::ColorPreset
+            | VcpFeature::AutoSetup => Some(false),
+            VcpFeature::Custom(_) => None,
+            _ => Some(true),
+        }
+    }
+
+    /// Whether MCCS defines the feature as read-only, such as status and
+    /// timing reports. Monitors may still deviate; this is a hint, not a
+    /// guarantee that writes fail.
+    pub fn is_typically_read_only(&self) -> bool {
+        match self {
+            VcpFeature::HorizontalFrequency | VcpFeature::VerticalFrequency => true,
+            VcpFeature::Custom(code) => READ_ONLY_CODES.contains(code),
+            _ => false,
+        }
+    }
+
+    /// A short description of the feature, paraphrasing the MCCS
+    /// specification.
+    pub fn description(&self) -> &'static str {
+        match self {
+            VcpFeature::InputSource => INPUT_SOURCE_DESCRIPTION,
+            VcpFeature::Brightness => BRIGHTNESS_DESCRIPTION,
+            VcpFeature::Contrast => CONTRAST_DESCRIPTION,
+            VcpFeature::Volume => VOLUME_DESCRIPTION,
+            VcpFeature::PowerMode => POWER_MODE_DESCRIPTION,
+            VcpFeature::ColorPreset => COLOR_PRESET_DESCRIPTION,
+            VcpFeature::RedGain => RED_GAIN_DESCRIPTION,
+            VcpFeature::GreenGain => GREEN_GAIN_DESCRIPTION,
+            VcpFeature::BlueGain => BLUE_GAIN_DESCRIPTION,
+            VcpFeature::Hue => HUE_DESCRIPTION,
+            VcpFeature::Saturation => SATURATION_DESCRIPTION,
+            VcpFeature::Sharpness => SHARPNESS_DESCRIPTION,
+            VcpFeature::HorizontalPosition => HORIZONTAL_POSITION_DESCRIPTION,
+            VcpFeature::HorizontalSize => HORIZONTAL_SIZE_DESCRIPTION,
+            VcpFeature::VerticalPosition => VERTICAL_POSITION_DESCRIPTION,
+            VcpFeature::VerticalSize => VERTICAL_SIZE_DESCRIPTION,
+            VcpFeature::HorizontalFrequency => HORIZONTAL_FREQUENCY_DESCRIPTION,
+            VcpFeature::VerticalFrequency => VERTICAL_FREQUENCY_DESCRIPTION,
+            VcpFeature::AutoSetup => AUTO_SETUP_DESCRIPTION,
+            VcpFeature::Custom(_) => "No description available for this code.",
+        }
+    }
+}
+
+impl VcpCategory {
+    /// Parses a category name case-insensitively (e.g. `"color"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "image" => Some(VcpCategory::Image),
+            "color" | "colour" => Some(VcpCategory::Color),
+            "geometry" => Some(VcpCategory::Geometry),
+            "audio" => Some(VcpCategory::Audio),
+            "misc" => Some(VcpCategory::Misc),
+            _ => None,
+        }
+    }
+}
+
+/// Category for codes without a dedicated variant, based on where the code
+/// sits in the MCCS table.
+fn custom_category(code: u8) -> VcpCategory {
+    match code {
+        0x0B | 0x0C | 0x17 | 0x59..=0x5E | 0x6B..=0x72 | 0x9B..=0xA0 => VcpCategory::Color,
+        0x1C | 0x2E | 0x86 | 0x88 | 0xDA | 0xDB => VcpCategory::Image,
+        0x0E | 0x1E | 0x20..=0x4C | 0x56 | 0x58 | 0x82 | 0x84 | 0xAA => VcpCategory::Geometry,
+        0x8D | 0x8F | 0x91 | 0x93 | 0x94 => VcpCategory::Audio,
+        _ => VcpCategory::Misc,
+    }
+}
+
+impl fmt::Display for VcpCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
 }
 
 /// Standard MCCS 2.2 VCP code names for codes not directly modeled as
@@ -191,12 +514,111 @@ fn mccs_name(code: u8) -> Option<&'static str> {
     })
 }
 
+/// Parses a feature name as accepted on the command line (`brightness`,
+/// `color-temp`, `red-gain`, ...) or a raw code in hex (`0x10`) or decimal.
+/// Codes without a dedicated variant become `VcpFeature::Custom`.
+impl FromStr for VcpFeature {
+    type Err = DisplayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        let feature = match lower.as_str() {
+            "brightness" => VcpFeature::Brightness,
+            "contrast" => VcpFeature::Contrast,
+            "volume" => VcpFeature::Volume,
+            "input" => VcpFeature::InputSource,
+            "power" => VcpFeature::PowerMode,
+            "color-temp" | "color-preset" => VcpFeature::ColorPreset,
+            "red-gain" => VcpFeature::RedGain,
+            "green-gain" => VcpFeature::GreenGain,
+            "blue-gain" => VcpFeature::BlueGain,
+            "hue" => VcpFeature::Hue,
+            "saturation" => VcpFeature::Saturation,
+            "sharpness" => VcpFeature::Sharpness,
+            "h-position" => VcpFeature::HorizontalPosition,
+            "h-size" => VcpFeature::HorizontalSize,
+            "v-position" => VcpFeature::VerticalPosition,
+            "v-size" => VcpFeature::VerticalSize,
+            "h-frequency" => VcpFeature::HorizontalFrequency,
+            "v-frequency" => VcpFeature::VerticalFrequency,
+            "auto-setup" => VcpFeature::AutoSetup,
+            other => {
+                let code = match other.strip_prefix("0x") {
+                    Some(hex) => u8::from_str_radix(hex, 16).map_err(|_| {
+                        DisplayError::FeatureNotSupported(format!("Invalid hex code: {}", s))
+                    })?,
+                    None => other.parse::<u8>().map_err(|_| {
+                        DisplayError::FeatureNotSupported(format!("Unknown feature: {}", s))
+                    })?,
+                };
+                VcpFeature::from_code(code)
+            }
+        };
+        Ok(feature)
+    }
+}
+
 impl fmt::Display for VcpFeature {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} (0x{:02X})", self.name(), self.code())
     }
 }
 
+/// Values of VCP 0xD6 (Power Mode).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerMode {
+    On,
+    Standby,
+    Suspend,
+    Off,
+    HardOff,
+    Unrecognized(u16),
+}
+
+impl PowerMode {
+    pub fn from_value(value: u16) -> Self {
+        match value {
+            0x01 => PowerMode::On,
+            0x02 => PowerMode::Standby,
+            0x03 => PowerMode::Suspend,
+            0x04 => PowerMode::Off,
+            0x05 => PowerMode::HardOff,
+            v => PowerMode::Unrecognized(v),
+        }
+    }
+
+    pub fn value(&self) -> u16 {
+        match self {
+            PowerMode::On => 0x01,
+            PowerMode::Standby => 0x02,
+            PowerMode::Suspend => 0x03,
+            PowerMode::Off => 0x04,
+            PowerMode::HardOff => 0x05,
+            PowerMode::Unrecognized(v) => *v,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerMode::On => "On",
+            PowerMode::Standby => "Standby",
+            PowerMode::Suspend => "Suspend",
+            PowerMode::Off => "Off",
+            PowerMode::HardOff => "HardOff",
+            PowerMode::Unrecognized(_) => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for PowerMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PowerMode::Unrecognized(v) => write!(f, "Unknown(0x{:02X})", v),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InputSource {
     Analog1,
@@ -217,11 +639,73 @@ pub enum InputSource {
     DisplayPort2,
     Hdmi1,
     Hdmi2,
-    UsbC,
+    /// USB-C, with the value this monitor uses for it. MCCS assigns no value
+    /// to USB-C, so it differs between vendors; see `resolve_usb_c`.
+    UsbC(u16),
     Unrecognized(u16),
 }
 
 impl InputSource {
+    /// Every named input source, in MCCS value order.
+    pub const ALL: [InputSource; 19] = [
+        InputSource::Analog1,
+        InputSource::Analog2,
+        InputSource::Digital1,
+        InputSource::Digital2,
+        InputSource::Composite1,
+        InputSource::Composite2,
+        InputSource::SVideo1,
+        InputSource::SVideo2,
+        InputSource::Tuner1,
+        InputSource::Tuner2,
+        InputSource::Tuner3,
+        InputSource::Component1,
+        InputSource::Component2,
+        InputSource::Component3,
+        InputSource::DisplayPort1,
+        InputSource::DisplayPort2,
+        InputSource::Hdmi1,
+        InputSource::Hdmi2,
+        InputSource::UsbC(InputSource::USB_C_FALLBACK),
+    ];
+
+    /// Value used for USB-C when the monitor's capabilities do not reveal
+    /// its own: the first value after the MCCS-defined range.
+    pub const USB_C_FALLBACK: u16 = 0x13;
+
+    /// Parses a source name such as `"hdmi1"`, `"DP-2"`, `"displayport1"` or
+    /// `"usb-c"`. Case, dashes, underscores and spaces are ignored, and common
+    /// aliases (`vga`, `dvi`, `dp`) are accepted.
+    pub fn from_name(s: &str) -> Option<Self> {
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .flat_map(char::to_lowercase)
+            .collect();
+        Some(match name.as_str() {
+            "analog1" | "vga1" | "vga" => InputSource::Analog1,
+            "analog2" | "vga2" => InputSource::Analog2,
+            "digital1" | "dvi1" | "dvi" => InputSource::Digital1,
+            "digital2" | "dvi2" => InputSource::Digital2,
+            "composite1" | "composite" => InputSource::Composite1,
+            "composite2" => InputSource::Composite2,
+            "svideo1" | "svideo" => InputSource::SVideo1,
+            "svideo2" => InputSource::SVideo2,
+            "tuner1" | "tuner" => InputSource::Tuner1,
+            "tuner2" => InputSource::Tuner2,
+            "tuner3" => InputSource::Tuner3,
+            "component1" | "component" => InputSource::Component1,
+            "component2" => InputSource::Component2,
+            "component3" => InputSource::Component3,
+            "displayport1" | "dp1" | "displayport" | "dp" => InputSource::DisplayPort1,
+            "displayport2" | "dp2" => InputSource::DisplayPort2,
+            "hdmi1" | "hdmi" => InputSource::Hdmi1,
+            "hdmi2" => InputSource::Hdmi2,
+            "usbc" | "typec" => InputSource::UsbC(InputSource::USB_C_FALLBACK),
+            _ => return None,
+        })
+    }
+
     pub fn from_value(value: u16) -> Self {
         match value {
             0x01 => InputSource::Analog1,
@@ -267,17 +751,402 @@ impl InputSource {
             InputSource::DisplayPort2 => 0x10,
             InputSource::Hdmi1 => 0x11,
             InputSource::Hdmi2 => 0x12,
-            InputSource::UsbC => 0x13, // Placeholder, often varies
-            InputSource::Unrecognized(v) => *v,
+            InputSource::UsbC(v) | InputSource::Unrecognized(v) => *v,
         }
     }
+
+    /// Guesses the USB-C input from the values `capabilities` lists for VCP
+    /// 0x60. MCCS defines values up to 0x12 (HDMI 2), so the highest listed
+    /// value above that is taken as USB-C (0x1B on many Dell and LG
+    /// monitors). Returns `None` if every listed value is a standard one,
+    /// as on monitors that route USB-C through a DisplayPort value.
+    pub fn resolve_usb_c(capabilities: &Capabilities) -> Option<InputSource> {
+        capabilities
+            .vcp_features
+            .get(&VcpFeature::InputSource.code())?
+            .iter()
+            .copied()
+            .filter(|&v| matches!(InputSource::from_value(v), InputSource::Unrecognized(_)))
+            .max()
+            .map(InputSource::UsbC)
+    }
+
+    /// The input after `current` in the order the monitor lists its inputs
+    /// for VCP 0x60, or the one before it if `forward` is false, wrapping
+    /// around. If `current` is not listed, the first listed input. `None` if
+    /// the capabilities list no inputs.
+    pub fn cycle(capabilities: &Capabilities, current: u16, forward: bool) -> Option<InputSource> {
+        let listed = capabilities
+            .vcp_features
+            .get(&VcpFeature::InputSource.code())
+            .filter(|values| !values.is_empty())?;
+        let next = match listed.iter().position(|&v| v == current) {
+            Some(i) if forward => listed[(i + 1) % listed.len()],
+            Some(i) => listed[(i + listed.len() - 1) % listed.len()],
+            None => listed[0],
+        };
+        Some(InputSource::from_value(next))
+    }
 }
 
 impl fmt::Display for InputSource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            InputSource::UsbC(_) => write!(f, "UsbC"),
             InputSource::Unrecognized(v) => write!(f, "Unknown(0x{:02X})", v),
             _ => write!(f, "{:?}", self),
         }
     }
 }
+
+/// Color temperature presets for VCP 0x14 (Select Color Preset), using the
+/// MCCS value assignments. Monitors typically support only a few of these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorPreset {
+    Srgb,
+    Native,
+    K5000,
+    K6500,
+    K7500,
+    K9300,
+    UserDefined,
+    Custom(u16),
+}
+
+impl ColorPreset {
+    /// Every named preset, in MCCS value order.
+    pub const ALL: [ColorPreset; 7] = [
+        ColorPreset::Srgb,
+        ColorPreset::Native,
+        ColorPreset::K5000,
+        ColorPreset::K6500,
+        ColorPreset::K7500,
+        ColorPreset::K9300,
+        ColorPreset::UserDefined,
+    ];
+
+    /// Parses a preset name such as `"srgb"`, `"native"`, `"6500k"` or
+    /// `"user"`. Case, dashes, underscores and spaces are ignored.
+    pub fn from_name(s: &str) -> Option<Self> {
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .flat_map(char::to_lowercase)
+            .collect();
+        Some(match name.as_str() {
+            "srgb" => ColorPreset::Srgb,
+            "native" | "displaynative" => ColorPreset::Native,
+            "5000k" | "k5000" => ColorPreset::K5000,
+            "6500k" | "k6500" => ColorPreset::K6500,
+            "7500k" | "k7500" => ColorPreset::K7500,
+            "9300k" | "k9300" => ColorPreset::K9300,
+            "user" | "user1" | "userdefined" => ColorPreset::UserDefined,
+            _ => return None,
+        })
+    }
+
+    pub fn from_value(value: u16) -> Self {
+        match value {
+            0x01 => ColorPreset::Srgb,
+            0x02 => ColorPreset::Native,
+            0x04 => ColorPreset::K5000,
+            0x05 => ColorPreset::K6500,
+            0x06 => ColorPreset::K7500,
+            0x08 => ColorPreset::K9300,
+            0x0B => ColorPreset::UserDefined,
+            v => ColorPreset::Custom(v),
+        }
+    }
+
+    pub fn value(&self) -> u16 {
+        match self {
+            ColorPreset::Srgb => 0x01,
+            ColorPreset::Native => 0x02,
+            ColorPreset::K5000 => 0x04,
+            ColorPreset::K6500 => 0x05,
+            ColorPreset::K7500 => 0x06,
+            ColorPreset::K9300 => 0x08,
+            ColorPreset::UserDefined => 0x0B,
+            ColorPreset::Custom(v) => *v,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorPreset::Srgb => "sRGB",
+            ColorPreset::Native => "Native",
+            ColorPreset::K5000 => "5000K",
+            ColorPreset::K6500 => "6500K",
+            ColorPreset::K7500 => "7500K",
+            ColorPreset::K9300 => "9300K",
+            ColorPreset::UserDefined => "User",
+            ColorPreset::Custom(_) => "Custom",
+        }
+    }
+}
+
+impl fmt::Display for ColorPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorPreset::Custom(v) => write!(f, "Custom(0x{:02X})", v),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+/// Picture modes for VCP 0xDC (Display Application), using the MCCS value
+/// assignments. Vendors often add their own modes (such as sRGB) outside
+/// this table; those are kept as `Custom`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PicturePreset {
+    Standard,
+    Productivity,
+    Mixed,
+    Movie,
+    UserDefined,
+    Game,
+    Sports,
+    Professional,
+    StandardIntermediatePower,
+    StandardLowPower,
+    Demonstration,
+    DynamicContrast,
+    Custom(u16),
+}
+
+impl PicturePreset {
+    /// Every named preset, in MCCS value order.
+    pub const ALL: [PicturePreset; 12] = [
+        PicturePreset::Standard,
+        PicturePreset::Productivity,
+        PicturePreset::Mixed,
+        PicturePreset::Movie,
+        PicturePreset::UserDefined,
+        PicturePreset::Game,
+        PicturePreset::Sports,
+        PicturePreset::Professional,
+        PicturePreset::StandardIntermediatePower,
+        PicturePreset::StandardLowPower,
+        PicturePreset::Demonstration,
+        PicturePreset::DynamicContrast,
+    ];
+
+    /// Parses a preset name such as `"movie"`, `"game"` or `"user"`. Case,
+    /// dashes, underscores and spaces are ignored.
+    pub fn from_name(s: &str) -> Option<Self> {
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .flat_map(char::to_lowercase)
+            .collect();
+        Some(match name.as_str() {
+            "standard" | "default" => PicturePreset::Standard,
+            "productivity" => PicturePreset::Productivity,
+            "mixed" => PicturePreset::Mixed,
+            "movie" => PicturePreset::Movie,
+            "user" | "userdefined" => PicturePreset::UserDefined,
+            "game" | "games" => PicturePreset::Game,
+            "sports" => PicturePreset::Sports,
+            "professional" => PicturePreset::Professional,
+            "intermediatepower" => PicturePreset::StandardIntermediatePower,
+            "lowpower" => PicturePreset::StandardLowPower,
+            "demo" | "demonstration" => PicturePreset::Demonstration,
+            "dynamiccontrast" => PicturePreset::DynamicContrast,
+            _ => return None,
+        })
+    }
+
+    pub fn from_value(value: u16) -> Self {
+        match value {
+            0x00 => PicturePreset::Standard,
+            0x01 => PicturePreset::Productivity,
+            0x02 => PicturePreset::Mixed,
+            0x03 => PicturePreset::Movie,
+            0x04 => PicturePreset::UserDefined,
+            0x05 => PicturePreset::Game,
+            0x06 => PicturePreset::Sports,
+            0x07 => PicturePreset::Professional,
+            0x08 => PicturePreset::StandardIntermediatePower,
+            0x09 => PicturePreset::StandardLowPower,
+            0x0A => PicturePreset::Demonstration,
+            0xF0 => PicturePreset::DynamicContrast,
+            v => PicturePreset::Custom(v),
+        }
+    }
+
+    pub fn value(&self) -> u16 {
+        match self {
+            PicturePreset::Standard => 0x00,
+            PicturePreset::Productivity => 0x01,
+            PicturePreset::Mixed => 0x02,
+            PicturePreset::Movie => 0x03,
+            PicturePreset::UserDefined => 0x04,
+            PicturePreset::Game => 0x05,
+            PicturePreset::Sports => 0x06,
+            PicturePreset::Professional => 0x07,
+            PicturePreset::StandardIntermediatePower => 0x08,
+            PicturePreset::StandardLowPower => 0x09,
+            PicturePreset::Demonstration => 0x0A,
+            PicturePreset::DynamicContrast => 0xF0,
+            PicturePreset::Custom(v) => *v,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PicturePreset::Standard => "Standard",
+            PicturePreset::Productivity => "Productivity",
+            PicturePreset::Mixed => "Mixed",
+            PicturePreset::Movie => "Movie",
+            PicturePreset::UserDefined => "User",
+            PicturePreset::Game => "Game",
+            PicturePreset::Sports => "Sports",
+            PicturePreset::Professional => "Professional",
+            PicturePreset::StandardIntermediatePower => "Intermediate Power",
+            PicturePreset::StandardLowPower => "Low Power",
+            PicturePreset::Demonstration => "Demonstration",
+            PicturePreset::DynamicContrast => "Dynamic Contrast",
+            PicturePreset::Custom(_) => "Custom",
+        }
+    }
+}
+
+impl fmt::Display for PicturePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PicturePreset::Custom(v) => write!(f, "Custom(0x{:02X})", v),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_feature_names() {
+        assert_eq!(
+            "brightness".parse::<VcpFeature>().unwrap(),
+            VcpFeature::Brightness
+        );
+        assert_eq!(
+            "input".parse::<VcpFeature>().unwrap(),
+            VcpFeature::InputSource
+        );
+        assert_eq!(
+            "h-size".parse::<VcpFeature>().unwrap(),
+            VcpFeature::HorizontalSize
+        );
+    }
+
+    #[test]
+    fn names_ignore_case() {
+        assert_eq!(
+            "Contrast".parse::<VcpFeature>().unwrap(),
+            VcpFeature::Contrast
+        );
+        assert_eq!(
+            "RED-GAIN".parse::<VcpFeature>().unwrap(),
+            VcpFeature::RedGain
+        );
+    }
+
+    #[test]
+    fn color_temp_and_color_preset_are_the_same_feature() {
+        assert_eq!(
+            "color-temp".parse::<VcpFeature>().unwrap(),
+            VcpFeature::ColorPreset
+        );
+        assert_eq!(
+            "color-preset".parse::<VcpFeature>().unwrap(),
+            VcpFeature::ColorPreset
+        );
+    }
+
+    #[test]
+    fn hex_codes_of_known_features_give_named_variant() {
+        assert_eq!(
+            "0x10".parse::<VcpFeature>().unwrap(),
+            VcpFeature::Brightness
+        );
+        assert_eq!("0X62".parse::<VcpFeature>().unwrap(), VcpFeature::Volume);
+    }
+
+    #[test]
+    fn other_hex_codes_are_custom() {
+        assert_eq!(
+            "0xDC".parse::<VcpFeature>().unwrap(),
+            VcpFeature::Custom(0xDC)
+        );
+        assert_eq!(
+            "0x0e".parse::<VcpFeature>().unwrap(),
+            VcpFeature::Custom(0x0E)
+        );
+    }
+
+    #[test]
+    fn decimal_codes_are_accepted() {
+        assert_eq!("16".parse::<VcpFeature>().unwrap(), VcpFeature::Brightness);
+        assert_eq!(
+            "220".parse::<VcpFeature>().unwrap(),
+            VcpFeature::Custom(0xDC)
+        );
+    }
+
+    #[test]
+    fn unknown_names_are_not_supported() {
+        let err = "warmth".parse::<VcpFeature>().unwrap_err();
+        assert!(matches!(err, DisplayError::FeatureNotSupported(_)));
+        assert!(err.to_string().contains("Unknown feature: warmth"));
+    }
+
+    #[test]
+    fn invalid_hex_codes_are_rejected() {
+        for s in ["0x", "0xZZ", "0x100"] {
+            let err = s.parse::<VcpFeature>().unwrap_err();
+            assert!(matches!(err, DisplayError::FeatureNotSupported(_)), "{}", s);
+        }
+        assert!("256".parse::<VcpFeature>().is_err());
+    }
+
+    #[test]
+    fn signal_frequencies_are_read_only() {
+        assert!(VcpFeature::HorizontalFrequency.is_typically_read_only());
+        assert!(VcpFeature::VerticalFrequency.is_typically_read_only());
+    }
+
+    #[test]
+    fn status_codes_are_read_only() {
+        // Usage time, firmware level and VCP version.
+        for code in [0xC0, 0xC9, 0xDF] {
+            assert!(
+                VcpFeature::from_code(code).is_typically_read_only(),
+                "0x{:02X}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn settings_are_writable() {
+        for feature in [
+            VcpFeature::Brightness,
+            VcpFeature::InputSource,
+            VcpFeature::PowerMode,
+            VcpFeature::Custom(0xDC),
+            VcpFeature::Custom(0x04),
+        ] {
+            assert!(!feature.is_typically_read_only(), "{:?}", feature);
+        }
+    }
+
+    #[test]
+    fn known_features_round_trip_through_their_code() {
+        for &feature in VcpFeature::ALL_KNOWN {
+            assert!(!matches!(feature, VcpFeature::Custom(_)));
+            let hex = format!("0x{:02X}", feature.code());
+            assert_eq!(hex.parse::<VcpFeature>().unwrap(), feature);
+        }
+    }
+}
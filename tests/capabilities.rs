@@ -0,0 +1,124 @@
+use dispman::capabilities::Capabilities;
+use dispman::output::{CsvFormatter, OutputFormatter};
+use proptest::collection::{btree_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+/// The parsed fields of `caps`, i.e. everything but `raw`.
+type Fields = (
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Vec<String>,
+    HashMap<u8, Vec<u16>>,
+    Option<String>,
+);
+
+fn fields(caps: &Capabilities) -> Fields {
+    (
+        caps.protocol.clone(),
+        caps.display_type.clone(),
+        caps.model.clone(),
+        caps.commands.clone(),
+        caps.vcp_features.clone(),
+        caps.mccs_version.clone(),
+    )
+}
+
+fn assert_round_trips(caps: &Capabilities) {
+    let rebuilt = String::from(caps);
+    let reparsed = Capabilities::parse(&rebuilt);
+    assert_eq!(fields(&reparsed), fields(caps), "rebuilt as {}", rebuilt);
+}
+
+/// Capabilities strings with the quirks monitors are known for: lowercase
+/// hex, missing spaces, extra keys, no outer parentheses.
+const SAMPLES: [&str; 10] = [
+    "(prot(monitor)type(LCD)model(U2415)cmds(01 02 03 07 0C E3 F3)vcp(02 04 05 08 10 12 14(01 04 05 06 08 09 0B 0C) 16 18 1A 52 60(01 0F 11) AA(01 02) AC AE B2 B6 C6 C8 C9 D6(01 04 05) DC(00 02 03 05) DF E0 E1 E2(00 01 02 04 0E 12 14 19 1D) F0(00 08) F1(01 02) F2 FD)mscc_ver(2.1)mccs_ver(2.1))",
+    "(prot(monitor)type(lcd)model(LG ULTRAFINE)cmds(01 02 03 0C E3 F3)vcp(02 04 05 08 10 12 14(05 08 0B) 16 18 1A 60(0F 10 11 12) 62 8D(01 02) D6(01 04) DF)mccs_ver(2.1))",
+    "(prot(monitor)type(lcd)model(S27R65)cmds(01 02 03 07 0C E3 F3)vcp(02 04 05 08 10 12 14(05 08 0B 0C) 16 18 1A 52 60(0F 11 ) AA(01 02 03 FF) AC AE B2 B6 C6 C8 C9 D6(01 04 05) DC(00 01 02 03 04) DF FD)mccs_ver(2.1)mswhql(1))",
+    "(prot(monitor)type(LCD)model(VG27A)cmds(01 02 03 07 0C F3)vcp(02 04 05 08 0B 0C 10 12 14(05 06 08 0B) 16 18 1A 60(01 03 11 0F) 62 6C 6E 70 87 8D(01 02) A8 AC AE B6 C6 C8 C9 CC(01 02 03 04 05 06 07 08 09 0A 0C 0D 11 12 14 1A 1E 1F 23 72 73) D6(01 04) DF)mswhql(1)asset_eep(40)mccs_ver(2.2))",
+    "(prot(monitor)type(lcd)model(27GL850)cmds(01 02 03 0c e3 f3)vcp(02 04 05 08 10 12 14(05 08 0b) 16 18 1a 60(11 12 0f 10) 62 8d(01 02) d6(01 04) df)mccs_ver(2.1))",
+    "(prot(monitor)type(LCD)model(P2419H)cmds(01 02 03 07 0C E3 F3)vcp(02 04 05 08 10 12 14(05 08 0B 0C) 16 18 1A 52 60(01 0F 11) AA(01 02 04) AC AE B2 B6 C6 C8 C9 CA(01 02) CC(02 03 04 06 09 0A 0D 0E) D6(01 04 05) DC(00 03 05) DF E0 E1 E2(00 1D 01 02 04 0E 12 14 19) F0(00 08) F1(01 02) F2 FD)mccs_ver(2.1)mswhql(1))",
+    "(prot(monitor)type(LCD)model(ACER)cmds(01 02 03 07 0C E3 F3)vcp(04 10 12 14(05 06 08 0B) 16 18 1A 59 5A 5B 5C 5D 5E 60(01 03 0F 11) 62 9B 9C 9D 9E 9F A0 D6(01 04 05) E0(00 01 02 03 04 05 06) E1(00 01) E3 E4)mccs_ver(2.2))",
+    "prot(monitor)type(LCD)model(BenQ GW2780)cmds(01 02 03 07 0C F3)vcp(02 04 05 08 10 12 14(04 05 08 0B) 16 18 1A 60(01 03 0F) 62 8D(01 02) D6(01 05))mccs_ver(2.2)",
+    "(prot(monitor) type(LCD) model(PHL 276E8V) cmds(01 02 03 07 0C E3 F3) vcp(02 04 05 08 0B 0C 10 12 14(01 05 06 08 0B) 16 18 1A 52 60(0F 11 12) 62 6C 6E 70 8D(01 02) D6(01 04 05) DC(00 01 02 03 04 05) DF) mccs_ver(2.2))",
+    "(prot(monitor)type(LCD)model(HP Z27)cmds(01 02 03 07 0C E3 F3)vcp(02 04 05 08 0B 0C 10 12 14(01 02 05 08 0B) 16 18 1A 52 60(0F 11 12 13) 62 6C 6E 70 86(01 02 05) 87 AC AE B6 C0 C6 C8 C9 CA(01 02) CC(01 02 03 04 05 06 07 08 09 0A 0C 0D 11 12 14 1A 1E 1F 20 24 4D) D6(01 04 05) DC(00 03 04 05 08) DF)mccs_ver(2.2)vcpname(10(Brightness)))",
+];
+
+#[test]
+fn samples_round_trip() {
+    for raw in SAMPLES {
+        let caps = Capabilities::parse(raw);
+        assert!(caps.vcp_features.contains_key(&0x10), "{}", raw);
+        assert_round_trips(&caps);
+    }
+}
+
+#[test]
+fn samples_keep_nested_values() {
+    let caps = Capabilities::parse(SAMPLES[0]);
+    assert_eq!(caps.model.as_deref(), Some("U2415"));
+    assert_eq!(caps.vcp_features[&0x60], [0x01, 0x0F, 0x11]);
+    assert_eq!(caps.mccs_version.as_deref(), Some("2.1"));
+}
+
+/// A field value as monitors write them: no parentheses, never empty.
+fn text() -> impl Strategy<Value = String> {
+    "[A-Za-z0-9][A-Za-z0-9 ._-]{0,15}"
+}
+
+prop_compose! {
+    fn capabilities()(
+        protocol in option::of(text()),
+        display_type in option::of(text()),
+        model in option::of(text()),
+        commands in vec("[0-9A-F]{2}", 0..8),
+        vcp_features in btree_map(any::<u8>(), vec(any::<u16>(), 0..6), 0..24),
+        mccs_version in option::of("[0-9]\\.[0-9]"),
+    ) -> Capabilities {
+        Capabilities {
+            protocol,
+            display_type,
+            model,
+            commands,
+            vcp_features: vcp_features.into_iter().collect(),
+            mccs_version,
+            ..Default::default()
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn rebuilt_string_parses_to_same_fields(caps in capabilities()) {
+        assert_round_trips(&caps);
+    }
+
+    #[test]
+    fn parse_never_panics(raw in "\\PC{0,200}") {
+        let caps = Capabilities::parse(&raw);
+        let _ = String::from(&caps);
+    }
+}
+
+#[test]
+fn read_only_features_are_marked() {
+    let caps = Capabilities::parse("(vcp(10 ac df))");
+    let text = caps.to_string();
+    assert!(
+        text.contains("0xAC (Horizontal Frequency) [RO]"),
+        "{}",
+        text
+    );
+    assert!(text.contains("0xDF"));
+    assert!(!text.contains("0x10 (Brightness) [RO]"));
+
+    let csv = CsvFormatter.format_capabilities(&caps).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[0], "code,name,values,writable");
+    assert!(rows[1].starts_with("0x10,") && rows[1].ends_with(",true"));
+    assert!(rows[2].starts_with("0xAC,") && rows[2].ends_with(",false"));
+    assert!(rows[3].starts_with("0xDF,") && rows[3].ends_with(",false"));
+}
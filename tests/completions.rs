@@ -0,0 +1,59 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn completions(shell: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_dispman"))
+        .args(["completions", shell])
+        // Keep a broken user config from failing the test.
+        .env(
+            "DISPMAN_CONFIG",
+            std::env::temp_dir().join("dispman-no-config.toml"),
+        )
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "completions {} failed", shell);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn every_shell_gets_a_script() {
+    for shell in ["bash", "zsh", "fish", "powershell", "elvish"] {
+        let script = completions(shell);
+        assert!(script.contains("dispman"), "{} script: {}", shell, script);
+    }
+}
+
+#[test]
+fn bash_script_registers_the_completion_function() {
+    let script = completions("bash");
+    assert!(script.starts_with("_dispman() {"));
+    assert!(script.contains("complete -F _dispman"));
+}
+
+#[test]
+fn bash_script_completes_features_and_display_ids() {
+    let script = completions("bash");
+    let get = script
+        .lines()
+        .find(|line| line.contains("opts=") && line.contains("--check-support --expect"))
+        .expect("no options line for `get`");
+    for feature in ["brightness", "contrast", "input", "sharpness", "hue"] {
+        assert!(get.contains(feature), "`get` does not complete {}", feature);
+    }
+    assert!(script.contains(r#"compgen -W "0 1 2""#));
+}
+
+#[test]
+fn bash_script_is_valid_bash() {
+    let script = completions("bash");
+    // Only checked where bash is installed.
+    let Ok(mut bash) = Command::new("bash").arg("-n").stdin(Stdio::piped()).spawn() else {
+        return;
+    };
+    bash.stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    assert!(bash.wait().unwrap().success());
+}
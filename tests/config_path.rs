@@ -0,0 +1,52 @@
+use dispman::config::{CONFIG_PATH_ENV, Config};
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn load_reads_file_named_by_env_var() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(
+        &path,
+        "version = 1\n\n[profiles.work.settings]\ndell = [[16, 80]]\n",
+    )
+    .unwrap();
+
+    // The only test in this binary that touches the environment.
+    unsafe { std::env::set_var(CONFIG_PATH_ENV, &path) };
+    assert_eq!(Config::config_path().unwrap(), path);
+    let config = Config::load().unwrap();
+    assert_eq!(config.profiles["work"].settings["dell"], [(0x10, 80)]);
+}
+
+#[test]
+fn config_path_flag_overrides_env_var() {
+    let dir = tempfile::tempdir().unwrap();
+    let from_env = dir.path().join("env.toml");
+    let from_flag = dir.path().join("flag.toml");
+    let output = Command::new(env!("CARGO_BIN_EXE_dispman"))
+        .args(["config", "set", "default_display", "2", "--config-path"])
+        .arg(&from_flag)
+        .env(CONFIG_PATH_ENV, &from_env)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(from_flag.exists());
+    assert!(!from_env.exists());
+}
+
+#[test]
+fn config_path_command_reports_env_var() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let output = Command::new(env!("CARGO_BIN_EXE_dispman"))
+        .args(["config", "path"])
+        .env(CONFIG_PATH_ENV, &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        path.display().to_string()
+    );
+}
@@ -0,0 +1,56 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Runs dispman with `args` against the config file at `config` and returns
+/// its exit code.
+fn exit_code(config: &Path, args: &[&str]) -> i32 {
+    let output = Command::new(env!("CARGO_BIN_EXE_dispman"))
+        .args(args)
+        .env("DISPMAN_CONFIG", config)
+        .env_remove("DISPMAN_DEFAULT_DISPLAY")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    output.status.code().unwrap()
+}
+
+#[test]
+fn unknown_profile_is_a_config_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    assert_eq!(exit_code(&config, &["profile", "load", "nosuch"]), 5);
+    assert_eq!(exit_code(&config, &["profile", "validate", "nosuch"]), 5);
+    assert_eq!(
+        exit_code(&config, &["inspect", "--changes-since", "nosuch"]),
+        5
+    );
+}
+
+#[test]
+fn unknown_setting_is_a_config_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    assert_eq!(exit_code(&config, &["config", "get", "nosuch"]), 5);
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+#[test]
+fn missing_display_is_monitor_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    assert_eq!(
+        exit_code(&config, &["get", "brightness", "--display", "999"]),
+        2
+    );
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[test]
+fn unsupported_platform_exits_with_1() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    assert_eq!(
+        exit_code(&config, &["get", "brightness", "--display", "999"]),
+        1
+    );
+}
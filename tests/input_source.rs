@@ -0,0 +1,161 @@
+use dispman::capabilities::Capabilities;
+use dispman::vcp::InputSource;
+
+#[test]
+fn names_ignore_case_and_separators() {
+    for name in ["hdmi1", "HDMI1", "hdmi-1", "hdmi 1", "Hdmi_1", "hdmi"] {
+        assert_eq!(
+            InputSource::from_name(name),
+            Some(InputSource::Hdmi1),
+            "{}",
+            name
+        );
+    }
+    for name in ["displayport1", "DisplayPort-1", "dp1", "dp-1", "DP 1", "dp"] {
+        assert_eq!(
+            InputSource::from_name(name),
+            Some(InputSource::DisplayPort1),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn aliases_map_to_mccs_sources() {
+    assert_eq!(InputSource::from_name("vga"), Some(InputSource::Analog1));
+    assert_eq!(InputSource::from_name("vga2"), Some(InputSource::Analog2));
+    assert_eq!(InputSource::from_name("dvi"), Some(InputSource::Digital1));
+    assert_eq!(
+        InputSource::from_name("dp2"),
+        Some(InputSource::DisplayPort2)
+    );
+    assert_eq!(InputSource::from_name("HDMI-2"), Some(InputSource::Hdmi2));
+}
+
+#[test]
+fn usb_c_uses_fallback_value() {
+    for name in ["usb-c", "USBC", "usb c", "type-c"] {
+        assert_eq!(
+            InputSource::from_name(name),
+            Some(InputSource::UsbC(InputSource::USB_C_FALLBACK)),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn unknown_names_are_rejected() {
+    for name in ["", "hdmi3", "thunderbolt", "0x11", "hdmi0"] {
+        assert_eq!(InputSource::from_name(name), None, "{}", name);
+    }
+}
+
+#[test]
+fn values_match_mccs() {
+    assert_eq!(InputSource::Analog1.value(), 0x01);
+    assert_eq!(InputSource::Digital1.value(), 0x03);
+    assert_eq!(InputSource::DisplayPort1.value(), 0x0F);
+    assert_eq!(InputSource::DisplayPort2.value(), 0x10);
+    assert_eq!(InputSource::Hdmi1.value(), 0x11);
+    assert_eq!(InputSource::Hdmi2.value(), 0x12);
+}
+
+#[test]
+fn named_sources_round_trip_through_value() {
+    for source in InputSource::ALL {
+        if let InputSource::UsbC(_) = source {
+            continue;
+        }
+        assert_eq!(InputSource::from_value(source.value()), source);
+    }
+}
+
+#[test]
+fn vendor_values_are_unrecognized() {
+    assert_eq!(
+        InputSource::from_value(0x1B),
+        InputSource::Unrecognized(0x1B)
+    );
+    assert_eq!(InputSource::Unrecognized(0x1B).value(), 0x1B);
+    assert_eq!(InputSource::Unrecognized(0x1B).to_string(), "Unknown(0x1B)");
+}
+
+#[test]
+fn usb_c_resolves_to_highest_vendor_value() {
+    let caps = Capabilities::parse("(vcp(60(0F 11 12 1B 19)))");
+    assert_eq!(
+        InputSource::resolve_usb_c(&caps),
+        Some(InputSource::UsbC(0x1B))
+    );
+    let caps = Capabilities::parse("(vcp(60(0F 11 12)))");
+    assert_eq!(InputSource::resolve_usb_c(&caps), None);
+}
+
+fn inputs() -> Capabilities {
+    Capabilities::parse("(prot(monitor)vcp(10 60(0F 11 12)))")
+}
+
+#[test]
+fn cycle_moves_forward_in_listed_order() {
+    let caps = inputs();
+    assert_eq!(
+        InputSource::cycle(&caps, 0x0F, true),
+        Some(InputSource::Hdmi1)
+    );
+    assert_eq!(
+        InputSource::cycle(&caps, 0x11, true),
+        Some(InputSource::Hdmi2)
+    );
+}
+
+#[test]
+fn cycle_wraps_around() {
+    let caps = inputs();
+    assert_eq!(
+        InputSource::cycle(&caps, 0x12, true),
+        Some(InputSource::DisplayPort1)
+    );
+    assert_eq!(
+        InputSource::cycle(&caps, 0x0F, false),
+        Some(InputSource::Hdmi2)
+    );
+}
+
+#[test]
+fn cycle_moves_backward() {
+    assert_eq!(
+        InputSource::cycle(&inputs(), 0x12, false),
+        Some(InputSource::Hdmi1)
+    );
+}
+
+#[test]
+fn cycle_from_unlisted_input_starts_at_first() {
+    assert_eq!(
+        InputSource::cycle(&inputs(), 0x03, true),
+        Some(InputSource::DisplayPort1)
+    );
+}
+
+#[test]
+fn cycle_needs_listed_inputs() {
+    assert_eq!(
+        InputSource::cycle(&Capabilities::parse("(vcp(10 60))"), 0x0F, true),
+        None
+    );
+    assert_eq!(
+        InputSource::cycle(&Capabilities::parse("(vcp(10))"), 0x0F, true),
+        None
+    );
+}
+
+#[test]
+fn cycle_with_single_input_stays() {
+    let caps = Capabilities::parse("(vcp(60(11)))");
+    assert_eq!(
+        InputSource::cycle(&caps, 0x11, true),
+        Some(InputSource::Hdmi1)
+    );
+}
@@ -0,0 +1,179 @@
+use dispman::config::{Config, Profile};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn dispman(config: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dispman"))
+        .args(args)
+        .env("DISPMAN_CONFIG", config)
+        .output()
+        .unwrap()
+}
+
+const SNAPSHOT: &str = r#"{
+  "display_name": "DELL U2722D",
+  "stable_id": "DEL-A1B2",
+  "values": {
+    "0x10": { "current": 70, "max": 100, "is_continuous": true },
+    "0x12": { "current": 55, "max": 100, "is_continuous": true },
+    "0x60": { "current": 15, "max": 18, "is_continuous": false }
+  },
+  "failed_codes": ["0x87"]
+}"#;
+
+#[test]
+fn save_from_snapshot_stores_every_value() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let snapshot = dir.path().join("snapshot.json");
+    std::fs::write(&snapshot, SNAPSHOT).unwrap();
+
+    let output = dispman(
+        &config,
+        &[
+            "profile",
+            "save",
+            "desk",
+            "--from-snapshot",
+            snapshot.to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let saved = Config::load_from(&config).unwrap();
+    assert_eq!(
+        saved.profiles["desk"].settings["DEL-A1B2"],
+        [(0x10, 70), (0x12, 55), (0x60, 15)]
+    );
+}
+
+#[test]
+fn save_from_snapshot_honours_continuous_only_and_exclude() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let snapshot = dir.path().join("snapshot.json");
+    std::fs::write(&snapshot, SNAPSHOT).unwrap();
+
+    let output = dispman(
+        &config,
+        &[
+            "profile",
+            "save",
+            "desk",
+            "--from-snapshot",
+            snapshot.to_str().unwrap(),
+            "--continuous-only",
+            "--exclude",
+            "contrast",
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let saved = Config::load_from(&config).unwrap();
+    let profile = &saved.profiles["desk"];
+    assert_eq!(profile.settings["DEL-A1B2"], [(0x10, 70)]);
+    assert_eq!(profile.excluded_codes, [0x12]);
+}
+
+#[test]
+fn save_from_snapshot_requires_stable_id() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let snapshot = dir.path().join("snapshot.json");
+    std::fs::write(&snapshot, SNAPSHOT.replace("DEL-A1B2", "")).unwrap();
+
+    let output = dispman(
+        &config,
+        &[
+            "profile",
+            "save",
+            "desk",
+            "--from-snapshot",
+            snapshot.to_str().unwrap(),
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not name its monitor"));
+    assert!(!config.exists());
+}
+
+fn work_profile() -> Profile {
+    Profile {
+        settings: HashMap::from([("DEL-A1B2".to_string(), vec![(0x10, 70)])]),
+        created_at: None,
+        excluded_codes: Vec::new(),
+        delay_between_sets_ms: None,
+        hostname: None,
+    }
+}
+
+#[test]
+fn copied_profile_is_independent() {
+    let mut config = Config::default();
+    config.save_profile("work".to_string(), work_profile());
+    config.copy_profile("work", "evening", false).unwrap();
+
+    let evening = config.profiles.get_mut("evening").unwrap();
+    evening
+        .settings
+        .insert("DEL-A1B2".to_string(), vec![(0x10, 20)]);
+    let work = config.profiles.get_mut("work").unwrap();
+    work.excluded_codes.push(0x60);
+
+    let work = config.get_profile("work").unwrap();
+    let evening = config.get_profile("evening").unwrap();
+    assert_eq!(work.settings["DEL-A1B2"], [(0x10, 70)]);
+    assert_eq!(evening.settings["DEL-A1B2"], [(0x10, 20)]);
+    assert!(evening.excluded_codes.is_empty());
+    assert!(evening.created_at.is_some());
+}
+
+#[test]
+fn copy_refuses_to_overwrite_without_force() {
+    let mut config = Config::default();
+    config.save_profile("work".to_string(), work_profile());
+    config.save_profile(
+        "evening".to_string(),
+        Profile {
+            settings: HashMap::new(),
+            ..work_profile()
+        },
+    );
+
+    assert!(config.copy_profile("work", "evening", false).is_err());
+    assert!(config.get_profile("evening").unwrap().settings.is_empty());
+    config.copy_profile("work", "evening", true).unwrap();
+    assert_eq!(
+        config.get_profile("evening").unwrap().settings["DEL-A1B2"],
+        [(0x10, 70)]
+    );
+    assert!(config.copy_profile("missing", "other", true).is_err());
+}
+
+#[test]
+fn copy_and_rename_commands_update_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let mut config = Config::default();
+    config.save_profile("work".to_string(), work_profile());
+    config.save_to(&path).unwrap();
+
+    assert!(
+        dispman(&path, &["profile", "copy", "work", "home"])
+            .status
+            .success()
+    );
+    assert!(
+        dispman(&path, &["profile", "rename", "home", "evening"])
+            .status
+            .success()
+    );
+    let output = dispman(&path, &["profile", "copy", "work", "evening"]);
+    assert_eq!(output.status.code(), Some(5));
+
+    let saved = Config::load_from(&path).unwrap();
+    let mut names: Vec<_> = saved.profiles.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    assert_eq!(names, ["evening", "work"]);
+}